@@ -0,0 +1,7 @@
+/* Small enough for both the LM3S6965 (Cortex-M3) and the nRF51822 (Cortex-M0)
+   emulated by qemu. Adjust it to your board when running on hardware. */
+MEMORY
+{
+  FLASH : ORIGIN = 0x00000000, LENGTH = 128K
+  RAM : ORIGIN = 0x20000000, LENGTH = 16K
+}
@@ -0,0 +1,108 @@
+//! Cycle counts of `fastdivide` on Cortex-M microcontrollers.
+//!
+//! `cargo bench` needs `std`, so it cannot run on thumbv6m / thumbv7m.
+//! This binary measures the same loops with the SysTick timer, which is
+//! available on every Cortex-M core, and prints the results through semihosting.
+//!
+//! ```text
+//! cargo run --release                              # Cortex-M3, thumbv7m
+//! cargo run --release --target thumbv6m-none-eabi  # Cortex-M0, thumbv6m
+//! ```
+//!
+//! The runners configured in `.cargo/config.toml` use qemu, whose timings
+//! are not cycle accurate. For real numbers, flash the binary on a board
+//! (adjusting `memory.x`) and attach a debugger with semihosting enabled.
+#![no_std]
+#![no_main]
+
+use core::hint::black_box;
+use core::panic::PanicInfo;
+
+use cortex_m::peripheral::syst::SystClkSource;
+use cortex_m::peripheral::SYST;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::{debug, hprintln};
+use fastdivide::{DividerU32, DividerU64};
+
+const NUM_ITERS: u32 = 100;
+
+// SysTick is a 24-bit down counter.
+const SYST_RELOAD: u32 = 0x00FF_FFFF;
+
+/// Returns the number of cycles taken by `f`.
+fn cycles<F: FnMut() -> u32>(mut f: F) -> u32 {
+    let start = SYST::get_current();
+    black_box(f());
+    let end = SYST::get_current();
+    start.wrapping_sub(end) & SYST_RELOAD
+}
+
+fn report<F: FnMut() -> u32>(name: &str, overhead: u32, f: F) {
+    let total = cycles(f).saturating_sub(overhead);
+    hprintln!(
+        "{:<24} {:>6} cycles for {} divisions ({}.{:02} per division)",
+        name,
+        total,
+        NUM_ITERS,
+        total / NUM_ITERS,
+        total % NUM_ITERS
+    );
+}
+
+fn bench_divisor(overhead: u32, divisor: u32) {
+    hprintln!("divisor = {}", divisor);
+    let d32 = black_box(divisor);
+    let divider_u32 = DividerU32::divide_by(d32);
+    let n32 = black_box(152_342_341u32);
+    report("u32 /", overhead, || {
+        (0..NUM_ITERS).map(|i| (n32 + i) / d32).sum::<u32>()
+    });
+    report("DividerU32", overhead, || {
+        (0..NUM_ITERS)
+            .map(|i| divider_u32.divide(n32 + i))
+            .sum::<u32>()
+    });
+    let d64 = black_box(divisor as u64);
+    let divider_u64 = DividerU64::divide_by(d64);
+    let n64 = black_box(152_342_341_001u64);
+    report("u64 /", overhead, || {
+        (0..NUM_ITERS as u64).map(|i| (n64 + i) / d64).sum::<u64>() as u32
+    });
+    report("DividerU64", overhead, || {
+        (0..NUM_ITERS as u64)
+            .map(|i| divider_u64.divide(n64 + i))
+            .sum::<u64>() as u32
+    });
+}
+
+#[entry]
+fn main() -> ! {
+    let mut syst = cortex_m::Peripherals::take().unwrap().SYST;
+    syst.set_clock_source(SystClkSource::Core);
+    syst.set_reload(SYST_RELOAD);
+    syst.clear_current();
+    syst.enable_counter();
+
+    // The cost of the loop itself, without any division.
+    let n = black_box(152_342_341u32);
+    let overhead = cycles(|| (0..NUM_ITERS).map(|i| n + i).sum::<u32>());
+    hprintln!("loop overhead: {} cycles", overhead);
+
+    // 7 takes the general path of both dividers, 10 the fast path,
+    // and 1024 is a bit shift.
+    for &divisor in &[7u32, 10, 1024] {
+        bench_divisor(overhead, divisor);
+    }
+    debug::exit(debug::EXIT_SUCCESS);
+    loop {
+        cortex_m::asm::wfi();
+    }
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    debug::exit(debug::EXIT_FAILURE);
+    loop {
+        cortex_m::asm::wfi();
+    }
+}
@@ -0,0 +1,68 @@
+//! Arrow compute kernel, behind the `arrow` feature.
+
+use arrow_array::types::UInt64Type;
+use arrow_array::{Array, PrimitiveArray};
+
+use crate::DividerU64;
+
+impl DividerU64 {
+    /// Returns the array of the quotients of the values of `array`,
+    /// with the same null bitmap.
+    ///
+    /// The null slots are not divided: they hold the value of the slot of `array`.
+    /// See [`DividerU64::divide_valid_in_place`].
+    ///
+    /// ```
+    /// use arrow_array::UInt64Array;
+    /// use fastdivide::DividerU64;
+    ///
+    /// let column = UInt64Array::from(vec![Some(3_600), None, Some(7_300)]);
+    /// let hours = DividerU64::divide_by(3_600).divide_arrow(&column);
+    /// assert_eq!(hours, UInt64Array::from(vec![Some(1), None, Some(2)]));
+    /// ```
+    pub fn divide_arrow(&self, array: &PrimitiveArray<UInt64Type>) -> PrimitiveArray<UInt64Type> {
+        let mut values = array.values().to_vec();
+        match array.nulls() {
+            // `sliced` moves the bit of the first value to the bit 0 of the
+            // first byte, copying the bitmap only if it is not aligned.
+            Some(nulls) => self.divide_valid_in_place(&mut values, &nulls.inner().sliced()),
+            None => self.divide_slice_in_place(&mut values),
+        }
+        PrimitiveArray::new(values.into(), array.nulls().cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::DividerU64;
+    use arrow_array::{Array, UInt64Array};
+    use std::vec::Vec;
+
+    #[test]
+    fn test_divide_arrow() {
+        let divider = DividerU64::divide_by(7);
+        let values: Vec<Option<u64>> = (0..200u64)
+            .map(|i| {
+                if i % 3 == 0 || (64..130).contains(&i) {
+                    None
+                } else {
+                    Some(i * 100)
+                }
+            })
+            .collect();
+        let array = UInt64Array::from(values.clone());
+        for offset in [0, 1, 8, 13] {
+            let sliced = array.slice(offset, array.len() - offset - 5);
+            let quotients = divider.divide_arrow(&sliced);
+            let expected: UInt64Array = values[offset..offset + sliced.len()]
+                .iter()
+                .map(|n| n.map(|n| n / 7))
+                .collect();
+            assert_eq!(quotients, expected);
+            assert_eq!(quotients.nulls(), sliced.nulls());
+        }
+        let quotients = divider.divide_arrow(&UInt64Array::from(vec![7, 14, u64::MAX]));
+        assert_eq!(quotients, UInt64Array::from(vec![1, 2, u64::MAX / 7]));
+        assert!(quotients.nulls().is_none());
+    }
+}
@@ -0,0 +1,277 @@
+//! AVX2 implementation of the slice kernels.
+//!
+//! AVX2 has no 64-bit multiplication, so the high multiplication is emulated,
+//! like libdivide does, with four `_mm256_mul_epu32` 32x32 bits multiplications.
+
+use core::arch::x86_64::*;
+
+use crate::DividerU64;
+
+const LANES: usize = 4;
+
+/// Returns true if the AVX2 kernels can be used on the current CPU.
+///
+/// Without the `std` feature, this is only known at compile time.
+#[inline]
+pub(crate) fn is_available() -> bool {
+    #[cfg(any(feature = "std", test))]
+    {
+        std::is_x86_feature_detected!("avx2")
+    }
+    #[cfg(not(any(feature = "std", test)))]
+    {
+        cfg!(target_feature = "avx2")
+    }
+}
+
+/// Returns the high 64 bits of the product of each lane of `n`
+/// with the factor whose 32-bits halves are splat in `factor_lo` and `factor_hi`.
+#[inline]
+#[target_feature(enable = "avx2")]
+pub(crate) unsafe fn mullhi(factor_lo: __m256i, factor_hi: __m256i, n: __m256i) -> __m256i {
+    let mask = _mm256_set1_epi64x(u32::MAX as i64);
+    let n_hi = _mm256_srli_epi64::<32>(n);
+    let lo_lo = _mm256_mul_epu32(factor_lo, n);
+    let hi_lo = _mm256_mul_epu32(factor_hi, n);
+    let lo_hi = _mm256_mul_epu32(factor_lo, n_hi);
+    let hi_hi = _mm256_mul_epu32(factor_hi, n_hi);
+    // None of these additions can overflow.
+    let cross = _mm256_add_epi64(
+        _mm256_add_epi64(
+            _mm256_srli_epi64::<32>(lo_lo),
+            _mm256_and_si256(hi_lo, mask),
+        ),
+        lo_hi,
+    );
+    _mm256_add_epi64(
+        _mm256_add_epi64(hi_hi, _mm256_srli_epi64::<32>(hi_lo)),
+        _mm256_srli_epi64::<32>(cross),
+    )
+}
+
+/// Returns the low 64 bits of the product of each lane of `n`
+/// with the factor whose 32-bits halves are splat in `factor_lo` and `factor_hi`.
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn mullo(factor_lo: __m256i, factor_hi: __m256i, n: __m256i) -> __m256i {
+    let n_hi = _mm256_srli_epi64::<32>(n);
+    let lo_lo = _mm256_mul_epu32(factor_lo, n);
+    let cross = _mm256_add_epi64(
+        _mm256_mul_epu32(factor_hi, n),
+        _mm256_mul_epu32(factor_lo, n_hi),
+    );
+    _mm256_add_epi64(lo_lo, _mm256_slli_epi64::<32>(cross))
+}
+
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn split(factor: u64) -> (__m256i, __m256i) {
+    (
+        _mm256_set1_epi64x((factor & u32::MAX as u64) as i64),
+        _mm256_set1_epi64x((factor >> 32) as i64),
+    )
+}
+
+/// Runs `$body` with `$divide` bound to a closure dividing `LANES` values
+/// by `$divider`, specialized for the algorithm of the divider.
+///
+/// Must be expanded within a function with the `avx2` target feature.
+macro_rules! with_avx2_divide_fn {
+    ($divider:expr, $divide:ident => $body:expr) => {
+        match $divider {
+            DividerU64::Fast { magic, shift } => {
+                let (magic_lo, magic_hi) = split(magic);
+                let shift = _mm_cvtsi64_si128(shift as i64);
+                let $divide =
+                    move |n: __m256i| _mm256_srl_epi64(mullhi(magic_lo, magic_hi, n), shift);
+                $body
+            }
+            DividerU64::BitShift(shift) => {
+                let shift = _mm_cvtsi64_si128(shift as i64);
+                let $divide = move |n: __m256i| _mm256_srl_epi64(n, shift);
+                $body
+            }
+            DividerU64::Identity => {
+                let $divide = |n: __m256i| n;
+                $body
+            }
+            DividerU64::Compare(divisor) => {
+                // AVX2 only has a signed comparison. Flipping the sign bits
+                // turns it into an unsigned one.
+                let sign = _mm256_set1_epi64x(i64::MIN);
+                let threshold = _mm256_set1_epi64x(((divisor - 1) ^ (1 << 63)) as i64);
+                let $divide = move |n: __m256i| {
+                    let greater = _mm256_cmpgt_epi64(_mm256_xor_si256(n, sign), threshold);
+                    _mm256_srli_epi64::<63>(greater)
+                };
+                $body
+            }
+            DividerU64::PreShift {
+                magic,
+                pre_shift,
+                shift,
+            } => {
+                let (magic_lo, magic_hi) = split(magic);
+                let pre_shift = _mm_cvtsi64_si128(pre_shift as i64);
+                let shift = _mm_cvtsi64_si128(shift as i64);
+                let $divide = move |n: __m256i| {
+                    let q = mullhi(magic_lo, magic_hi, _mm256_srl_epi64(n, pre_shift));
+                    _mm256_srl_epi64(q, shift)
+                };
+                $body
+            }
+            DividerU64::General { magic_low, shift } => {
+                let (magic_lo, magic_hi) = split(magic_low);
+                let shift = _mm_cvtsi64_si128(shift as i64);
+                let $divide = move |n: __m256i| {
+                    let q = mullhi(magic_lo, magic_hi, n);
+                    let t = _mm256_add_epi64(_mm256_srli_epi64::<1>(_mm256_sub_epi64(n, q)), q);
+                    _mm256_srl_epi64(t, shift)
+                };
+                $body
+            }
+        }
+    };
+}
+
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn map_slice(
+    f: impl Fn(__m256i) -> __m256i,
+    f_scalar: impl Fn(u64) -> u64,
+    src: &[u64],
+    dst: &mut [u64],
+) {
+    assert_eq!(src.len(), dst.len());
+    let mut src_chunks = src.chunks_exact(LANES);
+    let mut dst_chunks = dst.chunks_exact_mut(LANES);
+    for (src_chunk, dst_chunk) in (&mut src_chunks).zip(&mut dst_chunks) {
+        let n = _mm256_loadu_si256(src_chunk.as_ptr() as *const __m256i);
+        _mm256_storeu_si256(dst_chunk.as_mut_ptr() as *mut __m256i, f(n));
+    }
+    let src_tail = src_chunks.remainder();
+    for (dst_val, &src_val) in dst_chunks.into_remainder().iter_mut().zip(src_tail) {
+        *dst_val = f_scalar(src_val);
+    }
+}
+
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn map_slice_in_place(
+    f: impl Fn(__m256i) -> __m256i,
+    f_scalar: impl Fn(u64) -> u64,
+    values: &mut [u64],
+) {
+    let mut chunks = values.chunks_exact_mut(LANES);
+    for chunk in &mut chunks {
+        let ptr = chunk.as_mut_ptr() as *mut __m256i;
+        _mm256_storeu_si256(ptr, f(_mm256_loadu_si256(ptr)));
+    }
+    for val in chunks.into_remainder() {
+        *val = f_scalar(*val);
+    }
+}
+
+/// # Safety
+///
+/// The CPU must support AVX2.
+#[target_feature(enable = "avx2")]
+pub(crate) unsafe fn divide_slice(divider: DividerU64, src: &[u64], dst: &mut [u64]) {
+    with_avx2_divide_fn!(divider, divide => map_slice(divide, |n| divider.divide(n), src, dst))
+}
+
+/// # Safety
+///
+/// The CPU must support AVX2.
+#[target_feature(enable = "avx2")]
+pub(crate) unsafe fn divide_slice_in_place(divider: DividerU64, values: &mut [u64]) {
+    with_avx2_divide_fn!(divider, divide => map_slice_in_place(divide, |n| divider.divide(n), values))
+}
+
+/// # Safety
+///
+/// The CPU must support AVX2.
+#[target_feature(enable = "avx2")]
+pub(crate) unsafe fn mod_slice(divider: DividerU64, divisor: u64, src: &[u64], dst: &mut [u64]) {
+    let (divisor_lo, divisor_hi) = split(divisor);
+    with_avx2_divide_fn!(divider, divide => map_slice(
+        |n| _mm256_sub_epi64(n, mullo(divisor_lo, divisor_hi, divide(n))),
+        |n| n - divider.divide(n) * divisor,
+        src,
+        dst,
+    ))
+}
+
+/// # Safety
+///
+/// The CPU must support AVX2.
+#[target_feature(enable = "avx2")]
+pub(crate) unsafe fn mod_slice_in_place(divider: DividerU64, divisor: u64, values: &mut [u64]) {
+    let (divisor_lo, divisor_hi) = split(divisor);
+    with_avx2_divide_fn!(divider, divide => map_slice_in_place(
+        |n| _mm256_sub_epi64(n, mullo(divisor_lo, divisor_hi, divide(n))),
+        |n| n - divider.divide(n) * divisor,
+        values,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    fn numerators() -> Vec<u64> {
+        (0u64..1_000)
+            .chain([
+                u64::MAX - 1,
+                u64::MAX,
+                1 << 63,
+                (1 << 63) - 1,
+                234234131223u64,
+            ])
+            .collect()
+    }
+
+    #[test]
+    fn test_avx2_kernels() {
+        if !is_available() {
+            return;
+        }
+        let src = numerators();
+        let divisors = (1u64..300).chain((0..64).map(|i| 1 << i)).chain([
+            14,
+            234234131223u64,
+            (1 << 63) + 1,
+            u64::MAX,
+        ]);
+        for d in divisors {
+            let divider = DividerU64::divide_by(d);
+            let mut quotients = vec![0u64; src.len()];
+            unsafe { divide_slice(divider, &src, &mut quotients) };
+            let expected: Vec<u64> = src.iter().map(|n| n / d).collect();
+            assert_eq!(quotients, expected);
+            let mut remainders = src.clone();
+            unsafe { mod_slice_in_place(divider, d, &mut remainders) };
+            let expected: Vec<u64> = src.iter().map(|n| n % d).collect();
+            assert_eq!(remainders, expected);
+        }
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_avx2_mullhi_proptest(factor: u64, n: [u64; LANES]) {
+            if !is_available() {
+                return Ok(());
+            }
+            let mut res = [0u64; LANES];
+            unsafe {
+                let (factor_lo, factor_hi) = split(factor);
+                let n = _mm256_loadu_si256(n.as_ptr() as *const __m256i);
+                _mm256_storeu_si256(res.as_mut_ptr() as *mut __m256i, mullhi(factor_lo, factor_hi, n));
+            }
+            for (&n, &res) in n.iter().zip(&res) {
+                assert_eq!(res, ((factor as u128 * n as u128) >> 64) as u64);
+            }
+        }
+    }
+}
@@ -0,0 +1,266 @@
+//! AVX-512 implementation of the slice kernels.
+//!
+//! This is the AVX2 kernel widened to 8 lanes: the high multiplication is still
+//! emulated with four `_mm512_mul_epu32` 32-bit products. AVX-512DQ provides
+//! the low 64-bit multiplication (`vpmullq`) used to compute remainders,
+//! and AVX-512F provides unsigned comparisons.
+//!
+//! AVX-512 IFMA (`vpmadd52huq`) is not used: it multiplies 52-bit operands,
+//! and splitting 64-bit operands into 52-bit limbs takes as many instructions
+//! as the 32-bit products.
+
+use core::arch::x86_64::*;
+
+use crate::DividerU64;
+
+const LANES: usize = 8;
+
+/// Returns true if the AVX-512 kernels can be used on the current CPU.
+///
+/// Without the `std` feature, this is only known at compile time.
+#[inline]
+pub(crate) fn is_available() -> bool {
+    #[cfg(any(feature = "std", test))]
+    {
+        std::is_x86_feature_detected!("avx512f") && std::is_x86_feature_detected!("avx512dq")
+    }
+    #[cfg(not(any(feature = "std", test)))]
+    {
+        cfg!(all(target_feature = "avx512f", target_feature = "avx512dq"))
+    }
+}
+
+/// Returns the high 64 bits of the product of each lane of `n`
+/// with the factor whose 32-bits halves are splat in `factor_lo` and `factor_hi`.
+#[inline]
+#[target_feature(enable = "avx512f")]
+unsafe fn mullhi(factor_lo: __m512i, factor_hi: __m512i, n: __m512i) -> __m512i {
+    let mask = _mm512_set1_epi64(u32::MAX as i64);
+    let n_hi = _mm512_srli_epi64::<32>(n);
+    let lo_lo = _mm512_mul_epu32(factor_lo, n);
+    let hi_lo = _mm512_mul_epu32(factor_hi, n);
+    let lo_hi = _mm512_mul_epu32(factor_lo, n_hi);
+    let hi_hi = _mm512_mul_epu32(factor_hi, n_hi);
+    // None of these additions can overflow.
+    let cross = _mm512_add_epi64(
+        _mm512_add_epi64(
+            _mm512_srli_epi64::<32>(lo_lo),
+            _mm512_and_si512(hi_lo, mask),
+        ),
+        lo_hi,
+    );
+    _mm512_add_epi64(
+        _mm512_add_epi64(hi_hi, _mm512_srli_epi64::<32>(hi_lo)),
+        _mm512_srli_epi64::<32>(cross),
+    )
+}
+
+#[inline]
+#[target_feature(enable = "avx512f")]
+unsafe fn split(factor: u64) -> (__m512i, __m512i) {
+    (
+        _mm512_set1_epi64((factor & u32::MAX as u64) as i64),
+        _mm512_set1_epi64((factor >> 32) as i64),
+    )
+}
+
+/// Runs `$body` with `$divide` bound to a closure dividing `LANES` values
+/// by `$divider`, specialized for the algorithm of the divider.
+///
+/// Must be expanded within a function with the `avx512f` target feature.
+macro_rules! with_avx512_divide_fn {
+    ($divider:expr, $divide:ident => $body:expr) => {
+        match $divider {
+            DividerU64::Fast { magic, shift } => {
+                let (magic_lo, magic_hi) = split(magic);
+                let shift = _mm_cvtsi64_si128(shift as i64);
+                let $divide =
+                    move |n: __m512i| _mm512_srl_epi64(mullhi(magic_lo, magic_hi, n), shift);
+                $body
+            }
+            DividerU64::BitShift(shift) => {
+                let shift = _mm_cvtsi64_si128(shift as i64);
+                let $divide = move |n: __m512i| _mm512_srl_epi64(n, shift);
+                $body
+            }
+            DividerU64::Identity => {
+                let $divide = |n: __m512i| n;
+                $body
+            }
+            DividerU64::Compare(divisor) => {
+                let divisor = _mm512_set1_epi64(divisor as i64);
+                let one = _mm512_set1_epi64(1);
+                let $divide = move |n: __m512i| {
+                    _mm512_maskz_mov_epi64(_mm512_cmpge_epu64_mask(n, divisor), one)
+                };
+                $body
+            }
+            DividerU64::PreShift {
+                magic,
+                pre_shift,
+                shift,
+            } => {
+                let (magic_lo, magic_hi) = split(magic);
+                let pre_shift = _mm_cvtsi64_si128(pre_shift as i64);
+                let shift = _mm_cvtsi64_si128(shift as i64);
+                let $divide = move |n: __m512i| {
+                    let q = mullhi(magic_lo, magic_hi, _mm512_srl_epi64(n, pre_shift));
+                    _mm512_srl_epi64(q, shift)
+                };
+                $body
+            }
+            DividerU64::General { magic_low, shift } => {
+                let (magic_lo, magic_hi) = split(magic_low);
+                let shift = _mm_cvtsi64_si128(shift as i64);
+                let $divide = move |n: __m512i| {
+                    let q = mullhi(magic_lo, magic_hi, n);
+                    let t = _mm512_add_epi64(_mm512_srli_epi64::<1>(_mm512_sub_epi64(n, q)), q);
+                    _mm512_srl_epi64(t, shift)
+                };
+                $body
+            }
+        }
+    };
+}
+
+#[inline]
+#[target_feature(enable = "avx512f")]
+unsafe fn map_slice(
+    f: impl Fn(__m512i) -> __m512i,
+    f_scalar: impl Fn(u64) -> u64,
+    src: &[u64],
+    dst: &mut [u64],
+) {
+    assert_eq!(src.len(), dst.len());
+    let mut src_chunks = src.chunks_exact(LANES);
+    let mut dst_chunks = dst.chunks_exact_mut(LANES);
+    for (src_chunk, dst_chunk) in (&mut src_chunks).zip(&mut dst_chunks) {
+        let n = _mm512_loadu_si512(src_chunk.as_ptr() as *const __m512i);
+        _mm512_storeu_si512(dst_chunk.as_mut_ptr() as *mut __m512i, f(n));
+    }
+    let src_tail = src_chunks.remainder();
+    for (dst_val, &src_val) in dst_chunks.into_remainder().iter_mut().zip(src_tail) {
+        *dst_val = f_scalar(src_val);
+    }
+}
+
+#[inline]
+#[target_feature(enable = "avx512f")]
+unsafe fn map_slice_in_place(
+    f: impl Fn(__m512i) -> __m512i,
+    f_scalar: impl Fn(u64) -> u64,
+    values: &mut [u64],
+) {
+    let mut chunks = values.chunks_exact_mut(LANES);
+    for chunk in &mut chunks {
+        let ptr = chunk.as_mut_ptr() as *mut __m512i;
+        _mm512_storeu_si512(ptr, f(_mm512_loadu_si512(ptr)));
+    }
+    for val in chunks.into_remainder() {
+        *val = f_scalar(*val);
+    }
+}
+
+/// # Safety
+///
+/// The CPU must support AVX-512F and AVX-512DQ.
+#[target_feature(enable = "avx512f,avx512dq")]
+pub(crate) unsafe fn divide_slice(divider: DividerU64, src: &[u64], dst: &mut [u64]) {
+    with_avx512_divide_fn!(divider, divide => map_slice(divide, |n| divider.divide(n), src, dst))
+}
+
+/// # Safety
+///
+/// The CPU must support AVX-512F and AVX-512DQ.
+#[target_feature(enable = "avx512f,avx512dq")]
+pub(crate) unsafe fn divide_slice_in_place(divider: DividerU64, values: &mut [u64]) {
+    with_avx512_divide_fn!(divider, divide => map_slice_in_place(divide, |n| divider.divide(n), values))
+}
+
+/// # Safety
+///
+/// The CPU must support AVX-512F and AVX-512DQ.
+#[target_feature(enable = "avx512f,avx512dq")]
+pub(crate) unsafe fn mod_slice(divider: DividerU64, divisor: u64, src: &[u64], dst: &mut [u64]) {
+    let divisors = _mm512_set1_epi64(divisor as i64);
+    with_avx512_divide_fn!(divider, divide => map_slice(
+        |n| _mm512_sub_epi64(n, _mm512_mullo_epi64(divide(n), divisors)),
+        |n| n - divider.divide(n) * divisor,
+        src,
+        dst,
+    ))
+}
+
+/// # Safety
+///
+/// The CPU must support AVX-512F and AVX-512DQ.
+#[target_feature(enable = "avx512f,avx512dq")]
+pub(crate) unsafe fn mod_slice_in_place(divider: DividerU64, divisor: u64, values: &mut [u64]) {
+    let divisors = _mm512_set1_epi64(divisor as i64);
+    with_avx512_divide_fn!(divider, divide => map_slice_in_place(
+        |n| _mm512_sub_epi64(n, _mm512_mullo_epi64(divide(n), divisors)),
+        |n| n - divider.divide(n) * divisor,
+        values,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    fn numerators() -> Vec<u64> {
+        (0u64..1_000)
+            .chain([
+                u64::MAX - 1,
+                u64::MAX,
+                1 << 63,
+                (1 << 63) - 1,
+                234234131223u64,
+            ])
+            .collect()
+    }
+
+    #[test]
+    fn test_avx512_kernels() {
+        if !is_available() {
+            return;
+        }
+        let src = numerators();
+        let divisors = (1u64..300).chain((0..64).map(|i| 1 << i)).chain([
+            14,
+            234234131223u64,
+            (1 << 63) + 1,
+            u64::MAX,
+        ]);
+        for d in divisors {
+            let divider = DividerU64::divide_by(d);
+            let mut quotients = vec![0u64; src.len()];
+            unsafe { divide_slice(divider, &src, &mut quotients) };
+            let expected: Vec<u64> = src.iter().map(|n| n / d).collect();
+            assert_eq!(quotients, expected);
+            let mut remainders = src.clone();
+            unsafe { mod_slice_in_place(divider, d, &mut remainders) };
+            let expected: Vec<u64> = src.iter().map(|n| n % d).collect();
+            assert_eq!(remainders, expected);
+        }
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_avx512_mullhi_proptest(factor: u64, n: [u64; LANES]) {
+            if !is_available() {
+                return Ok(());
+            }
+            let mut res = [0u64; LANES];
+            unsafe {
+                let (factor_lo, factor_hi) = split(factor);
+                let n = _mm512_loadu_si512(n.as_ptr() as *const __m512i);
+                _mm512_storeu_si512(res.as_mut_ptr() as *mut __m512i, mullhi(factor_lo, factor_hi, n));
+            }
+            for (&n, &res) in n.iter().zip(&res) {
+                assert_eq!(res, ((factor as u128 * n as u128) >> 64) as u64);
+            }
+        }
+    }
+}
@@ -0,0 +1,193 @@
+//! Selection of the implementation of the slice kernels.
+
+use core::sync::atomic::{AtomicU8, Ordering};
+
+/// An implementation of the slice kernels.
+///
+/// By default, the best backend available on the current CPU is detected
+/// the first time a slice kernel is called. [`Backend::force`] makes it
+/// possible to pick another one, typically for reproducible benchmarks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum Backend {
+    /// Scalar code.
+    Scalar = 1,
+    /// `core::simd`, with the `simd` feature.
+    PortableSimd = 2,
+    /// AVX2, on x86_64.
+    Avx2 = 3,
+    /// AVX-512F and AVX-512DQ, on x86_64 with the `avx512` feature.
+    Avx512 = 4,
+    /// NEON, on aarch64 with the `neon` feature.
+    Neon = 5,
+    /// SVE, on aarch64 with the `sve` feature.
+    Sve = 6,
+    /// The RISC-V vector extension, on riscv64 with the `rvv` feature.
+    Rvv = 7,
+    /// SIMD128, on wasm32 with the `simd128` target feature.
+    Simd128 = 8,
+    /// The `wide` crate, with the `wide` feature.
+    Wide = 9,
+}
+
+/// All of the backends, the best ones first.
+const BACKENDS: [Backend; 9] = [
+    Backend::Avx512,
+    Backend::Avx2,
+    Backend::Sve,
+    Backend::Neon,
+    Backend::Rvv,
+    Backend::Simd128,
+    Backend::PortableSimd,
+    Backend::Wide,
+    Backend::Scalar,
+];
+
+/// The current backend, or 0 if it has not been detected yet.
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+impl Backend {
+    /// Returns true if the backend is compiled in and supported by the current CPU.
+    pub fn is_available(self) -> bool {
+        match self {
+            Backend::Scalar => true,
+            Backend::PortableSimd => cfg!(feature = "simd"),
+            Backend::Wide => cfg!(feature = "wide"),
+            #[cfg(target_arch = "x86_64")]
+            Backend::Avx2 => crate::avx2::is_available(),
+            #[cfg(all(feature = "avx512", target_arch = "x86_64"))]
+            Backend::Avx512 => crate::avx512::is_available(),
+            #[cfg(all(feature = "neon", target_arch = "aarch64"))]
+            Backend::Neon => crate::neon::is_available(),
+            #[cfg(all(feature = "sve", target_arch = "aarch64"))]
+            Backend::Sve => crate::sve::is_available(),
+            #[cfg(all(feature = "rvv", target_arch = "riscv64"))]
+            Backend::Rvv => crate::rvv::is_available(),
+            #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+            Backend::Simd128 => crate::wasm_simd::is_available(),
+            #[allow(unreachable_patterns)]
+            _ => false,
+        }
+    }
+
+    /// Returns the best backend available on the current CPU.
+    pub fn detect() -> Backend {
+        BACKENDS
+            .iter()
+            .copied()
+            .find(|backend| backend.is_available())
+            .unwrap_or(Backend::Scalar)
+    }
+
+    /// Returns the backend used by the slice kernels.
+    #[inline]
+    pub fn current() -> Backend {
+        match Backend::from_u8(CURRENT.load(Ordering::Relaxed)) {
+            Some(backend) => backend,
+            None => {
+                let backend = Backend::detect();
+                CURRENT.store(backend as u8, Ordering::Relaxed);
+                backend
+            }
+        }
+    }
+
+    /// Makes the slice kernels use this backend, in all threads.
+    ///
+    /// Panics if the backend is not available.
+    pub fn force(self) {
+        assert!(
+            self.is_available(),
+            "the {:?} backend is not available",
+            self
+        );
+        CURRENT.store(self as u8, Ordering::Relaxed);
+    }
+
+    /// Makes the slice kernels use the best backend available again.
+    pub fn reset() {
+        CURRENT.store(Backend::detect() as u8, Ordering::Relaxed);
+    }
+
+    fn from_u8(val: u8) -> Option<Backend> {
+        BACKENDS
+            .iter()
+            .copied()
+            .find(|&backend| backend as u8 == val)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Backend, BACKENDS};
+    use crate::{DivRemU64, DividerU64};
+    use std::sync::Mutex;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_detect() {
+        let backend = Backend::detect();
+        assert!(backend.is_available());
+        assert!(Backend::Scalar.is_available());
+        assert!(Backend::current().is_available());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_force_unavailable() {
+        let unavailable = BACKENDS
+            .iter()
+            .copied()
+            .find(|backend| !backend.is_available())
+            // The RISC-V and WASM backends cannot both be available.
+            .unwrap();
+        unavailable.force();
+    }
+
+    // `force` changes the backend of the whole process: the tests forcing
+    // backends must hold this lock, so that they do not run concurrently.
+    static FORCE_LOCK: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn test_all_backends() {
+        let _guard = FORCE_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let src: Vec<u64> = (0u64..1_000)
+            .chain([u64::MAX - 1, u64::MAX, 1 << 63, 234234131223u64])
+            .collect();
+        let divisors = (1u64..100).chain((0..64).map(|i| 1 << i)).chain([
+            14,
+            234234131223u64,
+            (1 << 63) + 1,
+            u64::MAX,
+        ]);
+        for d in divisors {
+            let divider = DividerU64::divide_by(d);
+            let divrem = DivRemU64::divide_by(d);
+            let quotients: Vec<u64> = src.iter().map(|n| n / d).collect();
+            let remainders: Vec<u64> = src.iter().map(|n| n % d).collect();
+            for backend in BACKENDS.iter().copied().filter(|b| b.is_available()) {
+                backend.force();
+                let mut dst = vec![0u64; src.len()];
+                divider.divide_slice(&src, &mut dst);
+                assert_eq!(dst, quotients, "{:?}", backend);
+                divrem.mod_slice(&src, &mut dst);
+                assert_eq!(dst, remainders, "{:?}", backend);
+                let mut counts = [0u64; 64];
+                let num_out_of_range = divider.bucket_counts(&src, &mut counts);
+                let mut expected = [0u64; 64];
+                for &q in &quotients {
+                    if let Some(count) = expected.get_mut(q as usize) {
+                        *count += 1;
+                    }
+                }
+                assert_eq!(counts, expected, "{:?}", backend);
+                let in_range = expected.iter().sum::<u64>() as usize;
+                assert_eq!(num_out_of_range, src.len() - in_range, "{:?}", backend);
+            }
+        }
+        Backend::reset();
+    }
+}
@@ -0,0 +1,187 @@
+//! Reduction of 256-bit numbers by a runtime 128-bit modulus.
+
+use crate::MulHi;
+
+/// Precomputed reciprocal of a `u128` modulus, reducing 256-bit numbers
+/// such as the products of two `u128`s.
+///
+/// This is the reduction of Möller and Granlund, "Improved division by
+/// invariant integers", in base 2^128: reducing a number lower than
+/// `modulus * 2^128` costs two 128-bit multiplications and a few corrections,
+/// without any division, which targets have no hardware for at this width.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Barrett128 {
+    modulus: u128,
+    // The modulus shifted left until its most significant bit is set.
+    normalized: u128,
+    // (2^256 - 1) / normalized - 2^128.
+    reciprocal: u128,
+    shift: u32,
+}
+
+/// Returns the 256-bit product `x * y`, as `(hi, lo)`.
+#[inline(always)]
+fn mul_u128(x: u128, y: u128) -> (u128, u128) {
+    (x.mulhi(y), x.wrapping_mul(y))
+}
+
+impl Barrett128 {
+    /// Precomputes the reciprocal of `modulus`.
+    ///
+    /// Panics if `modulus` is 0.
+    pub fn new(modulus: u128) -> Barrett128 {
+        assert!(modulus != 0);
+        let shift = modulus.leading_zeros();
+        let normalized = modulus << shift;
+        // Long division of (!normalized * 2^128 + 2^128 - 1) by `normalized`,
+        // one bit at a time. `!normalized < normalized`, so the quotient fits.
+        let mut remainder = !normalized;
+        let mut reciprocal = 0u128;
+        for _ in 0..128 {
+            let carry = remainder >> 127 != 0;
+            remainder = (remainder << 1) | 1;
+            reciprocal <<= 1;
+            if carry || remainder >= normalized {
+                remainder = remainder.wrapping_sub(normalized);
+                reciprocal |= 1;
+            }
+        }
+        Barrett128 {
+            modulus,
+            normalized,
+            reciprocal,
+            shift,
+        }
+    }
+
+    /// Returns the modulus.
+    #[inline(always)]
+    pub fn modulus(&self) -> u128 {
+        self.modulus
+    }
+
+    /// Returns `(hi * 2^128 + lo) % normalized`, for `hi < normalized`.
+    #[inline(always)]
+    fn reduce_normalized(&self, hi: u128, lo: u128) -> u128 {
+        let d = self.normalized;
+        let (q1, q0) = mul_u128(self.reciprocal, hi);
+        let (q0, carry) = q0.overflowing_add(lo);
+        let q1 = q1
+            .wrapping_add(hi)
+            .wrapping_add(carry as u128)
+            .wrapping_add(1);
+        let mut r = lo.wrapping_sub(q1.wrapping_mul(d));
+        if r > q0 {
+            r = r.wrapping_add(d);
+        }
+        if r >= d {
+            r -= d;
+        }
+        r
+    }
+
+    /// Returns `(hi * 2^128 + lo) % modulus`.
+    ///
+    /// This is cheapest when `hi` is lower than the modulus, which is the case
+    /// of the products of reduced numbers.
+    #[inline]
+    pub fn reduce(&self, hi: u128, lo: u128) -> u128 {
+        let hi = if hi >= self.modulus {
+            self.reduce(0, hi)
+        } else {
+            hi
+        };
+        if self.shift == 0 {
+            return self.reduce_normalized(hi, lo);
+        }
+        let hi = (hi << self.shift) | (lo >> (128 - self.shift));
+        self.reduce_normalized(hi, lo << self.shift) >> self.shift
+    }
+
+    /// Returns `(a * b) % modulus`.
+    #[inline]
+    pub fn mul_mod(&self, a: u128, b: u128) -> u128 {
+        let (hi, lo) = mul_u128(a, b);
+        self.reduce(hi, lo)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Barrett128;
+    use proptest::prelude::*;
+
+    // Double-and-add, with only additions modulo `m`.
+    fn mul_mod_reference(a: u128, b: u128, m: u128) -> u128 {
+        let add_mod = |x: u128, y: u128| {
+            let (sum, overflow) = x.overflowing_add(y);
+            if overflow || sum >= m {
+                sum.wrapping_sub(m)
+            } else {
+                sum
+            }
+        };
+        let a = a % m;
+        let mut result = 0;
+        for i in (0..128).rev() {
+            result = add_mod(result, result);
+            if (b >> i) & 1 == 1 {
+                result = add_mod(result, a);
+            }
+        }
+        result
+    }
+
+    fn moduli() -> impl Iterator<Item = u128> {
+        (1u128..20).chain([
+            u64::MAX as u128,
+            1 << 64,
+            (1 << 127) - 1,
+            1 << 127,
+            (1 << 127) + 1,
+            u128::MAX - 158,
+            u128::MAX,
+        ])
+    }
+
+    #[test]
+    fn test_barrett128_reduce() {
+        for m in moduli() {
+            let barrett = Barrett128::new(m);
+            assert_eq!(barrett.modulus(), m);
+            for lo in [0, 1, m - 1, m, u64::MAX as u128, u128::MAX] {
+                assert_eq!(barrett.reduce(0, lo), lo % m);
+            }
+            for a in [0, 1, 2, m / 3, m - 1, u128::MAX] {
+                for b in [0, 1, 3, m - 1, u128::MAX] {
+                    assert_eq!(barrett.mul_mod(a, b), mul_mod_reference(a, b, m));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_barrett128_hi() {
+        // 2^256 - 1 = (2^128 - 1) * (2^128 + 1), and 2^128 + 1 = u128::MAX + 2.
+        let barrett = Barrett128::new(1_000_000_007);
+        let expected = mul_mod_reference(u128::MAX, u128::MAX % 1_000_000_007 + 2, 1_000_000_007);
+        assert_eq!(barrett.reduce(u128::MAX, u128::MAX), expected);
+        assert_eq!(
+            barrett.reduce(1, 0),
+            mul_mod_reference(1 << 64, 1 << 64, 1_000_000_007)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_barrett128_zero() {
+        Barrett128::new(0);
+    }
+
+    proptest! {
+        #[test]
+        fn test_proptest_barrett128(a in any::<u128>(), b in any::<u128>(), m in 1..u128::MAX) {
+            assert_eq!(Barrett128::new(m).mul_mod(a, b), mul_mod_reference(a, b, m));
+        }
+    }
+}
@@ -3,7 +3,10 @@
 extern crate fastdivide;
 extern crate test;
 
-use fastdivide::DividerU64;
+use fastdivide::{
+    ApproxDividerU64, Backend, BranchFreeDividerU64, DivRemU64, DividerU32, DividerU64,
+    DividerU64By32,
+};
 use test::Bencher;
 
 #[bench]
@@ -35,3 +38,182 @@ fn bench_fast_divide(b: &mut Bencher) {
         v
     })
 }
+
+#[bench]
+fn bench_branchfree_divide(b: &mut Bencher) {
+    let branchfree_divider = BranchFreeDividerU64::divide_by(112u64);
+    b.iter(|| {
+        let mut v = 0;
+        {
+            let n: u64 = test::black_box(152342341u64);
+            v += branchfree_divider.divide(n)
+        }
+        {
+            let n: u64 = test::black_box(152342341u64);
+            v += branchfree_divider.divide(n)
+        }
+        {
+            let n: u64 = test::black_box(152342341u64);
+            v += branchfree_divider.divide(n)
+        }
+        v
+    })
+}
+
+// A mix of fast and general dividers: 7, 21 and 23 take the general path,
+// which is about a third of the dividers.
+fn mixed_dividers() -> Vec<DividerU64> {
+    (0..1_000u64)
+        .map(|i| {
+            if i % 10 == 0 {
+                DividerU64::divide_by(7)
+            } else {
+                DividerU64::divide_by(11 + 2 * (i % 7))
+            }
+        })
+        .collect()
+}
+
+#[bench]
+fn bench_mixed_normal_divide(b: &mut Bencher) {
+    let divisors: Vec<u64> = (0..1_000u64)
+        .map(|i| if i % 10 == 0 { 7 } else { 11 + 2 * (i % 7) })
+        .collect();
+    b.iter(|| {
+        let n: u64 = test::black_box(152342341u64);
+        divisors.iter().map(|&d| n / d).sum::<u64>()
+    })
+}
+
+#[bench]
+fn bench_mixed_fast_divide(b: &mut Bencher) {
+    let dividers = mixed_dividers();
+    b.iter(|| {
+        let n: u64 = test::black_box(152342341u64);
+        dividers
+            .iter()
+            .map(|divider| divider.divide(n))
+            .sum::<u64>()
+    })
+}
+
+#[bench]
+fn bench_divide_by_general(b: &mut Bencher) {
+    b.iter(|| {
+        let d: u64 = test::black_box(7u64);
+        DividerU64::divide_by(d)
+    })
+}
+
+#[bench]
+fn bench_general_divide(b: &mut Bencher) {
+    let divider = DividerU64::divide_by(7u64);
+    b.iter(|| {
+        let n: u64 = test::black_box(152342341u64);
+        (0..100u64).map(|i| divider.divide(n + i)).sum::<u64>()
+    })
+}
+
+#[bench]
+fn bench_approx_divide(b: &mut Bencher) {
+    let divider = ApproxDividerU64::divide_by(7u64);
+    b.iter(|| {
+        let n: u64 = test::black_box(152342341u64);
+        (0..100u64).map(|i| divider.divide(n + i)).sum::<u64>()
+    })
+}
+
+#[bench]
+fn bench_divide_by_32(b: &mut Bencher) {
+    let divider = DividerU64By32::divide_by(7u32);
+    b.iter(|| {
+        let n: u64 = test::black_box(152342341u64);
+        (0..100u64).map(|i| divider.divide(n + i)).sum::<u64>()
+    })
+}
+
+// These two decide whether `DividerU32` beats the hardware divider (or the
+// software division provided by the runtime). `cargo bench` cannot run on
+// microcontrollers such as thumbv6m / thumbv7m: the `mcu-bench` crate measures
+// the same loops there, in cycles.
+#[bench]
+fn bench_normal_divide_u32(b: &mut Bencher) {
+    let d: u32 = test::black_box(7u32);
+    b.iter(|| {
+        let n: u32 = test::black_box(152342341u32);
+        (0..100u32).map(|i| (n + i) / d).sum::<u32>()
+    })
+}
+
+#[bench]
+fn bench_divider_u32(b: &mut Bencher) {
+    let divider = DividerU32::divide_by(7u32);
+    b.iter(|| {
+        let n: u32 = test::black_box(152342341u32);
+        (0..100u32).map(|i| divider.divide(n + i)).sum::<u32>()
+    })
+}
+
+#[bench]
+fn bench_normal_remainder_mersenne(b: &mut Bencher) {
+    let d: u64 = test::black_box((1u64 << 61) - 1);
+    b.iter(|| {
+        let n: u64 = test::black_box(152342341223234u64);
+        (0..100u64).map(|i| (n * i) % d).sum::<u64>()
+    })
+}
+
+#[bench]
+fn bench_remainder_mersenne(b: &mut Bencher) {
+    let divider = DivRemU64::divide_by((1u64 << 61) - 1);
+    b.iter(|| {
+        let n: u64 = test::black_box(152342341223234u64);
+        (0..100u64).map(|i| divider.remainder(n * i)).sum::<u64>()
+    })
+}
+
+// The same remainder, computed from the quotient of the multiply path.
+#[bench]
+fn bench_remainder_mersenne_multiply(b: &mut Bencher) {
+    let d = (1u64 << 61) - 1;
+    let divider = DividerU64::divide_by(d);
+    b.iter(|| {
+        let n: u64 = test::black_box(152342341223234u64);
+        (0..100u64)
+            .map(|i| n * i - divider.divide(n * i) * d)
+            .sum::<u64>()
+    })
+}
+
+#[bench]
+fn bench_divide_slice(b: &mut Bencher) {
+    let divider = DividerU64::divide_by(112u64);
+    let src: Vec<u64> = (0..1_000u64).map(|i| i * 152342341u64).collect();
+    let mut dst = vec![0u64; src.len()];
+    b.iter(|| {
+        divider.divide_slice(test::black_box(&src), &mut dst);
+        dst[0]
+    })
+}
+
+#[bench]
+fn bench_divide_slice_scalar(b: &mut Bencher) {
+    let divider = DividerU64::divide_by(112u64);
+    let src: Vec<u64> = (0..1_000u64).map(|i| i * 152342341u64).collect();
+    let mut dst = vec![0u64; src.len()];
+    Backend::Scalar.force();
+    b.iter(|| {
+        divider.divide_slice(test::black_box(&src), &mut dst);
+        dst[0]
+    });
+    Backend::reset();
+}
+
+#[bench]
+fn bench_divide_array(b: &mut Bencher) {
+    let divider = DividerU64::divide_by(112u64);
+    b.iter(|| {
+        let n: u64 = test::black_box(152342341u64);
+        divider.divide_array([n, n + 1, n + 2, n + 3, n + 4, n + 5, n + 6, n + 7])
+    })
+}
@@ -0,0 +1,105 @@
+//! Prints the magic constants of the dividers, for hand-written assembly
+//! or for reviewing generated code.
+//!
+//! Usage: `fastdivide <divisor>...`
+
+use std::process::ExitCode;
+
+use fastdivide::{DividerU64, RecipeOp};
+
+fn algorithm(divider: &DividerU64) -> &'static str {
+    match divider {
+        DividerU64::Identity => "identity",
+        DividerU64::Fast { .. } => "fast: mulhi(magic, n) >> shift",
+        DividerU64::BitShift(_) => "bit shift: n >> shift",
+        DividerU64::General { .. } => "general: t = mulhi(magic, n); (((n - t) >> 1) + t) >> shift",
+        DividerU64::PreShift { .. } => "pre-shift: mulhi(magic, n >> pre_shift) >> shift",
+        DividerU64::Compare(_) => "compare: (n >= divisor) as u64",
+        // `DividerU64` is not exhaustive.
+        _ => "see the recipe below",
+    }
+}
+
+fn print_asm(divider: &DividerU64) {
+    println!("x86-64 (n in rdi, quotient in rax):");
+    println!("    mov rax, rdi");
+    for op in divider.recipe().ops() {
+        match *op {
+            RecipeOp::MulHi(magic) => {
+                println!("    mov rcx, {:#x}", magic);
+                println!("    mul rcx");
+                println!("    mov rax, rdx");
+            }
+            RecipeOp::Shr(shift) => println!("    shr rax, {}", shift),
+            RecipeOp::Sub => {
+                println!("    mov rax, rdi");
+                println!("    sub rax, rdx");
+            }
+            RecipeOp::Add => println!("    add rax, rdx"),
+            RecipeOp::GreaterEq(divisor) => {
+                println!("    mov rcx, {:#x}", divisor);
+                println!("    cmp rax, rcx");
+                println!("    setae al");
+                println!("    movzx eax, al");
+            }
+        }
+    }
+}
+
+fn print_divider(divisor: u64) {
+    let divider = DividerU64::divide_by(divisor);
+    println!("divisor: {}", divisor);
+    println!("algorithm: {}", algorithm(&divider));
+    match divider {
+        DividerU64::Identity => {}
+        DividerU64::BitShift(shift) => println!("shift: {}", shift),
+        DividerU64::Fast { magic, shift } => {
+            println!("magic: {:#x} ({})", magic, magic);
+            println!("shift: {}", shift);
+        }
+        DividerU64::General { magic_low, shift } => {
+            println!("magic: {:#x} ({}), plus 2^64", magic_low, magic_low);
+            println!("shift: {}", shift);
+        }
+        DividerU64::PreShift {
+            magic,
+            pre_shift,
+            shift,
+        } => {
+            println!("magic: {:#x} ({})", magic, magic);
+            println!("pre_shift: {}", pre_shift);
+            println!("shift: {}", shift);
+        }
+        _ => {}
+    }
+    println!("Rust: DividerU64::{:?}", divider);
+    let libdivide = divider.to_libdivide();
+    println!(
+        "C (libdivide_u64_t): {{ .magic = {:#x}ULL, .more = {:#x} }}",
+        libdivide.magic, libdivide.more
+    );
+    print_asm(&divider);
+}
+
+fn main() -> ExitCode {
+    let divisors: Vec<String> = std::env::args().skip(1).collect();
+    if divisors.is_empty() {
+        eprintln!("usage: fastdivide <divisor>...");
+        return ExitCode::FAILURE;
+    }
+    for (i, arg) in divisors.iter().enumerate() {
+        match arg.parse::<u64>() {
+            Ok(divisor) if divisor > 0 => {
+                if i > 0 {
+                    println!();
+                }
+                print_divider(divisor);
+            }
+            _ => {
+                eprintln!("invalid divisor: {}", arg);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    ExitCode::SUCCESS
+}
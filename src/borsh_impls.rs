@@ -0,0 +1,76 @@
+//! `BorshSerialize` and `BorshDeserialize` implementations,
+//! behind the `borsh` feature.
+//!
+//! As with serde, the dividers are written as their divisor, a little endian
+//! `u64`, and rebuilt with `divide_by` when read back. A divisor of 0 is
+//! rejected with `ErrorKind::InvalidData`.
+
+use borsh::io::{Error, ErrorKind, Read, Result, Write};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::{DivRemU64, DividerU64};
+
+fn read_divisor<R: Read>(reader: &mut R) -> Result<u64> {
+    let divisor = u64::deserialize_reader(reader)?;
+    if divisor == 0 {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "the divisor must not be 0",
+        ));
+    }
+    Ok(divisor)
+}
+
+impl BorshSerialize for DividerU64 {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.divisor().serialize(writer)
+    }
+}
+
+impl BorshDeserialize for DividerU64 {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
+        read_divisor(reader).map(DividerU64::divide_by)
+    }
+}
+
+impl BorshSerialize for DivRemU64 {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.divisor().serialize(writer)
+    }
+}
+
+impl BorshDeserialize for DivRemU64 {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
+        read_divisor(reader).map(DivRemU64::divide_by)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DivRemU64, DividerU64};
+
+    const DIVISORS: [u64; 6] = [1, 7, 11, 1 << 40, (1 << 63) + 1, u64::MAX];
+
+    #[test]
+    fn test_borsh_round_trip() {
+        for d in DIVISORS {
+            let divider = DividerU64::divide_by(d);
+            let bytes = borsh::to_vec(&divider).unwrap();
+            assert_eq!(bytes, d.to_le_bytes());
+            assert_eq!(borsh::from_slice::<DividerU64>(&bytes).unwrap(), divider);
+            let divrem = DivRemU64::divide_by(d);
+            let bytes = borsh::to_vec(&(3u8, divrem)).unwrap();
+            assert_eq!(
+                borsh::from_slice::<(u8, DivRemU64)>(&bytes).unwrap(),
+                (3u8, divrem)
+            );
+        }
+    }
+
+    #[test]
+    fn test_borsh_rejects_invalid_divisors() {
+        assert!(borsh::from_slice::<DividerU64>(&0u64.to_le_bytes()).is_err());
+        assert!(borsh::from_slice::<DivRemU64>(&0u64.to_le_bytes()).is_err());
+        assert!(borsh::from_slice::<DividerU64>(&[7, 0, 0]).is_err());
+    }
+}
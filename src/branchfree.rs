@@ -0,0 +1,106 @@
+use crate::{floor_log2, libdivide_mullhi_u64, DivideU64, DividerU64};
+
+/// Divider using libdivide's branchfree algorithm.
+///
+/// `DividerU64::divide` picks one of several instruction sequences
+/// depending on the divisor. This is ideal when the same divider is used
+/// over and over, but the resulting branch is hard to predict if dividers
+/// of different kinds are mixed together (e.g. one divider per element of
+/// a slice).
+///
+/// `BranchFreeDividerU64::divide` always executes the same
+/// multiply / sub / shift / add / shift sequence, regardless of the divisor.
+/// It is slightly slower than `DividerU64` on the fast path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BranchFreeDividerU64 {
+    pub(crate) magic: u64,
+    // Either 0 (divisor is 1) or 1.
+    pub(crate) half_shift: u8,
+    pub(crate) shift: u8,
+}
+
+impl BranchFreeDividerU64 {
+    /// Precomputes a branchfree divider for `divisor`.
+    ///
+    /// Panics if `divisor` is 0.
+    pub fn divide_by(divisor: u64) -> BranchFreeDividerU64 {
+        assert!(divisor > 0u64);
+        if divisor.is_power_of_two() {
+            let log2_d = floor_log2(divisor);
+            // With a magic number of 0, we compute `(n >> half_shift) >> shift`.
+            // libdivide does not accept 1 as a branchfree divisor. We handle it by
+            // not doing the halving shift at all.
+            return BranchFreeDividerU64 {
+                magic: 0,
+                half_shift: log2_d.min(1),
+                shift: log2_d.saturating_sub(1),
+            };
+        }
+        let (magic, shift) = DividerU64::general_magic(divisor);
+        BranchFreeDividerU64 {
+            magic,
+            half_shift: 1,
+            shift,
+        }
+    }
+
+    #[inline(always)]
+    pub fn divide(&self, n: u64) -> u64 {
+        // Computes n * (magic + 2^64) >> (64 + shift + 1) without overflowing.
+        let q = libdivide_mullhi_u64(self.magic, n);
+        let t = ((n - q) >> self.half_shift).wrapping_add(q);
+        t >> self.shift
+    }
+}
+
+impl DivideU64 for BranchFreeDividerU64 {
+    #[inline(always)]
+    fn divide(&self, n: u64) -> u64 {
+        BranchFreeDividerU64::divide(self, n)
+    }
+}
+
+impl core::ops::Div<BranchFreeDividerU64> for u64 {
+    type Output = u64;
+
+    #[inline(always)]
+    fn div(self, denom: BranchFreeDividerU64) -> Self::Output {
+        denom.divide(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BranchFreeDividerU64;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_branchfree_small_divisors() {
+        for d in (1u64..300).chain((0..64).map(|i| 1 << i)) {
+            let divider = BranchFreeDividerU64::divide_by(d);
+            for n in (0u64..1_000).chain([u64::MAX - 1, u64::MAX, 1 << 63, 234234131223u64]) {
+                assert_eq!(divider.divide(n), n / d);
+            }
+        }
+    }
+
+    #[test]
+    fn test_branchfree_large_divisors() {
+        for d in [u64::MAX, u64::MAX - 1, (1 << 63) + 1, (1 << 63) - 1] {
+            let divider = BranchFreeDividerU64::divide_by(d);
+            for n in [0u64, 1, d - 1, d, u64::MAX - 1, u64::MAX] {
+                assert_eq!(divider.divide(n), n / d);
+            }
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(100000))]
+        #[test]
+        fn test_proptest_branchfree(n in 0..u64::MAX, d in 1..u64::MAX) {
+            let divider = BranchFreeDividerU64::divide_by(d);
+            assert_eq!(n / divider, n / d);
+        }
+    }
+}
@@ -0,0 +1,127 @@
+/// Divider for `u64` numerators, specialized for divisors that fit in 32 bits.
+///
+/// The division is done as a schoolbook long division in base 2^32, where each
+/// digit of the quotient is computed with the 2-by-1 reciprocal algorithm of
+/// Möller and Granlund ("Improved division by invariant integers").
+/// This only requires 32 bits x 32 bits -> 64 bits multiplications, which makes it
+/// a good fit for 32-bit targets and for WebAssembly, where `DividerU64` has to
+/// emulate the 64 bits x 64 bits -> 128 bits multiplication.
+/// On 64-bit targets, prefer `DividerU64`.
+///
+/// The remainder comes for free, and the divider is only 12 bytes large.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DividerU64By32 {
+    // The divisor, shifted so that its most significant bit is set.
+    normalized_divisor: u32,
+    // ⌊(2^64 - 1) / normalized_divisor⌋ - 2^32
+    reciprocal: u32,
+    shift: u8,
+}
+
+impl DividerU64By32 {
+    /// Precomputes a divider for `divisor`.
+    ///
+    /// Panics if `divisor` is 0.
+    pub fn divide_by(divisor: u32) -> DividerU64By32 {
+        assert!(divisor > 0u32);
+        let shift = divisor.leading_zeros();
+        let normalized_divisor = divisor << shift;
+        // The quotient is in [2^32, 2^33), so the subtraction leaves
+        // a number that fits in 32 bits.
+        let reciprocal = (u64::MAX / normalized_divisor as u64 - (1u64 << 32)) as u32;
+        DividerU64By32 {
+            normalized_divisor,
+            reciprocal,
+            shift: shift as u8,
+        }
+    }
+
+    /// Returns the divisor.
+    pub fn divisor(&self) -> u32 {
+        self.normalized_divisor >> self.shift
+    }
+
+    // Returns the quotient and the remainder of `(u1 * 2^32 + u0) / normalized_divisor`.
+    // `u1` must be lower than the normalized divisor.
+    #[inline(always)]
+    fn div_2_by_1(&self, u1: u32, u0: u32) -> (u32, u32) {
+        let d = self.normalized_divisor;
+        let q = (self.reciprocal as u64 * u1 as u64).wrapping_add(((u1 as u64) << 32) | u0 as u64);
+        let mut q1 = ((q >> 32) as u32).wrapping_add(1);
+        let q0 = q as u32;
+        let mut r = u0.wrapping_sub(q1.wrapping_mul(d));
+        if r > q0 {
+            q1 = q1.wrapping_sub(1);
+            r = r.wrapping_add(d);
+        }
+        if r >= d {
+            q1 += 1;
+            r -= d;
+        }
+        (q1, r)
+    }
+
+    /// Returns `(n / divisor, n % divisor)`.
+    #[inline(always)]
+    pub fn div_rem(&self, n: u64) -> (u64, u32) {
+        let shifted = n << self.shift;
+        // The bits of `n` shifted out. This is lower than 2^31,
+        // and therefore lower than the normalized divisor.
+        let n2 = if self.shift == 0 {
+            0
+        } else {
+            (n >> (64 - self.shift)) as u32
+        };
+        let (q1, r) = self.div_2_by_1(n2, (shifted >> 32) as u32);
+        let (q0, r) = self.div_2_by_1(r, shifted as u32);
+        (((q1 as u64) << 32) | q0 as u64, r >> self.shift)
+    }
+
+    #[inline(always)]
+    pub fn divide(&self, n: u64) -> u64 {
+        self.div_rem(n).0
+    }
+}
+
+impl core::ops::Div<DividerU64By32> for u64 {
+    type Output = u64;
+
+    #[inline(always)]
+    fn div(self, denom: DividerU64By32) -> Self::Output {
+        denom.divide(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DividerU64By32;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_divider_by_32() {
+        let divisors = (1u32..300).chain((0..32).map(|i| 1 << i)).chain([
+            u32::MAX,
+            u32::MAX - 1,
+            (1 << 31) + 1,
+            234234131,
+        ]);
+        for d in divisors {
+            let divider = DividerU64By32::divide_by(d);
+            assert_eq!(divider.divisor(), d);
+            for n in (0u64..1_000).chain([u64::MAX - 1, u64::MAX, 1 << 63, 234234131223u64]) {
+                assert_eq!(divider.div_rem(n), (n / d as u64, (n % d as u64) as u32));
+            }
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(100000))]
+        #[test]
+        fn test_proptest_divider_by_32(n in any::<u64>(), d in 1..u32::MAX) {
+            let divider = DividerU64By32::divide_by(d);
+            assert_eq!(n / divider, n / d as u64);
+            assert_eq!(divider.div_rem(n).1 as u64, n % d as u64);
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! Bucketing of chrono date times, behind the `chrono` feature.
+
+use chrono::{DateTime, TimeZone};
+
+use crate::DivRemU64;
+
+impl DivRemU64 {
+    /// Returns the index of the interval of `divisor` seconds containing `datetime`,
+    /// counting from the epoch. See [`DivRemU64::div_floor_i64`].
+    #[inline]
+    pub fn bucket_datetime<Tz: TimeZone>(&self, datetime: &DateTime<Tz>) -> i64 {
+        self.div_floor_i64(datetime.timestamp())
+    }
+
+    /// Returns the start of the interval of `divisor` seconds containing `datetime`,
+    /// in the time zone of `datetime`.
+    ///
+    /// The intervals are aligned on the epoch, in UTC, and date times before
+    /// the epoch are truncated towards the past as well.
+    /// Returns `None` if the start is out of the range of `DateTime`.
+    ///
+    /// ```
+    /// use chrono::DateTime;
+    /// use fastdivide::DivRemU64;
+    ///
+    /// let seven_minutes = DivRemU64::divide_by(7 * 60);
+    /// // 21:55:30.5 UTC, in the interval starting at 21:54 UTC.
+    /// let datetime = DateTime::parse_from_rfc3339("1969-12-31T23:55:30.5+02:00").unwrap();
+    /// let start = seven_minutes.truncate_datetime(&datetime).unwrap();
+    /// let expected = DateTime::parse_from_rfc3339("1969-12-31T23:54:00+02:00").unwrap();
+    /// assert_eq!(start, expected);
+    /// assert_eq!(start.offset(), expected.offset());
+    /// ```
+    pub fn truncate_datetime<Tz: TimeZone>(&self, datetime: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let start = self.truncate_i64(datetime.timestamp())?;
+        Some(DateTime::from_timestamp(start, 0)?.with_timezone(&datetime.timezone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::DivRemU64;
+    use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+
+    #[test]
+    fn test_truncate_datetime() {
+        let divider = DivRemU64::divide_by(90);
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let datetime = Utc.with_ymd_and_hms(2024, 3, 1, 12, 1, 29).unwrap();
+        assert_eq!(divider.truncate_datetime(&datetime), Some(start));
+        assert_eq!(divider.bucket_datetime(&datetime), start.timestamp() / 90);
+        let offset = FixedOffset::west_opt(5 * 3600).unwrap();
+        let datetime = datetime.with_timezone(&offset);
+        let truncated = divider.truncate_datetime(&datetime).unwrap();
+        assert_eq!(truncated, start);
+        assert_eq!(truncated.offset(), &offset);
+    }
+
+    #[test]
+    fn test_truncate_datetime_before_epoch() {
+        let divider = DivRemU64::divide_by(90);
+        let datetime = DateTime::from_timestamp(-1, 999_999_999).unwrap();
+        assert_eq!(divider.bucket_datetime(&datetime), -1);
+        assert_eq!(
+            divider.truncate_datetime(&datetime),
+            DateTime::from_timestamp(-90, 0)
+        );
+        let divider = DivRemU64::divide_by(1 << 40);
+        assert_eq!(divider.truncate_datetime(&DateTime::<Utc>::MIN_UTC), None);
+    }
+}
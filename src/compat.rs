@@ -0,0 +1,98 @@
+//! Drop-in replacements for the types of other crates.
+
+use core::ops::{Div, DivAssign, Rem, RemAssign};
+
+use crate::DivRemU64;
+
+/// Mirrors the API of `strength_reduce::StrengthReducedU64`, so that
+/// switching crates only requires changing an import.
+///
+/// ```
+/// use fastdivide::compat::StrengthReducedU64;
+///
+/// let divisor = StrengthReducedU64::new(7);
+/// assert_eq!(100 / divisor, 14);
+/// assert_eq!(100 % divisor, 2);
+/// assert_eq!(StrengthReducedU64::div_rem(100, divisor), (14, 2));
+/// assert_eq!(divisor.get(), 7);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StrengthReducedU64(DivRemU64);
+
+impl StrengthReducedU64 {
+    /// Precomputes a divider for `divisor`.
+    ///
+    /// Panics if `divisor` is 0.
+    #[inline]
+    pub fn new(divisor: u64) -> StrengthReducedU64 {
+        StrengthReducedU64(DivRemU64::divide_by(divisor))
+    }
+
+    /// Returns the quotient and the remainder of `numerator / denom`.
+    #[inline]
+    pub fn div_rem(numerator: u64, denom: StrengthReducedU64) -> (u64, u64) {
+        denom.0.div_rem(numerator)
+    }
+
+    /// Returns the divisor.
+    #[inline]
+    pub fn get(&self) -> u64 {
+        self.0.divisor()
+    }
+}
+
+impl Div<StrengthReducedU64> for u64 {
+    type Output = u64;
+
+    #[inline]
+    fn div(self, rhs: StrengthReducedU64) -> u64 {
+        rhs.0.divide(self)
+    }
+}
+
+impl Rem<StrengthReducedU64> for u64 {
+    type Output = u64;
+
+    #[inline]
+    fn rem(self, rhs: StrengthReducedU64) -> u64 {
+        rhs.0.remainder(self)
+    }
+}
+
+impl DivAssign<StrengthReducedU64> for u64 {
+    #[inline]
+    fn div_assign(&mut self, rhs: StrengthReducedU64) {
+        *self = *self / rhs;
+    }
+}
+
+impl RemAssign<StrengthReducedU64> for u64 {
+    #[inline]
+    fn rem_assign(&mut self, rhs: StrengthReducedU64) {
+        *self = *self % rhs;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StrengthReducedU64;
+
+    #[test]
+    fn test_strength_reduced_u64() {
+        for d in [1u64, 2, 3, 7, 1 << 40, (1 << 61) - 1, u64::MAX] {
+            let divisor = StrengthReducedU64::new(d);
+            assert_eq!(divisor.get(), d);
+            for n in [0u64, 1, 6, 7, 1 << 63, u64::MAX] {
+                assert_eq!(n / divisor, n / d);
+                assert_eq!(n % divisor, n % d);
+                assert_eq!(StrengthReducedU64::div_rem(n, divisor), (n / d, n % d));
+                let mut quotient = n;
+                quotient /= divisor;
+                assert_eq!(quotient, n / d);
+                let mut remainder = n;
+                remainder %= divisor;
+                assert_eq!(remainder, n % d);
+            }
+        }
+    }
+}
@@ -0,0 +1,91 @@
+//! Division of `crypto_bigint::U256` by a `u64`, behind the `crypto-bigint` feature.
+
+use crypto_bigint::{Word, U256};
+
+use crate::DivRemU64;
+
+/// Returns the 64-bit limbs of `n`, least significant first.
+///
+/// The words of `U256` are 64-bit on 64-bit targets, and 32-bit otherwise:
+/// on 64-bit targets, the conversions are no-ops.
+#[inline(always)]
+#[allow(clippy::useless_conversion)]
+fn to_limbs(n: &U256) -> [u64; 4] {
+    let mut limbs = [0u64; 4];
+    for (i, &word) in n.as_words().iter().enumerate() {
+        let bit = i * Word::BITS as usize;
+        limbs[bit / 64] |= u64::from(word) << (bit % 64);
+    }
+    limbs
+}
+
+#[inline(always)]
+fn from_limbs(limbs: [u64; 4]) -> U256 {
+    let mut words = [0; U256::LIMBS];
+    for (i, word) in words.iter_mut().enumerate() {
+        let bit = i * Word::BITS as usize;
+        *word = (limbs[bit / 64] >> (bit % 64)) as Word;
+    }
+    U256::from_words(words)
+}
+
+impl DivRemU64 {
+    /// Returns the quotient and the remainder of the division of `n`
+    /// by the divisor, with [`DivRemU64::divide_limbs`].
+    ///
+    /// ```
+    /// use crypto_bigint::U256;
+    /// use fastdivide::DivRemU64;
+    ///
+    /// // 3 * 2^192 + 7
+    /// let n = U256::from_u64(3).shl_vartime(192).wrapping_add(&U256::from_u64(7));
+    /// let (q, r) = DivRemU64::divide_by(3).divide_u256(&n);
+    /// assert_eq!(q, U256::ONE.shl_vartime(192).wrapping_add(&U256::from_u64(2)));
+    /// assert_eq!(r, 1);
+    /// ```
+    pub fn divide_u256(&self, n: &U256) -> (U256, u64) {
+        let mut limbs = to_limbs(n);
+        let remainder = self.divide_limbs(&mut limbs);
+        (from_limbs(limbs), remainder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_limbs, to_limbs};
+    use crate::DivRemU64;
+    use crypto_bigint::{NonZero, U256};
+    use proptest::prelude::*;
+
+    fn check(n: [u64; 4], d: u64) {
+        let n = from_limbs(n);
+        let (q, r) = DivRemU64::divide_by(d).divide_u256(&n);
+        let (expected_q, expected_r) = n.div_rem(&NonZero::new(U256::from_u64(d)).unwrap());
+        assert_eq!(q, expected_q);
+        assert_eq!(U256::from_u64(r), expected_r);
+    }
+
+    #[test]
+    fn test_limbs() {
+        let limbs = [1, 2 << 32, 3, u64::MAX];
+        assert_eq!(to_limbs(&from_limbs(limbs)), limbs);
+        assert_eq!(from_limbs([7, 0, 0, 0]), U256::from_u64(7));
+        assert_eq!(from_limbs([0, 0, 0, 1 << 63]), U256::ONE.shl_vartime(255));
+    }
+
+    #[test]
+    fn test_divide_u256() {
+        for d in [1u64, 3, 7, 10_000_000_000_000_000_000, 1 << 63, u64::MAX] {
+            check([0; 4], d);
+            check([u64::MAX; 4], d);
+            check([12345, 0, 1 << 40, 17], d);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_proptest_divide_u256(n: [u64; 4], d in 1..u64::MAX) {
+            check(n, d);
+        }
+    }
+}
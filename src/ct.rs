@@ -0,0 +1,83 @@
+use crate::BranchFreeDividerU64;
+
+/// Divider meant to be used on secret numerators.
+///
+/// # Constant-time guarantees
+///
+/// `divide`, `remainder` and `div_rem` execute the same instruction sequence
+/// whatever the numerator and whatever the class of the divisor (power of 2, fast
+/// path or general path): there is no branch and no memory access depending on
+/// their arguments.
+///
+/// This relies on:
+/// - the 64 bits x 64 bits multiplication and the variable shifts of the CPU being constant-time.
+///   This is the case on x86_64 and aarch64, but not on some embedded cores with an
+///   early-terminating multiplier. The `soft-mul` feature avoids the multiplier
+///   entirely on these cores.
+/// - the compiler not introducing branches. There is no way to guarantee that in Rust,
+///   so sensitive code should check the generated assembly.
+///
+/// The divisor is considered public: `divide_by` takes a time that depends on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CtDividerU64 {
+    divider: BranchFreeDividerU64,
+    divisor: u64,
+}
+
+impl CtDividerU64 {
+    /// Precomputes a divider for `divisor`. This is *not* constant time.
+    ///
+    /// Panics if `divisor` is 0.
+    pub fn divide_by(divisor: u64) -> CtDividerU64 {
+        CtDividerU64 {
+            divider: BranchFreeDividerU64::divide_by(divisor),
+            divisor,
+        }
+    }
+
+    /// Returns `n / divisor`, in constant time.
+    #[inline(always)]
+    pub fn divide(&self, n: u64) -> u64 {
+        self.divider.divide(n)
+    }
+
+    /// Returns `n % divisor`, in constant time.
+    #[inline(always)]
+    pub fn remainder(&self, n: u64) -> u64 {
+        self.div_rem(n).1
+    }
+
+    /// Returns `(n / divisor, n % divisor)`, in constant time.
+    #[inline(always)]
+    pub fn div_rem(&self, n: u64) -> (u64, u64) {
+        let quotient = self.divide(n);
+        (quotient, n - quotient * self.divisor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CtDividerU64;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_ct_divider() {
+        for d in (1u64..100).chain([1 << 32, 1 << 63, u64::MAX]) {
+            let divider = CtDividerU64::divide_by(d);
+            for n in (0u64..1_000).chain([u64::MAX - 1, u64::MAX]) {
+                assert_eq!(divider.div_rem(n), (n / d, n % d));
+            }
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(10000))]
+        #[test]
+        fn test_proptest_ct_divider(n in any::<u64>(), d in 1..u64::MAX) {
+            let divider = CtDividerU64::divide_by(d);
+            assert_eq!(divider.divide(n), n / d);
+            assert_eq!(divider.remainder(n), n % d);
+        }
+    }
+}
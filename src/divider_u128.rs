@@ -0,0 +1,196 @@
+//! Port of libdivide's algorithm to `u128` numerators.
+//!
+//! The high half of the 128 bits x 128 bits -> 256 bits multiplication is built
+//! out of four 64 bits x 64 bits -> 128 bits multiplications.
+
+/// Precomputed divider for `u128` numerators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DividerU128 {
+    /// Dividing by 1. `divide` returns its argument untouched.
+    Identity,
+    Fast {
+        magic: u128,
+        shift: u8,
+    },
+    BitShift(u8),
+    General {
+        magic_low: u128,
+        shift: u8,
+    },
+}
+
+#[inline(always)]
+fn libdivide_mullhi_u128(x: u128, y: u128) -> u128 {
+    let x0 = x as u64 as u128;
+    let x1 = x >> 64;
+    let y0 = y as u64 as u128;
+    let y1 = y >> 64;
+    let x0y0_hi = (x0 * y0) >> 64;
+    let x0y1 = x0 * y1;
+    let x1y0 = x1 * y0;
+    let x1y1 = x1 * y1;
+    // None of these additions can overflow.
+    let temp = x1y0 + x0y0_hi;
+    let temp_lo = temp as u64 as u128;
+    let temp_hi = temp >> 64;
+    x1y1 + temp_hi + ((temp_lo + x0y1) >> 64)
+}
+
+/// Returns the quotient and the remainder of `(hi * 2^128) / divisor`.
+///
+/// `hi` must be smaller than `divisor`, for the quotient to fit in 128 bits.
+/// This is only used to build dividers: a simple bit by bit long division will do.
+fn div_256_by_128(hi: u128, divisor: u128) -> (u128, u128) {
+    debug_assert!(hi < divisor);
+    let mut reminder = hi;
+    let mut quotient = 0u128;
+    for _ in 0..128 {
+        // The remainder is smaller than the divisor: shifting it overflows
+        // by at most one bit, in which case it is larger than the divisor.
+        let overflow = reminder >> 127 == 1;
+        reminder <<= 1;
+        quotient <<= 1;
+        if overflow || reminder >= divisor {
+            reminder = reminder.wrapping_sub(divisor);
+            quotient |= 1;
+        }
+    }
+    (quotient, reminder)
+}
+
+#[inline(always)]
+fn floor_log2_u128(n: u128) -> u8 {
+    assert_ne!(n, 0);
+    127u8 - (n.leading_zeros() as u8)
+}
+
+impl DividerU128 {
+    /// Precomputes a divider for `divisor`.
+    ///
+    /// A divisor of 1 is guaranteed to yield `DividerU128::Identity`,
+    /// for which `divide` does not perform any arithmetic.
+    ///
+    /// Panics if `divisor` is 0.
+    pub fn divide_by(divisor: u128) -> DividerU128 {
+        assert!(divisor > 0u128);
+        if divisor == 1 {
+            return DividerU128::Identity;
+        }
+        let floor_log_2_d = floor_log2_u128(divisor);
+        if divisor.is_power_of_two() {
+            return DividerU128::BitShift(floor_log_2_d);
+        }
+        let (proposed_magic_number, reminder) = div_256_by_128(1u128 << floor_log_2_d, divisor);
+        let e = divisor - reminder;
+        // See `DividerU64`.
+        if e < (1u128 << floor_log_2_d) {
+            return DividerU128::Fast {
+                magic: proposed_magic_number + 1,
+                shift: floor_log_2_d,
+            };
+        }
+        // m=⌊2^{129+floor_log_2_d} / d⌋ + 1. This is a 129 bit number, so we keep only the low 128 bits.
+        let twice_reminder = reminder.wrapping_add(reminder);
+        let carry = (twice_reminder >= divisor || twice_reminder < reminder) as u128;
+        let magic_low = proposed_magic_number
+            .wrapping_add(proposed_magic_number)
+            .wrapping_add(carry)
+            .wrapping_add(1);
+        DividerU128::General {
+            magic_low,
+            shift: floor_log_2_d,
+        }
+    }
+
+    #[inline(always)]
+    pub fn divide(&self, n: u128) -> u128 {
+        match *self {
+            DividerU128::Fast { magic, shift } => libdivide_mullhi_u128(magic, n) >> shift,
+            DividerU128::BitShift(d) => n >> d,
+            DividerU128::Identity => n,
+            DividerU128::General { magic_low, shift } => {
+                // Computes n * (magic + 2^128) >> shift, see `DividerU64`.
+                let q = libdivide_mullhi_u128(magic_low, n);
+                let t = ((n - q) >> 1).wrapping_add(q);
+                t >> shift
+            }
+        }
+    }
+}
+
+impl core::ops::Div<DividerU128> for u128 {
+    type Output = u128;
+
+    #[inline(always)]
+    fn div(self, denom: DividerU128) -> Self::Output {
+        denom.divide(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{div_256_by_128, libdivide_mullhi_u128, DividerU128};
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_div_256_by_128() {
+        assert_eq!(div_256_by_128(1, 3), (u128::MAX / 3, 1));
+        assert_eq!(div_256_by_128(0, 7), (0, 0));
+        // (2^128 - 2) * 2^128 = (2^128 - 1) * (2^128 - 1) - 1
+        assert_eq!(
+            div_256_by_128(u128::MAX - 1, u128::MAX),
+            (u128::MAX - 1, u128::MAX - 1)
+        );
+    }
+
+    #[test]
+    fn test_mullhi_u128() {
+        assert_eq!(libdivide_mullhi_u128(u128::MAX, u128::MAX), u128::MAX - 1);
+        assert_eq!(libdivide_mullhi_u128(1 << 127, 4), 2);
+        assert_eq!(libdivide_mullhi_u128(u64::MAX as u128, u64::MAX as u128), 0);
+    }
+
+    #[test]
+    fn test_divider_u128_kinds() {
+        assert_eq!(DividerU128::divide_by(1), DividerU128::Identity);
+        assert_eq!(DividerU128::divide_by(1 << 100), DividerU128::BitShift(100));
+        assert!(matches!(
+            DividerU128::divide_by(7),
+            DividerU128::General { .. }
+        ));
+        assert!(matches!(
+            DividerU128::divide_by(3),
+            DividerU128::Fast { .. }
+        ));
+    }
+
+    #[test]
+    fn test_divider_u128() {
+        for d in (1u128..300)
+            .chain((0..128).map(|i| 1 << i))
+            .chain((1..128).map(|i| (1 << i) - 1))
+            .chain([u128::MAX, u128::MAX - 1, u64::MAX as u128 + 2])
+        {
+            let divider = DividerU128::divide_by(d);
+            for n in (0u128..1_000).chain([u128::MAX - 1, u128::MAX, 1 << 127, 1 << 64]) {
+                assert_eq!(divider.divide(n), n / d);
+            }
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(10000))]
+        #[test]
+        fn test_proptest_divider_u128(n in any::<u128>(), d in 1..u128::MAX) {
+            let divider = DividerU128::divide_by(d);
+            assert_eq!(n / divider, n / d);
+        }
+
+        #[test]
+        fn test_proptest_divider_u128_small_divisor(n in any::<u128>(), d in 1..u64::MAX as u128) {
+            let divider = DividerU128::divide_by(d);
+            assert_eq!(n / divider, n / d);
+        }
+    }
+}
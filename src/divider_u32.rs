@@ -0,0 +1,176 @@
+//! Port of libdivide's `u32` algorithm.
+//!
+//! It only relies on 32 bits x 32 bits -> 64 bits multiplications, which
+//! microcontrollers without a 64-bit multiplier support natively.
+
+/// Precomputed divider for `u32` numerators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DividerU32 {
+    /// Dividing by 1. `divide` returns its argument untouched.
+    Identity,
+    Fast {
+        magic: u32,
+        shift: u8,
+    },
+    BitShift(u8),
+    General {
+        magic_low: u32,
+        shift: u8,
+    },
+}
+
+#[inline(always)]
+fn libdivide_mullhi_u32(x: u32, y: u32) -> u32 {
+    ((x as u64 * y as u64) >> 32) as u32
+}
+
+#[inline(always)]
+fn floor_log2_u32(n: u32) -> u8 {
+    assert_ne!(n, 0);
+    31u8 - (n.leading_zeros() as u8)
+}
+
+impl DividerU32 {
+    /// Precomputes a divider for `divisor`.
+    ///
+    /// A divisor of 1 is guaranteed to yield `DividerU32::Identity`,
+    /// for which `divide` does not perform any arithmetic.
+    ///
+    /// Panics if `divisor` is 0.
+    pub fn divide_by(divisor: u32) -> DividerU32 {
+        assert!(divisor > 0u32);
+        if divisor == 1 {
+            return DividerU32::Identity;
+        }
+        let floor_log_2_d = floor_log2_u32(divisor);
+        if divisor.is_power_of_two() {
+            return DividerU32::BitShift(floor_log_2_d);
+        }
+        let u = 1u64 << (32 + floor_log_2_d);
+        let proposed_magic_number = (u / divisor as u64) as u32;
+        let reminder = (u % divisor as u64) as u32;
+        let e = divisor - reminder;
+        // See `DividerU64`.
+        if e < (1u32 << floor_log_2_d) {
+            return DividerU32::Fast {
+                magic: proposed_magic_number + 1,
+                shift: floor_log_2_d,
+            };
+        }
+        // m=⌊2^{33+floor_log_2_d} / d⌋ + 1. This is a 33 bit number, so we keep only the low 32 bits.
+        let twice_reminder = reminder.wrapping_add(reminder);
+        let carry = (twice_reminder >= divisor || twice_reminder < reminder) as u32;
+        let magic_low = proposed_magic_number
+            .wrapping_add(proposed_magic_number)
+            .wrapping_add(carry)
+            .wrapping_add(1);
+        DividerU32::General {
+            magic_low,
+            shift: floor_log_2_d,
+        }
+    }
+
+    #[inline(always)]
+    pub fn divide(&self, n: u32) -> u32 {
+        match *self {
+            DividerU32::Fast { magic, shift } => libdivide_mullhi_u32(magic, n) >> shift,
+            DividerU32::BitShift(d) => n >> d,
+            DividerU32::Identity => n,
+            DividerU32::General { magic_low, shift } => {
+                // Computes n * (magic + 2^32) >> shift, see `DividerU64`.
+                let q = libdivide_mullhi_u32(magic_low, n);
+                let t = ((n - q) >> 1).wrapping_add(q);
+                t >> shift
+            }
+        }
+    }
+
+    /// Divides all of the values of a fixed-size array, such as the components
+    /// of an integer vector.
+    #[inline]
+    pub fn divide_array<const N: usize>(&self, mut values: [u32; N]) -> [u32; N] {
+        self.divide_slice_in_place(&mut values);
+        values
+    }
+
+    /// Divides in place all of the values of `values`, such as the elements
+    /// of a matrix.
+    pub fn divide_slice_in_place(&self, values: &mut [u32]) {
+        // Matching outside of the loop lets the compiler vectorize each case.
+        match *self {
+            DividerU32::Identity => {}
+            DividerU32::BitShift(shift) => values.iter_mut().for_each(|n| *n >>= shift),
+            divider => values.iter_mut().for_each(|n| *n = divider.divide(*n)),
+        }
+    }
+}
+
+impl core::ops::Div<DividerU32> for u32 {
+    type Output = u32;
+
+    #[inline(always)]
+    fn div(self, denom: DividerU32) -> Self::Output {
+        denom.divide(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DividerU32;
+    use proptest::prelude::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_divider_u32_kinds() {
+        assert_eq!(DividerU32::divide_by(1), DividerU32::Identity);
+        assert_eq!(DividerU32::divide_by(8), DividerU32::BitShift(3));
+        assert!(matches!(
+            DividerU32::divide_by(7),
+            DividerU32::General { .. }
+        ));
+        assert!(matches!(DividerU32::divide_by(11), DividerU32::Fast { .. }));
+    }
+
+    #[test]
+    fn test_divider_u32() {
+        for d in (1u32..300)
+            .chain((0..32).map(|i| 1 << i))
+            .chain([u32::MAX, u32::MAX - 1])
+        {
+            let divider = DividerU32::divide_by(d);
+            for n in (0u32..1_000).chain([u32::MAX - 1, u32::MAX, 1 << 31]) {
+                assert_eq!(divider.divide(n), n / d);
+            }
+        }
+    }
+
+    #[test]
+    fn test_divide_array() {
+        let divider = DividerU32::divide_by(16);
+        assert_eq!(divider.divide_array([0, 15, 16, 100]), [0, 0, 1, 6]);
+        assert_eq!(
+            DividerU32::divide_by(7).divide_array([u32::MAX; 3]),
+            [u32::MAX / 7; 3]
+        );
+    }
+
+    #[test]
+    fn test_divide_slice_in_place() {
+        for d in [1u32, 3, 7, 16, u32::MAX] {
+            let mut values: Vec<u32> = (0..100).chain([u32::MAX - 1, u32::MAX]).collect();
+            let expected: Vec<u32> = values.iter().map(|n| n / d).collect();
+            DividerU32::divide_by(d).divide_slice_in_place(&mut values);
+            assert_eq!(values, expected);
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(100000))]
+        #[test]
+        fn test_proptest_divider_u32(n in any::<u32>(), d in 1..u32::MAX) {
+            let divider = DividerU32::divide_by(d);
+            assert_eq!(n / divider, n / d);
+        }
+    }
+}
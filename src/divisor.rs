@@ -0,0 +1,146 @@
+//! Abstraction over literal and precomputed divisors.
+
+use crate::{DivRemU64, DividerU64};
+
+/// A `u64` divisor, either literal or precomputed, in the spirit of
+/// `str::pattern::Pattern`.
+///
+/// Functions accepting an `impl Divisor` let their callers decide whether
+/// precomputing a `DividerU64` pays off.
+///
+/// The iterator adaptors of `DivideIterExt` accept an `impl Divisor`.
+/// The slice methods, such as `DividerU64::divide_slice` and
+/// `DividerU64::par_divide_slice`, are methods of the precomputed divider
+/// instead: `divisor.into_divider().divide_slice(..)` works with any `Divisor`,
+/// and keeps the precomputation explicit when dividing many slices.
+/// `FastDiv` takes the divider of its own width, and does not accept a `Divisor` either.
+///
+/// ```
+/// use fastdivide::{DividerU64, Divisor};
+///
+/// fn average<D: Divisor>(total: u64, count: D) -> u64 {
+///     count.divide_u64(total)
+/// }
+///
+/// assert_eq!(average(100, 7), 14);
+/// assert_eq!(average(100, DividerU64::divide_by(7)), 14);
+/// ```
+pub trait Divisor: Copy {
+    /// Returns `n / divisor`.
+    fn divide_u64(self, n: u64) -> u64;
+
+    /// Returns the precomputed divider, computing it if needed.
+    fn into_divider(self) -> DividerU64;
+
+    /// Returns the precomputed divider computing remainders, computing it if needed.
+    ///
+    /// A `DividerU64` does not store its divisor, which is then recovered
+    /// with `DividerU64::divisor`.
+    fn into_div_rem(self) -> DivRemU64 {
+        DivRemU64::divide_by(self.into_divider().divisor())
+    }
+}
+
+impl Divisor for u64 {
+    #[inline(always)]
+    fn divide_u64(self, n: u64) -> u64 {
+        n / self
+    }
+
+    #[inline]
+    fn into_divider(self) -> DividerU64 {
+        DividerU64::divide_by(self)
+    }
+
+    #[inline]
+    fn into_div_rem(self) -> DivRemU64 {
+        DivRemU64::divide_by(self)
+    }
+}
+
+impl Divisor for DividerU64 {
+    #[inline(always)]
+    fn divide_u64(self, n: u64) -> u64 {
+        self.divide(n)
+    }
+
+    #[inline(always)]
+    fn into_divider(self) -> DividerU64 {
+        self
+    }
+}
+
+impl Divisor for &DividerU64 {
+    #[inline(always)]
+    fn divide_u64(self, n: u64) -> u64 {
+        self.divide(n)
+    }
+
+    #[inline(always)]
+    fn into_divider(self) -> DividerU64 {
+        *self
+    }
+}
+
+impl Divisor for DivRemU64 {
+    #[inline(always)]
+    fn divide_u64(self, n: u64) -> u64 {
+        self.divide(n)
+    }
+
+    #[inline(always)]
+    fn into_divider(self) -> DividerU64 {
+        self.into()
+    }
+
+    #[inline(always)]
+    fn into_div_rem(self) -> DivRemU64 {
+        self
+    }
+}
+
+impl Divisor for &DivRemU64 {
+    #[inline(always)]
+    fn divide_u64(self, n: u64) -> u64 {
+        self.divide(n)
+    }
+
+    #[inline(always)]
+    fn into_divider(self) -> DividerU64 {
+        (*self).into()
+    }
+
+    #[inline(always)]
+    fn into_div_rem(self) -> DivRemU64 {
+        *self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Divisor;
+    use crate::{DivRemU64, DividerU64};
+
+    fn check<D: Divisor>(divisor: D, d: u64) {
+        for n in [0u64, 1, d - 1, d, 1 << 63, u64::MAX] {
+            assert_eq!(divisor.divide_u64(n), n / d);
+            assert_eq!(divisor.into_divider().divide(n), n / d);
+            assert_eq!(divisor.into_div_rem().div_rem(n), (n / d, n % d));
+        }
+    }
+
+    #[test]
+    fn test_divisor() {
+        for d in [1u64, 7, 1 << 40, u64::MAX] {
+            let divider = DividerU64::divide_by(d);
+            check(d, d);
+            check(divider, d);
+            let by_ref = &divider;
+            check(by_ref, d);
+            let divrem = DivRemU64::divide_by(d);
+            check(divrem, d);
+            let divrem_ref = &divrem;
+            check(divrem_ref, d);
+        }
+    }
+}
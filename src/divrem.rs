@@ -0,0 +1,445 @@
+use core::convert::TryFrom;
+
+use crate::{libdivide_mullhi_u64, DividerU64};
+
+/// Precomputed divider computing remainders as well as quotients.
+///
+/// `DividerU64` does not keep track of its divisor, in order to stay 16 bytes large.
+/// `DivRemU64` stores it alongside the divider, which is what computing
+/// `n % divisor` requires.
+///
+/// Divisors of the form `2^k - 1` and `2^k + 1` with `k >= 32`, such as the Mersenne
+/// prime `2^61 - 1` popular in hashing, are detected at construction:
+/// their remainder is computed by folding the high bits of the numerator
+/// onto its low bits, without any multiplication.
+/// For smaller `k`, such as 255 or 257, folding takes several rounds and is slower
+/// than going through the quotient, so these divisors use the multiplication.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DivRemU64 {
+    divider: DividerU64,
+    divisor: u64,
+    pub(crate) fold: Fold,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Fold {
+    None,
+    // divisor = 2^k - 1, with 32 <= k <= 63.
+    // 2^k = 1 mod divisor, so n = hi * 2^k + lo = hi + lo mod divisor.
+    Mersenne(u8),
+    // divisor = 2^k + 1, with 32 <= k <= 62.
+    // 2^k = -1 mod divisor, so n = hi * 2^k + lo = lo - hi mod divisor.
+    Fermat(u8),
+}
+
+impl Fold {
+    fn detect(divisor: u64) -> Fold {
+        if divisor & divisor.wrapping_add(1) == 0 {
+            let k = divisor.count_ones() as u8;
+            if (32..=63).contains(&k) {
+                return Fold::Mersenne(k);
+            }
+        }
+        let below = divisor.wrapping_sub(1);
+        if below.is_power_of_two() {
+            let k = below.trailing_zeros() as u8;
+            if (32..=62).contains(&k) {
+                return Fold::Fermat(k);
+            }
+        }
+        Fold::None
+    }
+}
+
+/// Returns the inverse of the odd number `odd` modulo 2^64.
+pub(crate) fn inverse_mod_2_64(odd: u64) -> u64 {
+    debug_assert!(odd & 1 == 1);
+    // Newton's iteration doubles the number of correct low bits each time.
+    // `odd` is its own inverse modulo 8, 3 bits are correct to begin with.
+    let mut inverse = odd;
+    for _ in 0..5 {
+        inverse = inverse.wrapping_mul(2u64.wrapping_sub(odd.wrapping_mul(inverse)));
+    }
+    inverse
+}
+
+/// Checks divisibility with a single multiplication, without computing the remainder
+/// (Granlund & Montgomery, section 9).
+///
+/// With `divisor = odd * 2^shift`, multiplying by the inverse of `odd` modulo 2^64
+/// maps the multiples of `odd` onto `0..=u64::MAX / odd`, and rotating right by
+/// `shift` moves the numbers whose low `shift` bits are not 0 above that range.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct DivisibilityCheck {
+    inverse: u64,
+    shift: u32,
+    threshold: u64,
+}
+
+impl DivisibilityCheck {
+    pub(crate) fn new(divisor: u64) -> DivisibilityCheck {
+        assert!(divisor > 0);
+        let shift = divisor.trailing_zeros();
+        DivisibilityCheck {
+            inverse: inverse_mod_2_64(divisor >> shift),
+            shift,
+            threshold: u64::MAX / divisor,
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn is_divisible(&self, n: u64) -> bool {
+        n.wrapping_mul(self.inverse).rotate_right(self.shift) <= self.threshold
+    }
+}
+
+impl DivRemU64 {
+    /// Precomputes a divider for `divisor`.
+    ///
+    /// Panics if `divisor` is 0.
+    pub fn divide_by(divisor: u64) -> DivRemU64 {
+        DivRemU64 {
+            divider: DividerU64::divide_by(divisor),
+            divisor,
+            fold: Fold::detect(divisor),
+        }
+    }
+
+    /// Returns the divisor.
+    #[inline(always)]
+    pub fn divisor(&self) -> u64 {
+        self.divisor
+    }
+
+    /// Returns the underlying `DividerU64`.
+    #[inline(always)]
+    pub fn divider(&self) -> DividerU64 {
+        self.divider
+    }
+
+    #[inline(always)]
+    pub fn divide(&self, n: u64) -> u64 {
+        self.divider.divide(n)
+    }
+
+    /// Returns `n % divisor`.
+    #[inline(always)]
+    pub fn remainder(&self, n: u64) -> u64 {
+        match self.fold {
+            Fold::None => {}
+            Fold::Mersenne(k) => {
+                // hi + lo < 2^k + 2^{64 - k} <= 2^{k+1}. A second fold gives
+                // at most `divisor + 1`.
+                let r = (n & self.divisor) + (n >> k);
+                let r = (r & self.divisor) + (r >> k);
+                return if r >= self.divisor {
+                    r - self.divisor
+                } else {
+                    r
+                };
+            }
+            Fold::Fermat(k) => {
+                // lo < 2^k < divisor and hi < 2^{64 - k} <= 2^k < divisor.
+                let hi = n >> k;
+                let lo = n & ((1u64 << k) - 1);
+                return if lo >= hi {
+                    lo - hi
+                } else {
+                    lo + self.divisor - hi
+                };
+            }
+        }
+        if let Some(mask) = self.divider.remainder_mask() {
+            return n & mask;
+        }
+        n - self.divide(n) * self.divisor
+    }
+
+    /// Returns `(n / divisor, n % divisor)`.
+    #[inline(always)]
+    pub fn div_rem(&self, n: u64) -> (u64, u64) {
+        let quotient = self.divide(n);
+        (quotient, n - quotient * self.divisor)
+    }
+
+    /// Maps `x` onto `0..divisor`, as `(x * divisor) >> 64`.
+    ///
+    /// This is Lemire's multiply-shift range reduction. It is not `x % divisor`,
+    /// but it spreads the uniformly distributed `x`, such as hashes or random words,
+    /// evenly over the range, with a single multiplication. `DividerU64` does not
+    /// offer it, as it does not know its divisor.
+    #[inline(always)]
+    pub fn map_to_range(&self, x: u64) -> u64 {
+        libdivide_mullhi_u64(x, self.divisor)
+    }
+
+    /// Returns `2^64 % divisor`.
+    ///
+    /// This is the number of values of `x` for which `map_to_range` has to be
+    /// rejected to sample `0..divisor` without bias.
+    #[inline]
+    pub fn threshold(&self) -> u64 {
+        self.remainder(self.divisor.wrapping_neg())
+    }
+
+    /// Returns `map_to_range(x)`, or `None` if `x` has to be rejected,
+    /// so that the results are uniformly distributed over `0..divisor`
+    /// for a uniformly distributed `x`.
+    ///
+    /// This is the rejection step of Lemire's nearly divisionless sampling.
+    /// The threshold is only computed for the rare values of `x` that
+    /// are close to being rejected.
+    #[inline]
+    pub fn debias(&self, x: u64) -> Option<u64> {
+        let low = x.wrapping_mul(self.divisor);
+        if low < self.divisor && low < self.threshold() {
+            return None;
+        }
+        Some(self.map_to_range(x))
+    }
+
+    /// Returns `n.div_euclid(divisor)`, rounding the quotient of negative
+    /// numerators towards negative infinity.
+    ///
+    /// This is the index of the bucket of a timestamp, for instance
+    /// `DateTime::timestamp()` divided by a 90 seconds interval, including
+    /// for timestamps before the epoch.
+    #[inline]
+    pub fn div_floor_i64(&self, n: i64) -> i64 {
+        if n >= 0 {
+            self.divide(n as u64) as i64
+        } else {
+            // `!n` is `-n - 1`, which is non-negative, and `!q` is `-q - 1`.
+            !(self.divide(!(n as u64)) as i64)
+        }
+    }
+
+    /// Returns `n.rem_euclid(divisor)`, which is always in `0..divisor`.
+    #[inline]
+    pub fn rem_euclid_i64(&self, n: i64) -> u64 {
+        if n >= 0 {
+            self.remainder(n as u64)
+        } else {
+            self.divisor - 1 - self.remainder(!(n as u64))
+        }
+    }
+
+    /// Returns the greatest multiple of the divisor which is lower than or equal
+    /// to `n`, such as the start of the interval containing a timestamp.
+    ///
+    /// Returns `None` if it does not fit in an `i64`.
+    #[inline]
+    pub fn truncate_i64(&self, n: i64) -> Option<i64> {
+        i64::try_from(n as i128 - self.rem_euclid_i64(n) as i128).ok()
+    }
+}
+
+/// Only prints the divisor: the way the remainder is computed is an
+/// implementation detail.
+#[cfg(feature = "defmt")]
+impl defmt::Format for DivRemU64 {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "DivRemU64 {{ divisor: {=u64} }}", self.divisor)
+    }
+}
+
+impl From<DivRemU64> for DividerU64 {
+    fn from(divider: DivRemU64) -> DividerU64 {
+        divider.divider
+    }
+}
+
+// Unlike `n % divider` with a `DividerU64`, this does not need to recover the divisor.
+impl core::ops::Rem<DivRemU64> for u64 {
+    type Output = u64;
+
+    #[inline(always)]
+    fn rem(self, denom: DivRemU64) -> Self::Output {
+        denom.remainder(self)
+    }
+}
+
+impl core::ops::Rem<&DivRemU64> for u64 {
+    type Output = u64;
+
+    #[inline(always)]
+    fn rem(self, denom: &DivRemU64) -> Self::Output {
+        denom.remainder(self)
+    }
+}
+
+impl core::ops::RemAssign<DivRemU64> for u64 {
+    #[inline(always)]
+    fn rem_assign(&mut self, denom: DivRemU64) {
+        *self = denom.remainder(*self);
+    }
+}
+
+impl core::ops::RemAssign<&DivRemU64> for u64 {
+    #[inline(always)]
+    fn rem_assign(&mut self, denom: &DivRemU64) {
+        *self = denom.remainder(*self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DivRemU64, DivisibilityCheck, Fold};
+    use core::convert::TryFrom;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_fold_detection() {
+        assert_eq!(Fold::detect(255), Fold::None);
+        assert_eq!(Fold::detect((1 << 32) - 1), Fold::Mersenne(32));
+        assert_eq!(Fold::detect((1 << 61) - 1), Fold::Mersenne(61));
+        assert_eq!(Fold::detect((1 << 63) - 1), Fold::Mersenne(63));
+        assert_eq!(Fold::detect(u64::MAX), Fold::None);
+        assert_eq!(Fold::detect(257), Fold::None);
+        assert_eq!(Fold::detect((1 << 32) + 1), Fold::Fermat(32));
+        assert_eq!(Fold::detect((1 << 62) + 1), Fold::Fermat(62));
+        assert_eq!(Fold::detect((1 << 63) + 1), Fold::None);
+        assert_eq!(Fold::detect(1 << 40), Fold::None);
+    }
+
+    #[test]
+    fn test_div_rem() {
+        let divisors = (1u64..300)
+            .chain((0..64).map(|i| 1 << i))
+            .chain((32..64).map(|k| (1 << k) - 1))
+            .chain((32..63).map(|k| (1 << k) + 1))
+            .chain([u64::MAX, u64::MAX - 1]);
+        for d in divisors {
+            let divider = DivRemU64::divide_by(d);
+            assert_eq!(divider.divisor(), d);
+            for n in (0u64..1_000).chain([(1 << 32) - 1, 1 << 32, 1 << 63, u64::MAX - 1, u64::MAX])
+            {
+                assert_eq!(divider.div_rem(n), (n / d, n % d));
+                assert_eq!(divider.remainder(n), n % d);
+            }
+        }
+    }
+
+    #[test]
+    fn test_small_mersenne_and_fermat_remainders() {
+        // These are not folded, and go through the multiplication.
+        for d in [3u64, 5, 7, 9, 15, 17, 255, 257, 65535, 65537] {
+            let divider = DivRemU64::divide_by(d);
+            for n in (0u64..1 << 20).chain(u64::MAX - (1 << 16)..=u64::MAX) {
+                assert_eq!(divider.remainder(n), n % d);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rem_op() {
+        let divider = DivRemU64::divide_by(7);
+        assert_eq!(23u64 % divider, 2);
+        assert_eq!(23u64 % &divider, 2);
+        let mut n = 100u64;
+        n %= divider;
+        assert_eq!(n, 2);
+        let mut n = 13u64;
+        n %= &divider;
+        assert_eq!(n, 6);
+    }
+
+    #[test]
+    fn test_floor_i64() {
+        for d in [1u64, 2, 7, 90, 1 << 40, (1 << 61) - 1, 1 << 63, u64::MAX] {
+            let divider = DivRemU64::divide_by(d);
+            for n in (-300i64..300).chain([i64::MIN, i64::MIN + 1, i64::MAX - 1, i64::MAX]) {
+                let (q, r) = if d > i64::MAX as u64 {
+                    let q = if n >= 0 { 0 } else { -1 };
+                    (q, (n as i128 - q as i128 * d as i128) as u64)
+                } else {
+                    (n.div_euclid(d as i64), n.rem_euclid(d as i64) as u64)
+                };
+                assert_eq!(divider.div_floor_i64(n), q);
+                assert_eq!(divider.rem_euclid_i64(n), r);
+                let start = n as i128 - r as i128;
+                assert_eq!(divider.truncate_i64(n), i64::try_from(start).ok());
+            }
+        }
+        let divider = DivRemU64::divide_by(90);
+        assert_eq!(divider.truncate_i64(-1), Some(-90));
+        assert_eq!(divider.truncate_i64(100), Some(90));
+        assert_eq!(divider.truncate_i64(i64::MIN), None);
+    }
+
+    #[test]
+    fn test_map_to_range() {
+        let divider = DivRemU64::divide_by(10);
+        assert_eq!(divider.map_to_range(0), 0);
+        assert_eq!(divider.map_to_range(u64::MAX), 9);
+        assert_eq!(divider.map_to_range(1 << 63), 5);
+        let mut counts = [0u32; 10];
+        for i in 0..1_000u64 {
+            counts[divider.map_to_range(i.wrapping_mul(u64::MAX / 1_000)) as usize] += 1;
+        }
+        assert!(counts.iter().all(|count| (99..=101).contains(count)));
+    }
+
+    #[test]
+    fn test_threshold() {
+        for d in [
+            1u64,
+            2,
+            3,
+            7,
+            10,
+            1 << 40,
+            (1 << 61) - 1,
+            (1 << 63) + 1,
+            u64::MAX,
+        ] {
+            let divider = DivRemU64::divide_by(d);
+            assert_eq!(divider.threshold() as u128, (1u128 << 64) % d as u128);
+        }
+    }
+
+    #[test]
+    fn test_debias() {
+        // 2^64 % 3 = 1: only the x for which x * 3 = 0 mod 2^64 is rejected.
+        let divider = DivRemU64::divide_by(3);
+        assert_eq!(divider.debias(0), None);
+        assert_eq!(divider.debias(1), Some(0));
+        assert_eq!(divider.debias(u64::MAX), Some(2));
+        let divider = DivRemU64::divide_by((1 << 63) + 1);
+        assert_eq!(divider.debias(0), None);
+        assert_eq!(divider.debias(u64::MAX), Some(1 << 63));
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(10000))]
+        #[test]
+        fn test_proptest_debias(x in any::<u64>(), d in 1..u64::MAX) {
+            let product = x as u128 * d as u128;
+            let expected = if ((product as u64) as u128) < (1u128 << 64) % d as u128 {
+                None
+            } else {
+                Some((product >> 64) as u64)
+            };
+            prop_assert_eq!(DivRemU64::divide_by(d).debias(x), expected);
+        }
+
+        #[test]
+        fn test_proptest_div_rem(n in any::<u64>(), d in 1..u64::MAX) {
+            let divider = DivRemU64::divide_by(d);
+            assert_eq!(divider.div_rem(n), (n / d, n % d));
+            assert_eq!(divider.remainder(n), n % d);
+        }
+
+        #[test]
+        fn test_proptest_divisibility_check(k in any::<u64>(), d in 1..u64::MAX, offset in 0u64..3) {
+            let n = (k % (u64::MAX / d + 1) * d).wrapping_add(offset);
+            assert_eq!(DivisibilityCheck::new(d).is_divisible(n), n % d == 0);
+        }
+
+        #[test]
+        fn test_proptest_fold(n in any::<u64>(), k in 32..64u32, plus_one in any::<bool>()) {
+            let d = if plus_one && k < 63 { (1u64 << k) + 1 } else { (1u64 << k) - 1 };
+            assert_eq!(DivRemU64::divide_by(d).remainder(n), n % d);
+        }
+    }
+}
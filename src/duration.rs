@@ -0,0 +1,172 @@
+//! Division of `Duration`s.
+
+use core::convert::TryFrom;
+use core::time::Duration;
+
+use crate::{libdivide_128_div_64_to_64, libdivide_mullhi_u64, DivRemU64, MulHiDivider};
+
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+impl DivRemU64 {
+    /// Returns `d / divisor`, rounded down to the nanosecond.
+    ///
+    /// Unlike with `Duration`'s `Div<u32>`, the divisor is a `u64`. The seconds
+    /// are divided by the divider, and so is the count of nanoseconds of the
+    /// remaining seconds, unless it exceeds 64 bits.
+    pub fn divide_duration(&self, d: Duration) -> Duration {
+        let (secs, rem_secs) = self.div_rem(d.as_secs());
+        // rem_secs * 10^9 + subsec_nanos < divisor * 10^9, so the quotient
+        // fits in the nanoseconds of a `Duration`.
+        let (lo, carry) = rem_secs
+            .wrapping_mul(NANOS_PER_SEC)
+            .overflowing_add(d.subsec_nanos() as u64);
+        let hi = libdivide_mullhi_u64(rem_secs, NANOS_PER_SEC) + carry as u64;
+        let nanos = if hi == 0 {
+            self.divide(lo)
+        } else {
+            libdivide_128_div_64_to_64(hi, lo, self.divisor()).0
+        };
+        Duration::new(secs, nanos as u32)
+    }
+}
+
+/// Precomputed divider counting how many times a fixed interval fits
+/// in a `Duration`, such as the number of elapsed ticks of a timer.
+///
+/// It divides the `u128` count of nanoseconds with a [`MulHiDivider`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DurationDivider {
+    interval: Duration,
+    interval_nanos: u128,
+    divider: MulHiDivider<u128>,
+}
+
+impl DurationDivider {
+    /// Precomputes a divider for `interval`.
+    ///
+    /// Panics if `interval` is zero.
+    pub fn new(interval: Duration) -> DurationDivider {
+        assert!(!interval.is_zero());
+        let interval_nanos = interval.as_nanos();
+        DurationDivider {
+            interval,
+            interval_nanos,
+            divider: MulHiDivider::divide_by(interval_nanos),
+        }
+    }
+
+    /// Returns the interval.
+    #[inline(always)]
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Returns the number of whole intervals in `elapsed`,
+    /// saturating at `u64::MAX`.
+    #[inline]
+    pub fn count_in(&self, elapsed: Duration) -> u64 {
+        let count = self.divider.divide(elapsed.as_nanos());
+        u64::try_from(count).unwrap_or(u64::MAX)
+    }
+
+    /// Returns what remains of `elapsed` after its whole intervals,
+    /// such as the time elapsed since the last tick.
+    #[inline]
+    pub fn rem(&self, elapsed: Duration) -> Duration {
+        let nanos = elapsed.as_nanos();
+        let rem = nanos - self.divider.divide(nanos) * self.interval_nanos;
+        // The remainder is lower than the interval, which is a `Duration`.
+        match u64::try_from(rem) {
+            Ok(rem) => Duration::from_nanos(rem),
+            Err(_) => Duration::new(
+                (rem / NANOS_PER_SEC as u128) as u64,
+                (rem % NANOS_PER_SEC as u128) as u32,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::convert::TryFrom;
+    use core::time::Duration;
+
+    use crate::{DivRemU64, DurationDivider};
+    use proptest::prelude::*;
+
+    fn divide_nanos(duration: Duration, d: u64) -> Duration {
+        let nanos = duration.as_nanos() / d as u128;
+        Duration::new(
+            u64::try_from(nanos / 1_000_000_000).unwrap(),
+            (nanos % 1_000_000_000) as u32,
+        )
+    }
+
+    #[test]
+    fn test_divide_duration() {
+        let divider = DivRemU64::divide_by(3);
+        assert_eq!(
+            divider.divide_duration(Duration::from_secs(10)),
+            Duration::new(3, 333_333_333)
+        );
+        assert_eq!(divider.divide_duration(Duration::ZERO), Duration::ZERO);
+        let durations = [
+            Duration::from_nanos(1),
+            Duration::new(1, 999_999_999),
+            Duration::new(u64::MAX, 999_999_999),
+        ];
+        for d in [1u64, 2, 3, 7, 1_000, u32::MAX as u64, 1 << 40, u64::MAX] {
+            let divider = DivRemU64::divide_by(d);
+            for &duration in &durations {
+                assert_eq!(divider.divide_duration(duration), divide_nanos(duration, d));
+            }
+        }
+    }
+
+    #[test]
+    fn test_duration_divider() {
+        let divider = DurationDivider::new(Duration::from_millis(16));
+        assert_eq!(divider.interval(), Duration::from_millis(16));
+        assert_eq!(divider.count_in(Duration::from_millis(100)), 6);
+        assert_eq!(
+            divider.rem(Duration::from_millis(100)),
+            Duration::from_millis(4)
+        );
+        assert_eq!(divider.count_in(Duration::ZERO), 0);
+        assert_eq!(
+            divider.rem(Duration::from_millis(15)),
+            Duration::from_millis(15)
+        );
+        let nanosecond = DurationDivider::new(Duration::from_nanos(1));
+        assert_eq!(nanosecond.count_in(Duration::MAX), u64::MAX);
+        assert_eq!(nanosecond.rem(Duration::MAX), Duration::ZERO);
+        let max = DurationDivider::new(Duration::MAX);
+        assert_eq!(max.count_in(Duration::MAX), 1);
+        assert_eq!(
+            max.rem(Duration::new(u64::MAX, 5)),
+            Duration::new(u64::MAX, 5)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_duration_divider_zero() {
+        DurationDivider::new(Duration::ZERO);
+    }
+
+    proptest! {
+        #[test]
+        fn test_proptest_divide_duration(secs in any::<u64>(), nanos in 0u32..1_000_000_000, d in 1..u64::MAX) {
+            let duration = Duration::new(secs, nanos);
+            assert_eq!(DivRemU64::divide_by(d).divide_duration(duration), divide_nanos(duration, d));
+        }
+
+        #[test]
+        fn test_proptest_duration_divider(elapsed_nanos in any::<u64>(), interval_nanos in 1..u64::MAX) {
+            let elapsed = Duration::from_nanos(elapsed_nanos);
+            let divider = DurationDivider::new(Duration::from_nanos(interval_nanos));
+            assert_eq!(divider.count_in(elapsed), elapsed_nanos / interval_nanos);
+            assert_eq!(divider.rem(elapsed), Duration::from_nanos(elapsed_nanos % interval_nanos));
+        }
+    }
+}
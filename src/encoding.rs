@@ -0,0 +1,87 @@
+//! Versioned binary encoding of the dividers, for persistent storage.
+//!
+//! The encoding is guaranteed to stay readable by all of the future versions
+//! of this crate. Version 1 takes 9 bytes:
+//!
+//! | bytes  | content                          |
+//! |--------|----------------------------------|
+//! | 0      | the version, 1                   |
+//! | 1..9   | the divisor, in little endian    |
+//!
+//! Storing the divisor rather than the magic numbers keeps the encoding
+//! independent from the algorithm: the divider is recomputed when decoding.
+
+use crate::DividerU64;
+
+const VERSION: u8 = 1;
+
+impl DividerU64 {
+    /// The length of the encoding written by `encode_into`.
+    pub const ENCODED_LEN: usize = 9;
+
+    /// Writes the encoding of the divider at the beginning of `out`,
+    /// and returns its length, `ENCODED_LEN`.
+    ///
+    /// Panics if `out` is shorter than `ENCODED_LEN`.
+    pub fn encode_into(&self, out: &mut [u8]) -> usize {
+        assert!(
+            out.len() >= Self::ENCODED_LEN,
+            "the encoding of a divider takes {} bytes",
+            Self::ENCODED_LEN
+        );
+        out[0] = VERSION;
+        out[1..Self::ENCODED_LEN].copy_from_slice(&self.divisor().to_le_bytes());
+        Self::ENCODED_LEN
+    }
+
+    /// Reads a divider encoded by `encode_into` at the beginning of `bytes`.
+    ///
+    /// Returns `None` if `bytes` is too short, if its version is unknown,
+    /// or if the encoded divisor is 0.
+    pub fn decode_from(bytes: &[u8]) -> Option<DividerU64> {
+        if bytes.len() < Self::ENCODED_LEN || bytes[0] != VERSION {
+            return None;
+        }
+        let mut divisor = [0u8; 8];
+        divisor.copy_from_slice(&bytes[1..Self::ENCODED_LEN]);
+        match u64::from_le_bytes(divisor) {
+            0 => None,
+            divisor => Some(DividerU64::divide_by(divisor)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::DividerU64;
+
+    #[test]
+    fn test_encoding_round_trip() {
+        let mut buffer = [0u8; 12];
+        for d in [1u64, 7, 14, 1 << 40, (1 << 63) + 1, u64::MAX] {
+            let divider = DividerU64::divide_by(d);
+            assert_eq!(divider.encode_into(&mut buffer), DividerU64::ENCODED_LEN);
+            assert_eq!(DividerU64::decode_from(&buffer), Some(divider));
+        }
+    }
+
+    #[test]
+    fn test_encoding_layout() {
+        let mut buffer = [0u8; DividerU64::ENCODED_LEN];
+        DividerU64::divide_by(0x0102).encode_into(&mut buffer);
+        assert_eq!(buffer, [1, 2, 1, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_decoding_invalid() {
+        assert_eq!(DividerU64::decode_from(&[1, 7, 0, 0, 0, 0, 0, 0]), None);
+        assert_eq!(DividerU64::decode_from(&[2, 7, 0, 0, 0, 0, 0, 0, 0]), None);
+        assert_eq!(DividerU64::decode_from(&[1, 0, 0, 0, 0, 0, 0, 0, 0]), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_encoding_buffer_too_short() {
+        DividerU64::divide_by(7).encode_into(&mut [0u8; 8]);
+    }
+}
@@ -0,0 +1,92 @@
+//! C interface, with the `ffi` feature.
+//!
+//! The dividers cross the boundary as [`RawDividerU64`], a `#[repr(C)]`
+//! struct of 16 bytes. The functions are `#[no_mangle]`, so that a
+//! `staticlib` or `cdylib` crate depending on `fastdivide` exports them,
+//! and their header can be generated with cbindgen.
+
+use crate::{DividerU64, RawDividerU64};
+
+fn divider(raw: &RawDividerU64) -> DividerU64 {
+    raw.decode()
+        .expect("invalid divider, which fastdivide_u64_gen did not return")
+}
+
+/// Precomputes a divider for `divisor`.
+///
+/// Aborts if `divisor` is 0.
+#[no_mangle]
+pub extern "C" fn fastdivide_u64_gen(divisor: u64) -> RawDividerU64 {
+    DividerU64::divide_by(divisor).to_raw()
+}
+
+/// Returns `numerator / divisor`.
+///
+/// # Safety
+///
+/// `divider` must point to a divider returned by `fastdivide_u64_gen`.
+#[no_mangle]
+pub unsafe extern "C" fn fastdivide_u64_do(numerator: u64, divider: *const RawDividerU64) -> u64 {
+    self::divider(&*divider).divide(numerator)
+}
+
+/// Divides the `len` values of `values` in place.
+///
+/// # Safety
+///
+/// `divider` must point to a divider returned by `fastdivide_u64_gen`,
+/// and `values` must be valid for reads and writes of `len` values.
+#[no_mangle]
+pub unsafe extern "C" fn fastdivide_u64_do_slice(
+    divider: *const RawDividerU64,
+    values: *mut u64,
+    len: usize,
+) {
+    if len == 0 {
+        return;
+    }
+    let values = core::slice::from_raw_parts_mut(values, len);
+    self::divider(&*divider).divide_slice_in_place(values);
+}
+
+/// Returns the divisor `divider` was precomputed for.
+///
+/// # Safety
+///
+/// `divider` must point to a divider returned by `fastdivide_u64_gen`.
+#[no_mangle]
+pub unsafe extern "C" fn fastdivide_u64_divisor(divider: *const RawDividerU64) -> u64 {
+    self::divider(&*divider).divisor()
+}
+
+/// Returns true if `divider` is a divider `fastdivide_u64_gen` may have returned,
+/// for instance after reading it from an untrusted file.
+///
+/// # Safety
+///
+/// `divider` must point to 16 readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn fastdivide_u64_is_valid(divider: *const RawDividerU64) -> bool {
+    (*divider).to_divider().is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ffi() {
+        for d in [1u64, 7, 14, 1 << 40, (1 << 63) + 1, u64::MAX] {
+            let divider = fastdivide_u64_gen(d);
+            unsafe {
+                assert!(fastdivide_u64_is_valid(&divider));
+                assert_eq!(fastdivide_u64_divisor(&divider), d);
+                assert_eq!(fastdivide_u64_do(u64::MAX, &divider), u64::MAX / d);
+                let mut values = [0u64, 100, u64::MAX];
+                fastdivide_u64_do_slice(&divider, values.as_mut_ptr(), values.len());
+                assert_eq!(values, [0, 100 / d, u64::MAX / d]);
+                fastdivide_u64_do_slice(&divider, core::ptr::null_mut(), 0);
+            }
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! Division of fixed-point numbers, behind the `fixed` feature.
+//!
+//! `FixedU64<Frac>` and `FixedI64<Frac>` can be divided by a `DividerU64`
+//! with `/` and `/=`. The bits of the numerator are divided, so that the
+//! quotient has the same fractional bits. As with integer division by the
+//! divisor, unsigned quotients are truncated, and signed quotients are
+//! rounded towards zero.
+//!
+//! ```
+//! use fastdivide::DividerU64;
+//! use fixed::types::{I32F32, U48F16};
+//!
+//! let window_len = DividerU64::divide_by(4);
+//! assert_eq!(U48F16::from_num(10.5) / window_len, U48F16::from_num(2.625));
+//! assert_eq!(I32F32::from_num(-3) / window_len, I32F32::from_num(-0.75));
+//! ```
+
+use core::ops::{Div, DivAssign};
+
+use fixed::types::extra::LeEqU64;
+use fixed::{FixedI64, FixedU64};
+
+use crate::DividerU64;
+
+impl<Frac: LeEqU64> Div<DividerU64> for FixedU64<Frac> {
+    type Output = FixedU64<Frac>;
+
+    #[inline(always)]
+    fn div(self, denom: DividerU64) -> Self::Output {
+        FixedU64::from_bits(denom.divide(self.to_bits()))
+    }
+}
+
+impl<Frac: LeEqU64> DivAssign<DividerU64> for FixedU64<Frac> {
+    #[inline(always)]
+    fn div_assign(&mut self, denom: DividerU64) {
+        *self = *self / denom;
+    }
+}
+
+impl<Frac: LeEqU64> Div<DividerU64> for FixedI64<Frac> {
+    type Output = FixedI64<Frac>;
+
+    #[inline(always)]
+    fn div(self, denom: DividerU64) -> Self::Output {
+        FixedI64::from_bits(denom.divide_i64(self.to_bits()))
+    }
+}
+
+impl<Frac: LeEqU64> DivAssign<DividerU64> for FixedI64<Frac> {
+    #[inline(always)]
+    fn div_assign(&mut self, denom: DividerU64) {
+        *self = *self / denom;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::DividerU64;
+    use fixed::types::{I0F64, I32F32, I64F0, U0F64, U60F4};
+
+    #[test]
+    fn test_divide_fixed_u64() {
+        for d in [1u64, 3, 7, 16, u64::MAX] {
+            let divider = DividerU64::divide_by(d);
+            for n in [
+                U60F4::ZERO,
+                U60F4::DELTA,
+                U60F4::from_num(1234.5625),
+                U60F4::MAX,
+            ] {
+                assert_eq!(n / divider, n / d);
+            }
+            let mut n = U0F64::MAX;
+            n /= divider;
+            assert_eq!(n, U0F64::MAX / d);
+        }
+    }
+
+    #[test]
+    fn test_divide_fixed_i64() {
+        for d in [1u64, 3, 7, 16, i64::MAX as u64] {
+            let divider = DividerU64::divide_by(d);
+            for n in [
+                I32F32::MIN + I32F32::DELTA,
+                I32F32::from_num(-2.5),
+                I32F32::DELTA,
+                I32F32::MAX,
+            ] {
+                assert_eq!(n / divider, n / d as i64);
+            }
+            let mut n = I0F64::MIN + I0F64::DELTA;
+            n /= divider;
+            assert_eq!(n, (I0F64::MIN + I0F64::DELTA) / d as i64);
+        }
+        // Rounded towards zero, like `i64` division.
+        assert_eq!(
+            I64F0::from_num(-7) / DividerU64::divide_by(2),
+            I64F0::from_num(-3)
+        );
+        assert_eq!(I64F0::MIN / DividerU64::divide_by(1), I64F0::MIN);
+    }
+}
@@ -0,0 +1,61 @@
+//! `Fn` implementations, making it possible to use the dividers as closures:
+//! `values.iter().copied().map(divider)`.
+
+use crate::{
+    BranchFreeDividerU64, CtDividerU64, DivRemU64, DividerU32, DividerU64, DividerU64By32,
+};
+
+macro_rules! impl_fn {
+    ($divider:ty, $t:ty) => {
+        impl FnOnce<($t,)> for $divider {
+            type Output = $t;
+
+            #[inline(always)]
+            extern "rust-call" fn call_once(self, (n,): ($t,)) -> $t {
+                self.divide(n)
+            }
+        }
+
+        impl FnMut<($t,)> for $divider {
+            #[inline(always)]
+            extern "rust-call" fn call_mut(&mut self, (n,): ($t,)) -> $t {
+                self.divide(n)
+            }
+        }
+
+        impl Fn<($t,)> for $divider {
+            #[inline(always)]
+            extern "rust-call" fn call(&self, (n,): ($t,)) -> $t {
+                self.divide(n)
+            }
+        }
+    };
+}
+
+impl_fn!(DividerU64, u64);
+impl_fn!(BranchFreeDividerU64, u64);
+impl_fn!(CtDividerU64, u64);
+impl_fn!(DivRemU64, u64);
+impl_fn!(DividerU64By32, u64);
+impl_fn!(DividerU32, u32);
+
+#[cfg(test)]
+mod tests {
+    use crate::{DivRemU64, DividerU32, DividerU64};
+    use std::vec::Vec;
+
+    #[test]
+    fn test_fn_traits() {
+        let values = [0u64, 6, 7, 100, u64::MAX];
+        let quotients: Vec<u64> = values
+            .iter()
+            .copied()
+            .map(DividerU64::divide_by(7))
+            .collect();
+        assert_eq!(quotients, values.iter().map(|n| n / 7).collect::<Vec<_>>());
+        let divider = DivRemU64::divide_by(7);
+        assert_eq!(divider(100), 14);
+        let divider = DividerU32::divide_by(7);
+        assert_eq!(divider(u32::MAX), u32::MAX / 7);
+    }
+}
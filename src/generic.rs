@@ -0,0 +1,282 @@
+//! Abstraction over the integer widths.
+
+use crate::{
+    BranchFreeDividerU64, CtDividerU64, DivRemU64, DividerU128, DividerU32, DividerU64,
+    DividerU64By32,
+};
+
+/// A precomputed divider for numerators of type `T`.
+///
+/// This makes it possible to write generic code once for all of the supported
+/// widths, e.g. a histogram over `u16` or `u64` values.
+/// `u8` and `u16` are divided with `DividerU32`, and signed numerators
+/// with a `SignedDivider` over the divider of the matching unsigned width.
+///
+/// `ApproxDividerU64` does not implement this trait, as its quotients are not exact.
+pub trait Divider<T>: Copy {
+    /// Precomputes a divider for `divisor`.
+    ///
+    /// Panics if `divisor` is 0.
+    fn divide_by(divisor: T) -> Self;
+
+    /// Returns `n / divisor`.
+    fn divide(&self, n: T) -> T;
+}
+
+macro_rules! impl_divider {
+    ($divider:ty, $t:ty) => {
+        impl Divider<$t> for $divider {
+            #[inline(always)]
+            fn divide_by(divisor: $t) -> Self {
+                <$divider>::divide_by(divisor)
+            }
+
+            #[inline(always)]
+            fn divide(&self, n: $t) -> $t {
+                <$divider>::divide(self, n)
+            }
+        }
+    };
+}
+
+impl_divider!(DividerU64, u64);
+impl_divider!(BranchFreeDividerU64, u64);
+impl_divider!(CtDividerU64, u64);
+impl_divider!(DivRemU64, u64);
+impl_divider!(DividerU32, u32);
+impl_divider!(DividerU128, u128);
+
+// The narrower widths are divided with the `u32` algorithm.
+macro_rules! impl_narrow_divider {
+    ($t:ty) => {
+        impl Divider<$t> for DividerU32 {
+            #[inline(always)]
+            fn divide_by(divisor: $t) -> Self {
+                DividerU32::divide_by(divisor as u32)
+            }
+
+            #[inline(always)]
+            fn divide(&self, n: $t) -> $t {
+                DividerU32::divide(self, n as u32) as $t
+            }
+        }
+    };
+}
+
+impl_narrow_divider!(u8);
+impl_narrow_divider!(u16);
+
+/// Divider for signed numerators, built on top of the unsigned divider `D`.
+///
+/// Like `/`, the quotient is truncated towards zero. Dividing the minimum value
+/// by -1 wraps around to the minimum value, instead of panicking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SignedDivider<D> {
+    /// Divider by the absolute value of the divisor.
+    magnitude: D,
+    negative: bool,
+}
+
+macro_rules! impl_signed_divider {
+    ($divider:ty, $t:ty, $unsigned:ty) => {
+        impl Divider<$t> for SignedDivider<$divider> {
+            #[inline(always)]
+            fn divide_by(divisor: $t) -> Self {
+                SignedDivider {
+                    magnitude: <$divider as Divider<$unsigned>>::divide_by(divisor.unsigned_abs()),
+                    negative: divisor < 0,
+                }
+            }
+
+            #[inline(always)]
+            fn divide(&self, n: $t) -> $t {
+                let quotient =
+                    <$divider as Divider<$unsigned>>::divide(&self.magnitude, n.unsigned_abs())
+                        as $t;
+                if (n < 0) != self.negative {
+                    quotient.wrapping_neg()
+                } else {
+                    quotient
+                }
+            }
+        }
+    };
+}
+
+impl_signed_divider!(DividerU32, i8, u8);
+impl_signed_divider!(DividerU32, i16, u16);
+impl_signed_divider!(DividerU32, i32, u32);
+impl_signed_divider!(DividerU64, i64, u64);
+impl_signed_divider!(DividerU128, i128, u128);
+
+/// Object-safe interface over all of the exact dividers, whatever their width
+/// and their algorithm, so that they can be stored as `Box<dyn Divide>`.
+pub trait Divide {
+    /// Returns the largest numerator the divider accepts.
+    fn max_numerator(&self) -> u64;
+
+    /// Returns `n / divisor`.
+    ///
+    /// Panics if `n` is larger than `max_numerator()`.
+    fn divide_u64(&self, n: u64) -> u64;
+
+    /// Returns `n / divisor`.
+    fn divide_u32(&self, n: u32) -> u32 {
+        self.divide_u64(n as u64) as u32
+    }
+}
+
+macro_rules! impl_divide_u64 {
+    ($divider:ty) => {
+        impl Divide for $divider {
+            #[inline]
+            fn max_numerator(&self) -> u64 {
+                u64::MAX
+            }
+
+            #[inline]
+            fn divide_u64(&self, n: u64) -> u64 {
+                <$divider>::divide(self, n)
+            }
+        }
+    };
+}
+
+impl_divide_u64!(DividerU64);
+impl_divide_u64!(BranchFreeDividerU64);
+impl_divide_u64!(CtDividerU64);
+impl_divide_u64!(DivRemU64);
+impl_divide_u64!(DividerU64By32);
+
+// Every `u64` numerator is a valid `u128` numerator.
+impl Divide for DividerU128 {
+    #[inline]
+    fn max_numerator(&self) -> u64 {
+        u64::MAX
+    }
+
+    #[inline]
+    fn divide_u64(&self, n: u64) -> u64 {
+        DividerU128::divide(self, n as u128) as u64
+    }
+}
+
+impl Divide for DividerU32 {
+    #[inline]
+    fn max_numerator(&self) -> u64 {
+        u32::MAX as u64
+    }
+
+    #[inline]
+    fn divide_u64(&self, n: u64) -> u64 {
+        assert!(
+            n <= u32::MAX as u64,
+            "numerator {} does not fit in a u32",
+            n
+        );
+        DividerU32::divide(self, n as u32) as u64
+    }
+
+    #[inline]
+    fn divide_u32(&self, n: u32) -> u32 {
+        DividerU32::divide(self, n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Divide, Divider, SignedDivider};
+    use crate::{
+        BranchFreeDividerU64, CtDividerU64, DivRemU64, DividerU128, DividerU32, DividerU64,
+        DividerU64By32,
+    };
+    use core::fmt::Debug;
+    use core::ops::Div;
+    use std::boxed::Box;
+    use std::vec::Vec;
+
+    fn check<T, D>(divisors: &[T], numerators: &[T])
+    where
+        T: Copy + Debug + PartialEq + Div<Output = T>,
+        D: Divider<T>,
+    {
+        for &d in divisors {
+            let divider = D::divide_by(d);
+            for &n in numerators {
+                assert_eq!(divider.divide(n), n / d);
+            }
+        }
+    }
+
+    #[test]
+    fn test_generic_divider() {
+        let divisors = [1u64, 2, 3, 7, 14, 1 << 40, u64::MAX];
+        let numerators = [0u64, 1, 6, 7, 1 << 50, u64::MAX];
+        check::<u64, DividerU64>(&divisors, &numerators);
+        check::<u64, BranchFreeDividerU64>(&divisors, &numerators);
+        check::<u64, CtDividerU64>(&divisors, &numerators);
+        check::<u64, DivRemU64>(&divisors, &numerators);
+        check::<u32, DividerU32>(&[1, 3, 7, u32::MAX], &[0, 1, 6, 7, u32::MAX]);
+        check::<u16, DividerU32>(&[1, 3, 7, u16::MAX], &[0, 1, 6, 7, u16::MAX]);
+        check::<u8, DividerU32>(&[1, 3, 7, u8::MAX], &[0, 1, 6, 7, u8::MAX]);
+        check::<u128, DividerU128>(&[1, 3, 7, 1 << 100, u128::MAX], &[0, 1, 6, 7, u128::MAX]);
+    }
+
+    macro_rules! check_signed {
+        ($t:ty, $divider:ty) => {{
+            let values = [<$t>::MIN, <$t>::MIN + 1, -7, -6, -1, 1, 2, 6, 7, <$t>::MAX];
+            for &d in &values {
+                let divider = <SignedDivider<$divider> as Divider<$t>>::divide_by(d);
+                for &n in values.iter().chain([0].iter()) {
+                    assert_eq!(divider.divide(n), n.wrapping_div(d), "{} / {}", n, d);
+                }
+            }
+        }};
+    }
+
+    #[test]
+    fn test_signed_divider() {
+        check_signed!(i8, DividerU32);
+        check_signed!(i16, DividerU32);
+        check_signed!(i32, DividerU32);
+        check_signed!(i64, DividerU64);
+        check_signed!(i128, DividerU128);
+    }
+
+    #[test]
+    fn test_signed_divider_exhaustive_i8() {
+        for d in (i8::MIN..=i8::MAX).filter(|&d| d != 0) {
+            let divider = <SignedDivider<DividerU32> as Divider<i8>>::divide_by(d);
+            for n in i8::MIN..=i8::MAX {
+                assert_eq!(divider.divide(n), n.wrapping_div(d), "{} / {}", n, d);
+            }
+        }
+    }
+
+    #[test]
+    fn test_dyn_divide() {
+        let dividers: Vec<Box<dyn Divide>> = vec![
+            Box::new(DividerU64::divide_by(7)),
+            Box::new(BranchFreeDividerU64::divide_by(7)),
+            Box::new(CtDividerU64::divide_by(7)),
+            Box::new(DivRemU64::divide_by(7)),
+            Box::new(DividerU64By32::divide_by(7)),
+            Box::new(DividerU32::divide_by(7)),
+            Box::new(DividerU128::divide_by(7)),
+        ];
+        for divider in &dividers {
+            assert_eq!(divider.divide_u32(u32::MAX), u32::MAX / 7);
+            assert_eq!(divider.divide_u64(100), 14);
+            let n = divider.max_numerator();
+            assert_eq!(divider.divide_u64(n), n / 7);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_dyn_divide_numerator_too_large() {
+        let divider: Box<dyn Divide> = Box::new(DividerU32::divide_by(7));
+        divider.divide_u64(1 << 32);
+    }
+}
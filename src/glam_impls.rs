@@ -0,0 +1,104 @@
+//! Componentwise division of the glam integer vectors, behind the `glam` feature.
+//!
+//! `UVec2`, `UVec3` and `UVec4` are divided by a `DividerU32`, and `IVec2`,
+//! `IVec3` and `IVec4` by a `SignedDivider<DividerU32>`, with `/` and `/=`.
+//! As with `IVec2 / i32`, the signed quotients are truncated towards zero.
+//!
+//! ```
+//! use fastdivide::{Divider, DividerU32, SignedDivider};
+//! use glam::{IVec2, UVec3};
+//!
+//! let cell_size = DividerU32::divide_by(16);
+//! assert_eq!(UVec3::new(15, 16, 100) / cell_size, UVec3::new(0, 1, 6));
+//! let cell_size: SignedDivider<DividerU32> = Divider::divide_by(16i32);
+//! assert_eq!(IVec2::new(-33, 33) / cell_size, IVec2::new(-2, 2));
+//! ```
+
+use core::ops::{Div, DivAssign};
+
+use glam::{IVec2, IVec3, IVec4, UVec2, UVec3, UVec4};
+
+use crate::{Divider, DividerU32, SignedDivider};
+
+macro_rules! impl_div_vec {
+    ($vec:ty, $divider:ty, $divide:expr) => {
+        impl Div<$divider> for $vec {
+            type Output = $vec;
+
+            #[inline]
+            fn div(self, divider: $divider) -> $vec {
+                <$vec>::from_array($divide(&divider, self.to_array()))
+            }
+        }
+
+        impl Div<&$divider> for $vec {
+            type Output = $vec;
+
+            #[inline]
+            fn div(self, divider: &$divider) -> $vec {
+                self / *divider
+            }
+        }
+
+        impl DivAssign<$divider> for $vec {
+            #[inline]
+            fn div_assign(&mut self, divider: $divider) {
+                *self = *self / divider;
+            }
+        }
+
+        impl DivAssign<&$divider> for $vec {
+            #[inline]
+            fn div_assign(&mut self, divider: &$divider) {
+                *self = *self / *divider;
+            }
+        }
+    };
+}
+
+fn divide_signed<const N: usize>(
+    divider: &SignedDivider<DividerU32>,
+    values: [i32; N],
+) -> [i32; N] {
+    values.map(|n| divider.divide(n))
+}
+
+impl_div_vec!(UVec2, DividerU32, DividerU32::divide_array);
+impl_div_vec!(UVec3, DividerU32, DividerU32::divide_array);
+impl_div_vec!(UVec4, DividerU32, DividerU32::divide_array);
+impl_div_vec!(IVec2, SignedDivider<DividerU32>, divide_signed);
+impl_div_vec!(IVec3, SignedDivider<DividerU32>, divide_signed);
+impl_div_vec!(IVec4, SignedDivider<DividerU32>, divide_signed);
+
+#[cfg(test)]
+mod tests {
+    use glam::{IVec4, UVec2, UVec4};
+
+    use crate::{Divider, DividerU32, SignedDivider};
+
+    #[test]
+    fn test_divide_uvec() {
+        for d in [1u32, 7, 16, 90, u32::MAX] {
+            let divider = DividerU32::divide_by(d);
+            let v = UVec4::new(0, 1_000, u32::MAX - 1, u32::MAX);
+            assert_eq!(v / divider, v / d);
+            let divider_ref = &divider;
+            assert_eq!(v / divider_ref, v / d);
+            let mut w = UVec2::new(d - 1, d);
+            w /= divider;
+            assert_eq!(w, UVec2::new(0, 1));
+        }
+    }
+
+    #[test]
+    fn test_divide_ivec() {
+        for d in [1i32, -1, 7, -16, 90, i32::MAX, i32::MIN] {
+            let divider: SignedDivider<DividerU32> = Divider::divide_by(d);
+            let v = IVec4::new(-1_000, 1_000, i32::MAX, i32::MIN + 1);
+            assert_eq!(v / divider, v / d);
+            let mut w = v;
+            w /= &divider;
+            assert_eq!(w, v / d);
+        }
+    }
+}
@@ -0,0 +1,626 @@
+//! Generation of GPU shader code dividing by a `DividerU64`.
+//!
+//! WGSL, GLSL and HLSL do not all have 64-bit integers: the values are stored
+//! as pairs of `u32` (low bits first), and the 64-bit arithmetic is emulated
+//! with 32-bit operations, down to 16x16 bits multiplications.
+//!
+//! With the `wgpu` feature, `DividerU64::divide_slice_gpu` dispatches
+//! the WGSL kernels on the default GPU adapter.
+
+use core::fmt::{self, Write};
+#[cfg(feature = "wgpu")]
+use std::borrow::Cow;
+#[cfg(feature = "wgpu")]
+use std::string::String;
+#[cfg(feature = "wgpu")]
+use std::sync::{mpsc, OnceLock};
+#[cfg(feature = "wgpu")]
+use std::vec::Vec;
+
+use crate::DividerU64;
+
+/// 64-bit arithmetic over `vec2<u32>`, shared by all of the kernels.
+const WGSL_U64_HELPERS: &str = "\
+fn add64(x: vec2<u32>, y: vec2<u32>) -> vec2<u32> {
+    let lo = x.x + y.x;
+    return vec2<u32>(lo, x.y + y.y + select(0u, 1u, lo < x.x));
+}
+
+fn sub64(x: vec2<u32>, y: vec2<u32>) -> vec2<u32> {
+    return vec2<u32>(x.x - y.x, x.y - y.y - select(0u, 1u, x.x < y.x));
+}
+
+fn shr64(x: vec2<u32>, shift: u32) -> vec2<u32> {
+    if (shift == 0u) {
+        return x;
+    }
+    if (shift >= 32u) {
+        return vec2<u32>(x.y >> (shift - 32u), 0u);
+    }
+    return vec2<u32>((x.x >> shift) | (x.y << (32u - shift)), x.y >> shift);
+}
+
+fn ge64(x: vec2<u32>, y: vec2<u32>) -> bool {
+    return x.y > y.y || (x.y == y.y && x.x >= y.x);
+}
+
+fn mul32x32(a: u32, b: u32) -> vec2<u32> {
+    let a_lo = a & 0xffffu;
+    let a_hi = a >> 16u;
+    let b_lo = b & 0xffffu;
+    let b_hi = b >> 16u;
+    let lo_lo = a_lo * b_lo;
+    let hi_lo = a_hi * b_lo;
+    let lo_hi = a_lo * b_hi;
+    let hi_hi = a_hi * b_hi;
+    let cross = (lo_lo >> 16u) + (hi_lo & 0xffffu) + lo_hi;
+    return vec2<u32>((cross << 16u) | (lo_lo & 0xffffu), hi_hi + (hi_lo >> 16u) + (cross >> 16u));
+}
+
+fn mulhi64(a: vec2<u32>, b: vec2<u32>) -> vec2<u32> {
+    let lo_lo = mul32x32(a.x, b.x);
+    let hi_lo = mul32x32(a.y, b.x);
+    let lo_hi = mul32x32(a.x, b.y);
+    let hi_hi = mul32x32(a.y, b.y);
+    let cross = add64(add64(vec2<u32>(lo_lo.y, 0u), vec2<u32>(hi_lo.x, 0u)), lo_hi);
+    return add64(add64(hi_hi, vec2<u32>(hi_lo.y, 0u)), vec2<u32>(cross.y, 0u));
+}
+";
+
+/// The GLSL and HLSL version of `WGSL_U64_HELPERS`, `{ty}` being the type
+/// of a pair of `u32`.
+macro_rules! c_u64_helpers {
+    () => {
+        "\
+{ty} add64({ty} x, {ty} y) {{
+    uint lo = x.x + y.x;
+    return {ty}(lo, x.y + y.y + (lo < x.x ? 1u : 0u));
+}}
+
+{ty} sub64({ty} x, {ty} y) {{
+    return {ty}(x.x - y.x, x.y - y.y - (x.x < y.x ? 1u : 0u));
+}}
+
+{ty} shr64({ty} x, uint shift) {{
+    if (shift == 0u) {{
+        return x;
+    }}
+    if (shift >= 32u) {{
+        return {ty}(x.y >> (shift - 32u), 0u);
+    }}
+    return {ty}((x.x >> shift) | (x.y << (32u - shift)), x.y >> shift);
+}}
+
+bool ge64({ty} x, {ty} y) {{
+    return x.y > y.y || (x.y == y.y && x.x >= y.x);
+}}
+
+{ty} mul32x32(uint a, uint b) {{
+    uint a_lo = a & 0xffffu;
+    uint a_hi = a >> 16u;
+    uint b_lo = b & 0xffffu;
+    uint b_hi = b >> 16u;
+    uint lo_lo = a_lo * b_lo;
+    uint hi_lo = a_hi * b_lo;
+    uint lo_hi = a_lo * b_hi;
+    uint hi_hi = a_hi * b_hi;
+    uint cross = (lo_lo >> 16u) + (hi_lo & 0xffffu) + lo_hi;
+    return {ty}((cross << 16u) | (lo_lo & 0xffffu), hi_hi + (hi_lo >> 16u) + (cross >> 16u));
+}}
+
+{ty} mulhi64({ty} a, {ty} b) {{
+    {ty} lo_lo = mul32x32(a.x, b.x);
+    {ty} hi_lo = mul32x32(a.y, b.x);
+    {ty} lo_hi = mul32x32(a.x, b.y);
+    {ty} hi_hi = mul32x32(a.y, b.y);
+    {ty} cross = add64(add64({ty}(lo_lo.y, 0u), {ty}(hi_lo.x, 0u)), lo_hi);
+    return add64(add64(hi_hi, {ty}(hi_lo.y, 0u)), {ty}(cross.y, 0u));
+}}
+"
+    };
+}
+
+/// A shading language.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShaderLanguage {
+    Wgsl,
+    Glsl,
+    Hlsl,
+}
+
+impl ShaderLanguage {
+    /// The type of a `u64`, as a pair of `u32`.
+    fn u64_type(self) -> &'static str {
+        match self {
+            ShaderLanguage::Wgsl => "vec2<u32>",
+            ShaderLanguage::Glsl => "uvec2",
+            ShaderLanguage::Hlsl => "uint2",
+        }
+    }
+
+    fn write_helpers(self, out: &mut impl Write) -> fmt::Result {
+        match self {
+            ShaderLanguage::Wgsl => out.write_str(WGSL_U64_HELPERS),
+            ShaderLanguage::Glsl | ShaderLanguage::Hlsl => {
+                write!(out, c_u64_helpers!(), ty = self.u64_type())
+            }
+        }
+    }
+}
+
+/// Formats a `u64` as a literal of the `u64` type of a shading language.
+struct Literal64(ShaderLanguage, u64);
+
+impl fmt::Display for Literal64 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}({}u, {}u)",
+            self.0.u64_type(),
+            self.1 as u32,
+            (self.1 >> 32) as u32
+        )
+    }
+}
+
+impl DividerU64 {
+    /// Writes a shader function named `name`, taking a `u64` numerator
+    /// and returning its quotient, preceded by the 64-bit arithmetic helpers it uses.
+    ///
+    /// The `u64`s are `vec2<u32>` in WGSL, `uvec2` in GLSL, and `uint2` in HLSL,
+    /// holding the low 32 bits followed by the high 32 bits. The magic constants
+    /// of the divider are embedded in the function, which computes bit-exact results.
+    pub fn write_shader_function(
+        &self,
+        language: ShaderLanguage,
+        name: &str,
+        out: &mut impl Write,
+    ) -> fmt::Result {
+        language.write_helpers(out)?;
+        let ty = language.u64_type();
+        let lit = |val: u64| Literal64(language, val);
+        if language == ShaderLanguage::Wgsl {
+            writeln!(out, "\nfn {}(n: {}) -> {} {{", name, ty, ty)?;
+        } else {
+            writeln!(out, "\n{} {}({} n) {{", ty, name, ty)?;
+        }
+        match *self {
+            DividerU64::Identity => writeln!(out, "    return n;")?,
+            DividerU64::BitShift(shift) => writeln!(out, "    return shr64(n, {}u);", shift)?,
+            DividerU64::Compare(divisor) => {
+                if language == ShaderLanguage::Wgsl {
+                    writeln!(
+                        out,
+                        "    return {}(select(0u, 1u, ge64(n, {})), 0u);",
+                        ty,
+                        lit(divisor)
+                    )?
+                } else {
+                    writeln!(
+                        out,
+                        "    return {}(ge64(n, {}) ? 1u : 0u, 0u);",
+                        ty,
+                        lit(divisor)
+                    )?
+                }
+            }
+            DividerU64::Fast { magic, shift } => writeln!(
+                out,
+                "    return shr64(mulhi64({}, n), {}u);",
+                lit(magic),
+                shift
+            )?,
+            DividerU64::PreShift {
+                magic,
+                pre_shift,
+                shift,
+            } => writeln!(
+                out,
+                "    return shr64(mulhi64({}, shr64(n, {}u)), {}u);",
+                lit(magic),
+                pre_shift,
+                shift
+            )?,
+            DividerU64::General { magic_low, shift } => {
+                if language == ShaderLanguage::Wgsl {
+                    writeln!(out, "    let q = mulhi64({}, n);", lit(magic_low))?;
+                } else {
+                    writeln!(out, "    {} q = mulhi64({}, n);", ty, lit(magic_low))?;
+                }
+                writeln!(
+                    out,
+                    "    return shr64(add64(shr64(sub64(n, q), 1u), q), {}u);",
+                    shift
+                )?;
+            }
+        }
+        out.write_str("}\n")
+    }
+
+    /// Writes a WGSL compute shader dividing, in place, all of the values
+    /// of the storage buffer bound at `@group(0) @binding(0)`.
+    ///
+    /// The buffer is an `array<vec2<u32>>`, each `u64` being stored
+    /// as its low 32 bits followed by its high 32 bits, that is to say
+    /// in little endian. The entry point is `main`, with a workgroup size of 64.
+    ///
+    /// The magic constants of the divider are embedded in the shader,
+    /// which computes bit-exact results.
+    pub fn write_wgsl_kernel(&self, out: &mut impl Write) -> fmt::Result {
+        self.write_shader_function(ShaderLanguage::Wgsl, "divide", out)?;
+        out.write_str(
+            "
+@group(0) @binding(0) var<storage, read_write> values: array<vec2<u32>>;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    if (i >= arrayLength(&values)) {
+        return;
+    }
+    values[i] = divide(values[i]);
+}
+",
+        )
+    }
+}
+
+/// Number of invocations per workgroup of the kernels.
+#[cfg(feature = "wgpu")]
+const WORKGROUP_SIZE: u32 = 64;
+
+/// The device of the default adapter, shared by all of the calls to
+/// `divide_slice_gpu`. `None` if there is no adapter.
+#[cfg(feature = "wgpu")]
+static GPU: OnceLock<Option<(wgpu::Device, wgpu::Queue)>> = OnceLock::new();
+
+#[cfg(feature = "wgpu")]
+fn gpu() -> Option<&'static (wgpu::Device, wgpu::Queue)> {
+    GPU.get_or_init(|| {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let adapter =
+            pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))
+                .ok()?;
+        pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default())).ok()
+    })
+    .as_ref()
+}
+
+#[cfg(feature = "wgpu")]
+impl DividerU64 {
+    /// Same as [`DividerU64::divide_slice_in_place`], but dispatches the
+    /// shader written by [`DividerU64::write_wgsl_kernel`] on the GPU.
+    ///
+    /// The device of the default adapter is requested on the first call.
+    /// If there is no adapter, or if the GPU fails, the values are divided on the CPU.
+    ///
+    /// Transferring the values to the GPU and back costs more than dividing them on
+    /// the CPU: this is only worth it within a larger pipeline of GPU computations.
+    pub fn divide_slice_gpu(&self, values: &mut [u64]) {
+        let done = match gpu() {
+            Some((device, queue)) => self.divide_slice_on(device, queue, values).is_some(),
+            None => false,
+        };
+        if !done {
+            self.divide_slice_in_place(values);
+        }
+    }
+
+    fn divide_slice_on(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        values: &mut [u64],
+    ) -> Option<()> {
+        let mut shader = String::new();
+        self.write_wgsl_kernel(&mut shader).ok()?;
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("fastdivide"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Owned(shader)),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("fastdivide"),
+            layout: None,
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+        // A dispatch is limited both by the size of a storage buffer binding,
+        // and by the number of workgroups.
+        let limits = device.limits();
+        let max_chunk_len = (limits.max_storage_buffer_binding_size as usize / 8)
+            .min(limits.max_compute_workgroups_per_dimension as usize * WORKGROUP_SIZE as usize);
+        for chunk in values.chunks_mut(max_chunk_len.max(1)) {
+            divide_chunk_on(device, queue, &pipeline, chunk)?;
+        }
+        Some(())
+    }
+}
+
+#[cfg(feature = "wgpu")]
+fn divide_chunk_on(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    pipeline: &wgpu::ComputePipeline,
+    values: &mut [u64],
+) -> Option<()> {
+    if values.is_empty() {
+        return Some(());
+    }
+    let size = (values.len() * 8) as u64;
+    let bytes: Vec<u8> = values.iter().flat_map(|n| n.to_le_bytes()).collect();
+    let storage = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("fastdivide values"),
+        size,
+        usage: wgpu::BufferUsages::STORAGE
+            | wgpu::BufferUsages::COPY_DST
+            | wgpu::BufferUsages::COPY_SRC,
+        mapped_at_creation: false,
+    });
+    let readback = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("fastdivide readback"),
+        size,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    queue.write_buffer(&storage, 0, &bytes);
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("fastdivide"),
+        layout: &pipeline.get_bind_group_layout(0),
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: storage.as_entire_binding(),
+        }],
+    });
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    {
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+        pass.set_pipeline(pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.dispatch_workgroups((values.len() as u32).div_ceil(WORKGROUP_SIZE), 1, 1);
+    }
+    encoder.copy_buffer_to_buffer(&storage, 0, &readback, 0, size);
+    queue.submit(Some(encoder.finish()));
+
+    let (sender, receiver) = mpsc::channel();
+    let slice = readback.slice(..);
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        let _ = sender.send(result);
+    });
+    device.poll(wgpu::PollType::Wait).ok()?;
+    receiver.recv().ok()?.ok()?;
+    {
+        let mapped = slice.get_mapped_range();
+        for (n, bytes) in values.iter_mut().zip(mapped.chunks_exact(8)) {
+            let mut le_bytes = [0u8; 8];
+            le_bytes.copy_from_slice(bytes);
+            *n = u64::from_le_bytes(le_bytes);
+        }
+    }
+    readback.unmap();
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DividerU64, ShaderLanguage};
+    use std::string::String;
+
+    #[test]
+    fn test_wgsl_kernel() {
+        for (d, expected) in [
+            (1u64, "    return n;\n"),
+            (8, "    return shr64(n, 3u);\n"),
+            (
+                (1 << 63) + 1,
+                "    return vec2<u32>(select(0u, 1u, ge64(n, vec2<u32>(1u, 2147483648u))), 0u);\n",
+            ),
+            (
+                11,
+                "    return shr64(mulhi64(vec2<u32>(3904515724u, 3123612578u), n), 3u);\n",
+            ),
+        ] {
+            let mut shader = String::new();
+            DividerU64::divide_by(d)
+                .write_wgsl_kernel(&mut shader)
+                .unwrap();
+            assert!(shader.contains("fn main("));
+            assert!(shader.contains(expected), "{}", shader);
+        }
+    }
+
+    #[test]
+    fn test_shader_functions() {
+        let divider = DividerU64::divide_by(7);
+        for (language, signature, general) in [
+            (
+                ShaderLanguage::Wgsl,
+                "fn div7(n: vec2<u32>) -> vec2<u32> {",
+                "    let q = mulhi64(vec2<u32>(",
+            ),
+            (
+                ShaderLanguage::Glsl,
+                "uvec2 div7(uvec2 n) {",
+                "    uvec2 q = mulhi64(uvec2(",
+            ),
+            (
+                ShaderLanguage::Hlsl,
+                "uint2 div7(uint2 n) {",
+                "    uint2 q = mulhi64(uint2(",
+            ),
+        ] {
+            let mut shader = String::new();
+            divider
+                .write_shader_function(language, "div7", &mut shader)
+                .unwrap();
+            assert!(shader.contains("mulhi64("), "{}", shader);
+            assert!(shader.contains(signature), "{}", shader);
+            assert!(shader.contains(general), "{}", shader);
+            assert!(!shader.contains("fn main("));
+        }
+        let mut shader = String::new();
+        DividerU64::divide_by((1 << 63) + 1)
+            .write_shader_function(ShaderLanguage::Hlsl, "div", &mut shader)
+            .unwrap();
+        assert!(
+            shader.contains("    return uint2(ge64(n, uint2(1u, 2147483648u)) ? 1u : 0u, 0u);\n")
+        );
+    }
+    // One divisor for each of the algorithms.
+    const DIVISORS: [u64; 7] = [1, 8, 7, 11, 14, (1 << 63) + 1, u64::MAX];
+
+    #[test]
+    fn test_wgsl_kernel_is_valid() {
+        for &d in &DIVISORS {
+            let mut shader = String::new();
+            DividerU64::divide_by(d)
+                .write_wgsl_kernel(&mut shader)
+                .unwrap();
+            let module = naga::front::wgsl::parse_str(&shader).unwrap();
+            naga::valid::Validator::new(
+                naga::valid::ValidationFlags::all(),
+                naga::valid::Capabilities::empty(),
+            )
+            .validate(&module)
+            .unwrap_or_else(|err| panic!("{:?}\n{}", err, shader));
+        }
+    }
+
+    // Rust versions of the helpers of the shaders, on `[u32; 2]`.
+    // The arithmetic on `u32` wraps around, as it does in the shaders.
+
+    fn add64(x: [u32; 2], y: [u32; 2]) -> [u32; 2] {
+        let lo = x[0].wrapping_add(y[0]);
+        let carry = (lo < x[0]) as u32;
+        [lo, x[1].wrapping_add(y[1]).wrapping_add(carry)]
+    }
+
+    fn sub64(x: [u32; 2], y: [u32; 2]) -> [u32; 2] {
+        let borrow = (x[0] < y[0]) as u32;
+        [
+            x[0].wrapping_sub(y[0]),
+            x[1].wrapping_sub(y[1]).wrapping_sub(borrow),
+        ]
+    }
+
+    fn shr64(x: [u32; 2], shift: u32) -> [u32; 2] {
+        if shift == 0 {
+            return x;
+        }
+        if shift >= 32 {
+            return [x[1] >> (shift - 32), 0];
+        }
+        [(x[0] >> shift) | (x[1] << (32 - shift)), x[1] >> shift]
+    }
+
+    fn ge64(x: [u32; 2], y: [u32; 2]) -> bool {
+        x[1] > y[1] || (x[1] == y[1] && x[0] >= y[0])
+    }
+
+    fn mul32x32(a: u32, b: u32) -> [u32; 2] {
+        let a_lo = a & 0xffff;
+        let a_hi = a >> 16;
+        let b_lo = b & 0xffff;
+        let b_hi = b >> 16;
+        let lo_lo = a_lo * b_lo;
+        let hi_lo = a_hi * b_lo;
+        let lo_hi = a_lo * b_hi;
+        let hi_hi = a_hi * b_hi;
+        let cross = (lo_lo >> 16)
+            .wrapping_add(hi_lo & 0xffff)
+            .wrapping_add(lo_hi);
+        [
+            (cross << 16) | (lo_lo & 0xffff),
+            hi_hi.wrapping_add(hi_lo >> 16).wrapping_add(cross >> 16),
+        ]
+    }
+
+    fn mulhi64(a: [u32; 2], b: [u32; 2]) -> [u32; 2] {
+        let lo_lo = mul32x32(a[0], b[0]);
+        let hi_lo = mul32x32(a[1], b[0]);
+        let lo_hi = mul32x32(a[0], b[1]);
+        let hi_hi = mul32x32(a[1], b[1]);
+        let cross = add64(add64([lo_lo[1], 0], [hi_lo[0], 0]), lo_hi);
+        add64(add64(hi_hi, [hi_lo[1], 0]), [cross[1], 0])
+    }
+
+    fn split(n: u64) -> [u32; 2] {
+        [n as u32, (n >> 32) as u32]
+    }
+
+    /// The function `write_shader_function` writes, for each kind of divider.
+    fn emulate_shader_function(divider: &DividerU64, n: [u32; 2]) -> [u32; 2] {
+        match *divider {
+            DividerU64::Identity => n,
+            DividerU64::BitShift(shift) => shr64(n, shift as u32),
+            DividerU64::Compare(divisor) => [ge64(n, split(divisor)) as u32, 0],
+            DividerU64::Fast { magic, shift } => shr64(mulhi64(split(magic), n), shift as u32),
+            DividerU64::PreShift {
+                magic,
+                pre_shift,
+                shift,
+            } => shr64(
+                mulhi64(split(magic), shr64(n, pre_shift as u32)),
+                shift as u32,
+            ),
+            DividerU64::General { magic_low, shift } => {
+                let q = mulhi64(split(magic_low), n);
+                shr64(add64(shr64(sub64(n, q), 1), q), shift as u32)
+            }
+        }
+    }
+
+    #[test]
+    fn test_emulated_shader_arithmetic() {
+        let mut kinds = std::vec::Vec::new();
+        let divisors = DIVISORS
+            .iter()
+            .copied()
+            .chain(1..1_000)
+            .chain((0..64).map(|i| 1 << i))
+            .chain((1..64).map(|i| (1 << i) - 1))
+            .chain((1..64).map(|i| (1 << i) + 1))
+            .chain([234234131223u64, 6, 28, 1 << 40 | 2]);
+        for d in divisors {
+            let divider = DividerU64::divide_by(d);
+            if !kinds.contains(&core::mem::discriminant(&divider)) {
+                kinds.push(core::mem::discriminant(&divider));
+            }
+            let numerators = (0u64..300)
+                .chain((0..64).map(|i| 1 << i))
+                .chain((1..=64).map(|i| u64::MAX >> (64 - i)))
+                .chain((0..300u64).map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15)))
+                .chain([d - 1, d, d.wrapping_add(1), u64::MAX - 1]);
+            for n in numerators {
+                let [lo, hi] = emulate_shader_function(&divider, split(n));
+                assert_eq!((hi as u64) << 32 | lo as u64, n / d, "{} / {}", n, d);
+            }
+        }
+        assert_eq!(kinds.len(), 6);
+    }
+
+    #[cfg(feature = "wgpu")]
+    #[test]
+    fn test_divide_slice_gpu() {
+        let src: std::vec::Vec<u64> = (0..100_000u64)
+            .map(|i| i.wrapping_mul(152342341223234u64))
+            .chain([0, 1, u64::MAX - 1, u64::MAX])
+            .collect();
+        for &d in &DIVISORS {
+            let divider = DividerU64::divide_by(d);
+            let expected: std::vec::Vec<u64> = src.iter().map(|n| n / d).collect();
+            let mut values = src.clone();
+            divider.divide_slice_gpu(&mut values);
+            assert_eq!(values, expected, "{}", d);
+            // Without the CPU fallback, if there is an adapter.
+            if let Some((device, queue)) = super::gpu() {
+                let mut values = src.clone();
+                assert!(divider
+                    .divide_slice_on(device, queue, &mut values)
+                    .is_some());
+                assert_eq!(values, expected, "{}", d);
+            }
+        }
+    }
+}
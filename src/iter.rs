@@ -0,0 +1,135 @@
+//! Iterator adaptors.
+
+use crate::{DivRemU64, DividerU64, Divisor};
+
+/// Extension trait dividing the items of an iterator of `u64`.
+///
+/// ```
+/// use fastdivide::{DivRemU64, DivideIterExt, DividerU64};
+///
+/// let divider = DividerU64::divide_by(10);
+/// let quotients: Vec<u64> = (0..50).step_by(7).fast_div(divider).collect();
+/// assert_eq!(quotients, vec![0, 0, 1, 2, 2, 3, 4, 4]);
+/// let quotients: Vec<u64> = (0..50).step_by(7).fast_div(10).collect();
+/// assert_eq!(quotients, vec![0, 0, 1, 2, 2, 3, 4, 4]);
+///
+/// let divider = DivRemU64::divide_by(10);
+/// let remainders: Vec<u64> = [12u64, 35].iter().copied().fast_rem(divider).collect();
+/// assert_eq!(remainders, vec![2, 5]);
+/// ```
+pub trait DivideIterExt: Iterator<Item = u64> + Sized {
+    /// Divides each item by `divisor`.
+    ///
+    /// A literal divisor is precomputed once for all of the items.
+    fn fast_div(self, divisor: impl Divisor) -> FastDivIter<Self> {
+        FastDivIter {
+            iter: self,
+            divider: divisor.into_divider(),
+        }
+    }
+
+    /// Returns the remainder of each item divided by `divisor`.
+    ///
+    /// Prefer passing a `DivRemU64` or a literal divisor: a `DividerU64`
+    /// has to recover its divisor first.
+    fn fast_rem(self, divisor: impl Divisor) -> FastRemIter<Self> {
+        FastRemIter {
+            iter: self,
+            divider: divisor.into_div_rem(),
+        }
+    }
+
+    /// Returns the quotient and the remainder of each item divided by `divisor`.
+    ///
+    /// Prefer passing a `DivRemU64` or a literal divisor: a `DividerU64`
+    /// has to recover its divisor first.
+    fn fast_div_rem(self, divisor: impl Divisor) -> FastDivRemIter<Self> {
+        FastDivRemIter {
+            iter: self,
+            divider: divisor.into_div_rem(),
+        }
+    }
+}
+
+impl<I: Iterator<Item = u64>> DivideIterExt for I {}
+
+macro_rules! impl_adaptor {
+    ($adaptor:ident, $divider:ty, $item:ty, $map:expr) => {
+        /// Iterator adaptor returned by [`DivideIterExt`].
+        #[derive(Debug, Clone)]
+        pub struct $adaptor<I> {
+            iter: I,
+            divider: $divider,
+        }
+
+        impl<I: Iterator<Item = u64>> Iterator for $adaptor<I> {
+            type Item = $item;
+
+            #[inline]
+            fn next(&mut self) -> Option<$item> {
+                let n = self.iter.next()?;
+                Some($map(&self.divider, n))
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.iter.size_hint()
+            }
+        }
+
+        impl<I: DoubleEndedIterator<Item = u64>> DoubleEndedIterator for $adaptor<I> {
+            #[inline]
+            fn next_back(&mut self) -> Option<$item> {
+                let n = self.iter.next_back()?;
+                Some($map(&self.divider, n))
+            }
+        }
+
+        impl<I: ExactSizeIterator<Item = u64>> ExactSizeIterator for $adaptor<I> {}
+
+        impl<I: core::iter::FusedIterator<Item = u64>> core::iter::FusedIterator for $adaptor<I> {}
+    };
+}
+
+impl_adaptor!(FastDivIter, DividerU64, u64, DividerU64::divide);
+impl_adaptor!(FastRemIter, DivRemU64, u64, DivRemU64::remainder);
+impl_adaptor!(FastDivRemIter, DivRemU64, (u64, u64), DivRemU64::div_rem);
+
+#[cfg(test)]
+mod tests {
+    use super::DivideIterExt;
+    use crate::{DivRemU64, DividerU64};
+    use std::vec::Vec;
+
+    #[test]
+    fn test_iter_adaptors() {
+        let numerators = [0u64, 1, 7, 14, 1 << 63, u64::MAX];
+        for d in [1u64, 3, 7, 14, 1 << 40, (1 << 61) - 1, u64::MAX] {
+            let divider = DividerU64::divide_by(d);
+            let divrem = DivRemU64::divide_by(d);
+            let quotients: Vec<u64> = numerators.iter().copied().fast_div(divider).collect();
+            assert_eq!(
+                quotients,
+                numerators.iter().map(|n| n / d).collect::<Vec<_>>()
+            );
+            let remainders: Vec<u64> = numerators.iter().copied().fast_rem(divrem).collect();
+            assert_eq!(
+                remainders,
+                numerators.iter().map(|n| n % d).collect::<Vec<_>>()
+            );
+            let div_rems: Vec<(u64, u64)> = numerators
+                .iter()
+                .copied()
+                .fast_div_rem(divrem)
+                .rev()
+                .collect();
+            let expected: Vec<(u64, u64)> =
+                numerators.iter().rev().map(|n| (n / d, n % d)).collect();
+            assert_eq!(div_rems, expected);
+        }
+        assert_eq!(
+            numerators.iter().copied().fast_div(3).len(),
+            numerators.len()
+        );
+    }
+}
@@ -0,0 +1,187 @@
+//! Each of the algorithms used by `DividerU64`, as a standalone type.
+//!
+//! If the class of the divisor is known ahead of time, using one of these types
+//! removes the dispatch on the algorithm from the inner loop.
+//! Code generic over `DivideU64` gets monomorphized for each algorithm.
+
+use crate::{floor_log2, libdivide_mullhi_u64, DividerU64};
+
+/// Common interface of the `u64` dividers.
+pub trait DivideU64 {
+    /// Returns `n / divisor`.
+    fn divide(&self, n: u64) -> u64;
+}
+
+/// Division by a power of 2, as a bit shift.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BitShiftDivider {
+    pub(crate) shift: u8,
+}
+
+impl BitShiftDivider {
+    /// Returns `None` if `divisor` is not a power of 2.
+    pub fn divide_by(divisor: u64) -> Option<BitShiftDivider> {
+        if !divisor.is_power_of_two() {
+            return None;
+        }
+        Some(BitShiftDivider {
+            shift: floor_log2(divisor),
+        })
+    }
+
+    #[inline(always)]
+    pub fn divide(&self, n: u64) -> u64 {
+        n >> self.shift
+    }
+}
+
+/// Division by a divisor with a 64-bits magic number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FastDivider {
+    pub(crate) magic: u64,
+    pub(crate) shift: u8,
+}
+
+impl FastDivider {
+    /// Returns `None` if `divisor` is 0 or does not have a 64-bits magic number.
+    pub fn divide_by(divisor: u64) -> Option<FastDivider> {
+        if divisor == 0 {
+            return None;
+        }
+        match DividerU64::fast_path(divisor)? {
+            DividerU64::Fast { magic, shift } => Some(FastDivider { magic, shift }),
+            _ => None,
+        }
+    }
+
+    #[inline(always)]
+    pub fn divide(&self, n: u64) -> u64 {
+        // The divisor has a magic number that fits in 64 bits.
+        // We get away with a multiplication and a bit-shift.
+        libdivide_mullhi_u64(self.magic, n) >> self.shift
+    }
+}
+
+/// Division by any divisor that is not a power of 2,
+/// using a 65-bits magic number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GeneralDivider {
+    pub(crate) magic_low: u64,
+    pub(crate) shift: u8,
+}
+
+impl GeneralDivider {
+    /// Returns `None` if `divisor` is 0 or a power of 2.
+    pub fn divide_by(divisor: u64) -> Option<GeneralDivider> {
+        if divisor == 0 || divisor.is_power_of_two() {
+            return None;
+        }
+        let (magic_low, shift) = DividerU64::general_magic(divisor);
+        Some(GeneralDivider { magic_low, shift })
+    }
+
+    #[inline(always)]
+    pub fn divide(&self, n: u64) -> u64 {
+        // magic only contains the low 64 bits of our actual magic number which actually has a 65 bits.
+        // The following dance computes n * (magic + 2^64) >> shift
+        let q = libdivide_mullhi_u64(self.magic_low, n);
+        let t = ((n - q) >> 1).wrapping_add(q);
+        t >> self.shift
+    }
+}
+
+impl DivideU64 for BitShiftDivider {
+    #[inline(always)]
+    fn divide(&self, n: u64) -> u64 {
+        BitShiftDivider::divide(self, n)
+    }
+}
+
+impl DivideU64 for FastDivider {
+    #[inline(always)]
+    fn divide(&self, n: u64) -> u64 {
+        FastDivider::divide(self, n)
+    }
+}
+
+impl DivideU64 for GeneralDivider {
+    #[inline(always)]
+    fn divide(&self, n: u64) -> u64 {
+        GeneralDivider::divide(self, n)
+    }
+}
+
+impl From<BitShiftDivider> for DividerU64 {
+    fn from(divider: BitShiftDivider) -> DividerU64 {
+        DividerU64::BitShift(divider.shift)
+    }
+}
+
+impl From<FastDivider> for DividerU64 {
+    fn from(divider: FastDivider) -> DividerU64 {
+        DividerU64::Fast {
+            magic: divider.magic,
+            shift: divider.shift,
+        }
+    }
+}
+
+/// Returns the divider `DividerU64::divide_by` computes for the same divisor,
+/// which is not necessarily `DividerU64::General`: the general algorithm also
+/// works for the divisors eligible to a faster path.
+impl From<GeneralDivider> for DividerU64 {
+    fn from(divider: GeneralDivider) -> DividerU64 {
+        let general = DividerU64::General {
+            magic_low: divider.magic_low,
+            shift: divider.shift,
+        };
+        DividerU64::divide_by(general.divisor())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_divider<D: DivideU64>(divider: &D, d: u64) {
+        for n in (0u64..1_000).chain([u64::MAX - 1, u64::MAX, 1 << 63, 234234131223u64]) {
+            assert_eq!(divider.divide(n), n / d);
+        }
+    }
+
+    #[test]
+    fn test_bitshift_divider() {
+        assert!(BitShiftDivider::divide_by(0).is_none());
+        assert!(BitShiftDivider::divide_by(7).is_none());
+        for d in (0..64).map(|i| 1u64 << i) {
+            check_divider(&BitShiftDivider::divide_by(d).unwrap(), d);
+        }
+    }
+
+    #[test]
+    fn test_fast_divider() {
+        assert!(FastDivider::divide_by(0).is_none());
+        assert!(FastDivider::divide_by(4).is_none());
+        assert!(FastDivider::divide_by(7).is_none());
+        let divider = FastDivider::divide_by(11).unwrap();
+        check_divider(&divider, 11);
+        assert_eq!(DividerU64::from(divider), DividerU64::divide_by(11));
+    }
+
+    #[test]
+    fn test_general_divider() {
+        assert!(GeneralDivider::divide_by(0).is_none());
+        assert!(GeneralDivider::divide_by(4).is_none());
+        // The general algorithm also works for divisors eligible to the fast path.
+        for d in (3u64..300).filter(|d| !d.is_power_of_two()) {
+            let divider = GeneralDivider::divide_by(d).unwrap();
+            check_divider(&divider, d);
+            assert_eq!(DividerU64::from(divider), DividerU64::divide_by(d));
+        }
+        let divider = GeneralDivider::divide_by(7).unwrap();
+        assert_eq!(DividerU64::from(divider), DividerU64::divide_by(7));
+    }
+}
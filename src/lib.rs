@@ -60,13 +60,27 @@ fn histogram(vals: &[u64], min: u64, interval: u64, output: &mut [usize]) {
 
 */
 #![no_std]
-
-#[cfg(feature = "std")]
+#![cfg_attr(
+    all(
+        feature = "nightly",
+        not(any(
+            feature = "soft-mul",
+            all(feature = "mulx", target_arch = "x86_64", target_feature = "bmi2"),
+            target_pointer_width = "32",
+            target_family = "wasm"
+        ))
+    ),
+    feature(widening_mul)
+)]
+#![cfg_attr(feature = "simd", feature(portable_simd))]
+#![cfg_attr(feature = "fn-traits", feature(unboxed_closures, fn_traits))]
+
+#[cfg(any(feature = "std", test))]
+#[cfg_attr(test, macro_use)]
 extern crate std;
 
-#[cfg(test)]
-#[macro_use]
-extern crate std;
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
 // ported from  libdivide.h by ridiculous_fish
 //
@@ -75,20 +89,180 @@ extern crate std;
 
 // This algorithm is described in https://ridiculousfish.com/blog/posts/labor-of-division-episode-i.html
 
+mod approx;
+#[cfg(feature = "arrow")]
+mod arrow_impls;
+#[cfg(target_arch = "x86_64")]
+mod avx2;
+#[cfg(all(feature = "avx512", target_arch = "x86_64"))]
+mod avx512;
+mod backend;
+mod barrett;
+#[cfg(feature = "borsh")]
+mod borsh_impls;
+mod branchfree;
+mod by32;
+#[cfg(feature = "chrono")]
+mod chrono_impls;
+pub mod compat;
+#[cfg(feature = "crypto-bigint")]
+mod crypto_bigint_impls;
+mod ct;
+mod divider_u128;
+mod divider_u32;
+mod divisor;
+mod divrem;
+mod duration;
+mod encoding;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "fixed")]
+mod fixed_impls;
+#[cfg(feature = "fn-traits")]
+mod fn_traits;
+mod generic;
+#[cfg(feature = "glam")]
+mod glam_impls;
+mod gpu;
+mod iter;
+mod kinds;
+mod libdivide;
+mod limbs;
+mod mod64;
+mod montgomery;
+mod mulhi;
+mod mulhi_generic;
+#[cfg(feature = "nalgebra")]
+mod nalgebra_impls;
+#[cfg(feature = "ndarray")]
+mod ndarray_impls;
+#[cfg(all(feature = "neon", target_arch = "aarch64"))]
+mod neon;
+#[cfg(feature = "num")]
+mod num;
+#[cfg(feature = "num-bigint")]
+mod num_bigint_impls;
+mod ops;
+#[cfg(feature = "std")]
+mod par;
+mod primes;
+#[cfg(feature = "python")]
+pub mod python;
+mod rational;
+mod raw;
+mod recipe;
+#[cfg(feature = "rkyv")]
+mod rkyv_impls;
+mod rolling;
+#[cfg(all(feature = "rvv", target_arch = "riscv64"))]
+mod rvv;
+mod seed;
+#[cfg(feature = "serde")]
+mod serde_impls;
+mod shard;
+#[cfg(feature = "simd")]
+mod simd;
+mod slice;
+#[cfg(feature = "proptest")]
+mod strategy;
+#[cfg(all(feature = "sve", target_arch = "aarch64"))]
+mod sve;
+#[cfg(feature = "time")]
+mod time_impls;
+#[cfg(feature = "rand")]
+mod uniform;
+mod universal;
+#[cfg(feature = "wasm-bindgen")]
+pub mod wasm;
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+mod wasm_simd;
+#[cfg(feature = "wide")]
+mod wide_impls;
+mod x4;
+
+#[cfg(all(
+    feature = "simd128",
+    target_arch = "wasm32",
+    not(target_feature = "simd128")
+))]
+compile_error!("the `simd128` feature requires the `simd128` target feature, e.g. `RUSTFLAGS=\"-C target-feature=+simd128\"`");
+
+pub use crate::approx::ApproxDividerU64;
+pub use crate::backend::Backend;
+pub use crate::barrett::Barrett128;
+pub use crate::branchfree::BranchFreeDividerU64;
+pub use crate::by32::DividerU64By32;
+pub use crate::ct::CtDividerU64;
+pub use crate::divider_u128::DividerU128;
+pub use crate::divider_u32::DividerU32;
+pub use crate::divisor::Divisor;
+pub use crate::divrem::DivRemU64;
+pub use crate::duration::DurationDivider;
+pub use crate::generic::{Divide, Divider, SignedDivider};
+pub use crate::gpu::ShaderLanguage;
+pub use crate::iter::{DivideIterExt, FastDivIter, FastDivRemIter, FastRemIter};
+pub use crate::kinds::{BitShiftDivider, DivideU64, FastDivider, GeneralDivider};
+pub use crate::libdivide::{LibdivideU64, LibdivideU64BranchFree};
+pub use crate::mod64::{crt_combine, Mod64};
+pub use crate::montgomery::Montgomery64;
+pub use crate::mulhi_generic::{MulHi, MulHiDivider};
+#[cfg(feature = "num")]
+pub use crate::num::{FastDivisible, IntegerDivider, WithDivisor};
+pub use crate::ops::FastDiv;
+pub use crate::primes::{is_probable_prime, next_prime_divider};
+pub use crate::rational::{approximate_ratio, ContinuedFraction};
+pub use crate::raw::RawDividerU64;
+pub use crate::recipe::{Recipe, RecipeOp};
+pub use crate::rolling::RollingHash;
+pub use crate::shard::{jump_consistent_hash, JumpHash, Sharder};
+pub use crate::slice::OutOfRange;
+#[cfg(feature = "proptest")]
+pub use crate::strategy::{any_divider, divider_for_range};
+#[cfg(feature = "rand")]
+pub use crate::uniform::UniformU64Bounded;
+pub use crate::universal::{UniversalHasher, UniversalHasherState, MERSENNE_61};
+pub use crate::x4::DividerU64x4;
+
+use crate::mulhi::{libdivide_mullhi_u64, mullhi_u64_by_u32};
+
+/// Precomputed divider for `u64` numerators.
+///
+/// `DividerU64` is meant to be stored in large arrays: it is guaranteed to take 16 bytes,
+/// and `Option<DividerU64>` is guaranteed to take 16 bytes too, thanks to the niche
+/// in its discriminant.
+///
+/// `divide_by` picks the algorithm, and new variants may be added as cheaper
+/// algorithms are found for some divisors: the enum is not exhaustive.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
 pub enum DividerU64 {
-    Fast { magic: u64, shift: u8 },
+    /// Dividing by 1. `divide` returns its argument untouched.
+    Identity,
+    Fast {
+        magic: u64,
+        shift: u8,
+    },
     BitShift(u8),
-    General { magic_low: u64, shift: u8 },
-}
-
-#[inline(always)]
-fn libdivide_mullhi_u64(x: u64, y: u64) -> u64 {
-    let xl = x as u128;
-    let yl = y as u128;
-    ((xl * yl) >> 64) as u64
+    General {
+        magic_low: u64,
+        shift: u8,
+    },
+    /// Dividing by an even divisor `c * 2^pre_shift` for which the fast path does not apply.
+    /// The numerator is shifted first, and the division by `c` of the resulting
+    /// smaller numerator works with a 64-bits magic number.
+    PreShift {
+        magic: u64,
+        pre_shift: u8,
+        shift: u8,
+    },
+    /// Dividing by a divisor greater than 2^63, that is not a power of 2.
+    /// The quotient is then either 0 or 1, and a comparison is all we need.
+    Compare(u64),
 }
 
+const _: () = assert!(core::mem::size_of::<DividerU64>() == 16);
+const _: () = assert!(core::mem::size_of::<Option<DividerU64>>() == 16);
 
 #[inline(always)]
 fn floor_log2(n: u64) -> u8 {
@@ -96,11 +270,117 @@ fn floor_log2(n: u64) -> u8 {
     63u8 - (n.leading_zeros() as u8)
 }
 
+/// Returns the quotient and the remainder of `(numhi * 2^64 + numlo) / den`.
+///
+/// `numhi` must be strictly lower than `den`, so that the quotient fits in 64 bits.
+///
+/// This only relies on 64-bit operations: u128 divisions lower to a call to
+/// `__udivti3`, which is slow and which embedded users prefer to avoid.
+/// This is the "divlu" algorithm from Hacker's Delight, working in base 2^32.
+fn libdivide_128_div_64_to_64(numhi: u64, numlo: u64, den: u64) -> (u64, u64) {
+    assert!(numhi < den);
+    const B: u64 = 1u64 << 32;
+    // Normalize the divisor, so that its most significant bit is set.
+    // This cannot overflow `numhi`, as `numhi < den`.
+    let shift = den.leading_zeros();
+    let den = den << shift;
+    let numhi = if shift == 0 {
+        numhi
+    } else {
+        (numhi << shift) | (numlo >> (64 - shift))
+    };
+    let numlo = numlo << shift;
+    let den1 = den >> 32;
+    let den0 = den & (B - 1);
+    let num1 = numlo >> 32;
+    let num0 = numlo & (B - 1);
+
+    // Estimates a digit of the quotient as `[num_hi] / [den1]` and corrects it.
+    // The estimate is off by at most 2.
+    let quotient_digit = |num_hi: u64, num_lo: u64| -> u64 {
+        let mut qhat = num_hi / den1;
+        let mut rhat = num_hi - qhat * den1;
+        while qhat >= B || qhat * den0 > (rhat << 32) + num_lo {
+            qhat -= 1;
+            rhat += den1;
+            if rhat >= B {
+                break;
+            }
+        }
+        qhat
+    };
+
+    let q1 = quotient_digit(numhi, num1);
+    let rem = (numhi << 32)
+        .wrapping_add(num1)
+        .wrapping_sub(q1.wrapping_mul(den));
+    let q0 = quotient_digit(rem, num0);
+    let rem = (rem << 32)
+        .wrapping_add(num0)
+        .wrapping_sub(q0.wrapping_mul(den));
+    ((q1 << 32) | q0, rem >> shift)
+}
+
+/// `2^{64 + floor_log_2_d} / divisor`, from which the magic numbers are derived,
+/// for a divisor that is not a power of 2.
+struct Reciprocal {
+    divisor: u64,
+    floor_log_2_d: u8,
+    quotient: u64,
+    reminder: u64,
+}
+
+impl Reciprocal {
+    fn of(divisor: u64) -> Reciprocal {
+        let floor_log_2_d: u8 = floor_log2(divisor);
+        let (quotient, reminder) = libdivide_128_div_64_to_64(1u64 << floor_log_2_d, 0, divisor);
+        assert!(reminder > 0 && reminder < divisor);
+        Reciprocal {
+            divisor,
+            floor_log_2_d,
+            quotient,
+            reminder,
+        }
+    }
+
+    fn fast_path(&self) -> Option<DividerU64> {
+        let e: u64 = self.divisor - self.reminder;
+        // This is a sufficient condition for our 64-bits magic number
+        // condition to work as described in
+        // See https://ridiculousfish.com/blog/posts/labor-of-division-episode-i.html
+        if e >= (1u64 << self.floor_log_2_d) {
+            return None;
+        }
+        Some(DividerU64::Fast {
+            magic: self.quotient + 1u64,
+            shift: self.floor_log_2_d,
+        })
+    }
+
+    /// Returns the low 64 bits of the 65 bits magic number, and the associated shift.
+    fn general_magic(&self) -> (u64, u8) {
+        // m=⌊2^{65+floor_log_2_d} / d⌋ + 1. This is a 65 bit number, so we keep only the low 64 bits.
+        // We double 2^{64+floor_log_2_d} / d with the help of its remainder
+        // to avoid overflowing.
+        let twice_reminder = self.reminder.wrapping_add(self.reminder);
+        let carry = (twice_reminder >= self.divisor || twice_reminder < self.reminder) as u64;
+        let m = self
+            .quotient
+            .wrapping_add(self.quotient)
+            .wrapping_add(carry)
+            .wrapping_add(1);
+        (m, self.floor_log_2_d)
+    }
+}
+
 impl DividerU64 {
     fn power_of_2_division(divisor: u64) -> Option<DividerU64> {
         if divisor == 0 {
             return None;
         }
+        if divisor == 1 {
+            return Some(DividerU64::Identity);
+        }
         if !divisor.is_power_of_two() {
             return None;
         }
@@ -112,61 +392,191 @@ impl DividerU64 {
         if divisor.is_power_of_two() {
             return None;
         }
-        let floor_log_2_d: u8 = floor_log2(divisor);
-        let u = 1u128 << (floor_log_2_d + 64);
-        let proposed_magic_number: u128 = u / divisor as u128;
-        let reminder: u64 = (u - proposed_magic_number * (divisor as u128)) as u64;
-        assert!(reminder > 0 && reminder < divisor);
-        let e: u64 = divisor - reminder;
-        // This is a sufficient condition for our 64-bits magic number
-        // condition to work as described in
-        // See https://ridiculousfish.com/blog/posts/labor-of-division-episode-i.html
-        if e >= (1u64 << floor_log_2_d) {
+        Reciprocal::of(divisor).fast_path()
+    }
+
+    fn pre_shift_path(divisor: u64) -> Option<DividerU64> {
+        if divisor & 1 == 1 || divisor.is_power_of_two() {
             return None;
         }
-        Some(DividerU64::Fast {
-            magic: (proposed_magic_number as u64) + 1u64,
-            shift: floor_log_2_d,
+        // divisor = c * 2^pre_shift, with c odd.
+        let pre_shift = divisor.trailing_zeros() as u8;
+        let c = divisor >> pre_shift;
+        // After the pre-shift, our numerator has at most 64 - pre_shift bits.
+        // A magic number m=⌈2^{64 - pre_shift + l} / c⌉ with l ≥ ⌈log2(c)⌉ is then exact,
+        // and it fits in 64 bits.
+        // We pick l ≥ pre_shift, so that the final shift is not negative.
+        let l = (floor_log2(c) + 1).max(pre_shift);
+        let (quotient, reminder) = libdivide_128_div_64_to_64(1u64 << (l - pre_shift), 0, c);
+        assert!(reminder > 0);
+        Some(DividerU64::PreShift {
+            magic: quotient + 1,
+            pre_shift,
+            shift: l - pre_shift,
         })
     }
 
-    fn general_path(divisor: u64) -> DividerU64 {
-        // p=⌈log2d⌉
-        let p: u8 = 64u8 - (divisor.leading_zeros() as u8);
-        // m=⌈2^{64+p} / d⌉. This is a 33 bit number, so keep only the low 32 bits.
-        // we do a little dance to avoid the overflow if p = 64.
-        let e = 1u128 << (63 + p);
-        let m = 2 + (e + (e - divisor as u128)) / divisor as u128;
-        DividerU64::General {
-            magic_low: m as u64,
-            shift: p - 1,
-        }
+    /// Computes the low 64 bits of the 65 bits magic number for `divisor`,
+    /// and the associated shift.
+    ///
+    /// `divisor` must not be a power of 2.
+    fn general_magic(divisor: u64) -> (u64, u8) {
+        Reciprocal::of(divisor).general_magic()
     }
 
+    /// Precomputes a divider for `divisor`.
+    ///
+    /// A divisor of 1 is guaranteed to yield `DividerU64::Identity`,
+    /// for which `divide` does not perform any arithmetic.
+    ///
+    /// Panics if `divisor` is 0.
     pub fn divide_by(divisor: u64) -> DividerU64 {
         assert!(divisor > 0u64);
-        Self::power_of_2_division(divisor)
-            .or_else(|| DividerU64::fast_path(divisor))
-            .unwrap_or_else(|| DividerU64::general_path(divisor))
+        Self::power_of_2_division(divisor).unwrap_or_else(|| {
+            // Both the fast path and the general path are derived from the
+            // same reciprocal, so we compute it only once.
+            if divisor > (1u64 << 63) {
+                return DividerU64::Compare(divisor);
+            }
+            let reciprocal = Reciprocal::of(divisor);
+            reciprocal
+                .fast_path()
+                .or_else(|| DividerU64::pre_shift_path(divisor))
+                .unwrap_or_else(|| {
+                    let (magic_low, shift) = reciprocal.general_magic();
+                    DividerU64::General { magic_low, shift }
+                })
+        })
+    }
+
+    /// Returns `divisor - 1` if the divisor is a power of two, and `None` otherwise.
+    ///
+    /// For a power of two divisor, `n & mask` is equal to `n % divisor`.
+    #[inline(always)]
+    pub fn remainder_mask(&self) -> Option<u64> {
+        match *self {
+            DividerU64::Identity => Some(0),
+            DividerU64::BitShift(d) => Some((1u64 << d) - 1),
+            _ => None,
+        }
+    }
+
+    /// Returns the divisor the divider was built for.
+    ///
+    /// The divisor is not stored: it is recovered from the magic number,
+    /// which costs a 128 bits division. The result is meaningless for a divider
+    /// that was not returned by `divide_by`.
+    pub fn divisor(&self) -> u64 {
+        let (magic, k, pre_shift) = match *self {
+            DividerU64::Identity => return 1,
+            DividerU64::BitShift(shift) => return 1u64.wrapping_shl(shift as u32),
+            DividerU64::Compare(divisor) => return divisor,
+            DividerU64::Fast { magic, shift } => (magic as u128, 64 + shift as u32, 0),
+            DividerU64::PreShift {
+                magic,
+                pre_shift,
+                shift,
+            } => (magic as u128, 64 + shift as u32, pre_shift as u32),
+            // The magic number has 65 bits, and an extra shift.
+            DividerU64::General { magic_low, shift } => {
+                ((1u128 << 64) | magic_low as u128, 65 + shift as u32, 0)
+            }
+        };
+        // The magic number is ⌊2^k / d⌋ + 1, and d (d - 1) <= 2^k, so that
+        // d is the only integer within ]2^k / magic, 2^k / (magic - 1)].
+        let quotient = 1u128
+            .checked_shl(k)
+            .and_then(|power| power.checked_div(magic))
+            .unwrap_or(0);
+        ((quotient as u64).wrapping_add(1)).wrapping_shl(pre_shift)
+    }
+
+    /// Returns true if the divider is the one `divide_by` returns for its divisor.
+    ///
+    /// A divider read from an untrusted source should be checked before use,
+    /// as inconsistent magic numbers silently return wrong quotients.
+    pub fn is_valid(&self) -> bool {
+        let shifts_in_range = match *self {
+            DividerU64::Identity | DividerU64::Compare(_) => true,
+            DividerU64::BitShift(shift)
+            | DividerU64::Fast { shift, .. }
+            | DividerU64::General { shift, .. } => shift < 64,
+            DividerU64::PreShift {
+                pre_shift, shift, ..
+            } => pre_shift < 64 && shift < 64,
+        };
+        shifts_in_range && DividerU64::divide_by(self.divisor()) == *self
     }
 
     #[inline(always)]
     pub fn divide(&self, n: u64) -> u64 {
+        // The fast path is the most common case, so we keep it as the
+        // fall-through branch.
         match *self {
-            DividerU64::Fast { magic, shift } => {
-                // The divisor has a magic number that is lower than 32 bits.
-                // We get away with a multiplication and a bit-shift.
-                libdivide_mullhi_u64(magic, n) >> shift
+            DividerU64::Fast { magic, shift } => FastDivider { magic, shift }.divide(n),
+            DividerU64::BitShift(d) => BitShiftDivider { shift: d }.divide(n),
+            DividerU64::Identity => n,
+            DividerU64::Compare(divisor) => (n >= divisor) as u64,
+            DividerU64::PreShift {
+                magic,
+                pre_shift,
+                shift,
+            } => libdivide_mullhi_u64(magic, n >> pre_shift) >> shift,
+            DividerU64::General { magic_low, shift } => {
+                GeneralDivider { magic_low, shift }.divide(n)
             }
+        }
+    }
+
+    /// Returns `n / divisor` for a signed numerator, rounded towards zero
+    /// like the `/` operator of `i64`.
+    ///
+    /// Dividing the bits of a fixed-point number, such as `FixedI64::to_bits()`,
+    /// yields the bits of its quotient, with the same fractional scaling.
+    #[inline(always)]
+    pub fn divide_i64(&self, n: i64) -> i64 {
+        let quotient = self.divide(n.unsigned_abs()) as i64;
+        // `quotient` is `i64::MIN` only for `n = i64::MIN` divided by 1.
+        if n < 0 {
+            quotient.wrapping_neg()
+        } else {
+            quotient
+        }
+    }
+
+    /// Returns `n / divisor`, for a numerator that fits in 32 bits.
+    ///
+    /// This is cheaper than `divide` on targets without a native
+    /// 64 bits x 64 bits -> 128 bits multiplication, since only
+    /// two 32 bits x 32 bits partial products are required.
+    #[inline(always)]
+    pub fn divide_u32_numerator(&self, n: u32) -> u32 {
+        let n = n as u64;
+        let quotient = match *self {
+            DividerU64::Fast { magic, shift } => mullhi_u64_by_u32(magic, n as u32) >> shift,
             DividerU64::BitShift(d) => n >> d,
+            DividerU64::Identity => n,
+            // `n < 2^32 < divisor`
+            DividerU64::Compare(_) => 0,
+            DividerU64::PreShift {
+                magic,
+                pre_shift,
+                shift,
+            } => mullhi_u64_by_u32(magic, (n >> pre_shift) as u32) >> shift,
             DividerU64::General { magic_low, shift } => {
-                // magic only contains the low 64 bits of our actual magic number which actually has a 65 bits.
-                // The following dance computes n * (magic + 2^64) >> shift
-                let q = libdivide_mullhi_u64(magic_low, n);
-                let t = ((n - q) >> 1).wrapping_add(q);
-                t >> shift
+                // `n + q` cannot overflow, so we do not need the halving dance of `divide`.
+                let q = mullhi_u64_by_u32(magic_low, n as u32);
+                ((n + q) >> 1) >> shift
             }
-        }
+        };
+        quotient as u32
+    }
+}
+
+impl DivideU64 for DividerU64 {
+    #[inline(always)]
+    fn divide(&self, n: u64) -> u64 {
+        DividerU64::divide(self, n)
     }
 }
 
@@ -179,6 +589,63 @@ impl core::ops::Div<DividerU64> for u64 {
     }
 }
 
+impl core::ops::Div<&DividerU64> for u64 {
+    type Output = u64;
+
+    #[inline(always)]
+    fn div(self, denom: &DividerU64) -> Self::Output {
+        denom.divide(self)
+    }
+}
+
+impl core::ops::DivAssign<DividerU64> for u64 {
+    #[inline(always)]
+    fn div_assign(&mut self, denom: DividerU64) {
+        *self = denom.divide(*self);
+    }
+}
+
+impl core::ops::DivAssign<&DividerU64> for u64 {
+    #[inline(always)]
+    fn div_assign(&mut self, denom: &DividerU64) {
+        *self = denom.divide(*self);
+    }
+}
+
+// `DividerU64` does not store its divisor: each `%` recovers it with
+// `divisor()`. `DivRemU64` stores it, and is the one to use in hot loops.
+impl core::ops::Rem<DividerU64> for u64 {
+    type Output = u64;
+
+    #[inline]
+    fn rem(self, denom: DividerU64) -> Self::Output {
+        self - denom.divide(self) * denom.divisor()
+    }
+}
+
+impl core::ops::Rem<&DividerU64> for u64 {
+    type Output = u64;
+
+    #[inline]
+    fn rem(self, denom: &DividerU64) -> Self::Output {
+        self % *denom
+    }
+}
+
+impl core::ops::RemAssign<DividerU64> for u64 {
+    #[inline]
+    fn rem_assign(&mut self, denom: DividerU64) {
+        *self = *self % denom;
+    }
+}
+
+impl core::ops::RemAssign<&DividerU64> for u64 {
+    #[inline]
+    fn rem_assign(&mut self, denom: &DividerU64) {
+        *self = *self % denom;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::DividerU64;
@@ -191,6 +658,49 @@ mod tests {
         assert_eq!(res, 2);
         let res = 8u64 / divider;
         assert_eq!(res, 4);
+        assert_eq!(9u64 / &divider, 4);
+        let mut n = 17u64;
+        n /= divider;
+        assert_eq!(n, 8);
+        n /= &divider;
+        assert_eq!(n, 4);
+    }
+
+    #[test]
+    fn test_rem_op() {
+        let divider = DividerU64::divide_by(7);
+        assert_eq!(23u64 % divider, 2);
+        assert_eq!(23u64 % &divider, 2);
+        let mut n = 100u64;
+        n %= divider;
+        assert_eq!(n, 2);
+        let mut n = 13u64;
+        n %= &divider;
+        assert_eq!(n, 6);
+    }
+
+    #[test]
+    fn test_divisor() {
+        let divisors = (1u64..100_000)
+            .chain((0..64).map(|i| 1 << i))
+            .chain((1..64).map(|i| (1 << i) - 1))
+            .chain((1..64).map(|i| (1 << i) + 1))
+            .chain((1..40).map(|i| 3 << i))
+            .chain([234234131223u64, (1 << 63) + 1, u64::MAX - 1, u64::MAX]);
+        for d in divisors {
+            let divider = DividerU64::divide_by(d);
+            assert_eq!(divider.divisor(), d);
+            assert!(divider.is_valid());
+        }
+    }
+
+    #[test]
+    fn test_divide_by_1() {
+        let divider = DividerU64::divide_by(1);
+        assert_eq!(divider, DividerU64::Identity);
+        for n in [0u64, 1, 2, 1 << 32, u64::MAX] {
+            assert_eq!(divider.divide(n), n);
+        }
     }
 
     #[test]
@@ -205,6 +715,32 @@ mod tests {
         assert!(matches!(divider, DividerU64::General { .. }));
     }
 
+    #[test]
+    fn test_divide_by_large_divisor() {
+        assert_eq!(DividerU64::divide_by(1 << 63), DividerU64::BitShift(63));
+        assert!(!matches!(
+            DividerU64::divide_by((1 << 63) - 1),
+            DividerU64::Compare(_)
+        ));
+        for d in [(1 << 63) + 1, u64::MAX - 1, u64::MAX] {
+            let divider = DividerU64::divide_by(d);
+            assert_eq!(divider, DividerU64::Compare(d));
+            for n in [0u64, 1, 1 << 63, d - 1, d, u64::MAX] {
+                assert_eq!(divider.divide(n), n / d);
+            }
+        }
+    }
+
+    #[test]
+    fn test_divide_by_14() {
+        // 7 goes through the general path, but 14 = 7 * 2 does not need to.
+        let divider = DividerU64::divide_by(14);
+        assert!(matches!(divider, DividerU64::PreShift { pre_shift: 1, .. }));
+        for n in (0u64..10_000).chain([u64::MAX - 1, u64::MAX]) {
+            assert_eq!(divider.divide(n), n / 14);
+        }
+    }
+
     #[test]
     fn test_divide_by_11() {
         let divider = DividerU64::divide_by(11);
@@ -217,6 +753,63 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_divide_u32_numerator() {
+        for d in (1u64..300).chain([1 << 32, 1 << 40, u32::MAX as u64, u64::MAX]) {
+            let divider = DividerU64::divide_by(d);
+            for n in (0u32..1_000).chain([u32::MAX - 1, u32::MAX, 1 << 31]) {
+                assert_eq!(divider.divide_u32_numerator(n) as u64, n as u64 / d);
+            }
+        }
+    }
+
+    #[test]
+    fn test_remainder_mask() {
+        assert_eq!(DividerU64::divide_by(1).remainder_mask(), Some(0));
+        assert_eq!(DividerU64::divide_by(8).remainder_mask(), Some(7));
+        assert_eq!(
+            DividerU64::divide_by(1 << 63).remainder_mask(),
+            Some((1 << 63) - 1)
+        );
+        assert_eq!(DividerU64::divide_by(7).remainder_mask(), None);
+        assert_eq!(DividerU64::divide_by(11).remainder_mask(), None);
+        for d in [2u64, 4, 64, 1 << 40] {
+            let mask = DividerU64::divide_by(d).remainder_mask().unwrap();
+            for n in [0u64, 1, 3, 1000, u64::MAX] {
+                assert_eq!(n & mask, n % d);
+            }
+        }
+    }
+
+    fn check_128_div_64_to_64(numhi: u64, numlo: u64, den: u64) {
+        let num = ((numhi as u128) << 64) | numlo as u128;
+        let (quotient, remainder) = super::libdivide_128_div_64_to_64(numhi, numlo, den);
+        assert_eq!(quotient as u128, num / den as u128);
+        assert_eq!(remainder as u128, num % den as u128);
+    }
+
+    #[test]
+    fn test_128_div_64_to_64() {
+        let vals = [
+            0u64,
+            1,
+            2,
+            3,
+            7,
+            u32::MAX as u64,
+            1 << 32,
+            1 << 63,
+            u64::MAX - 1,
+            u64::MAX,
+        ];
+        for &den in vals.iter().filter(|&&den| den > 0) {
+            for &numhi in vals.iter().filter(|&&numhi| numhi < den) {
+                for &numlo in &vals {
+                    check_128_div_64_to_64(numhi, numlo, den);
+                }
+            }
+        }
+    }
 
     #[test]
     fn test_floor_log2() {
@@ -229,6 +822,38 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_divide_i64() {
+        for d in [1u64, 2, 3, 7, 10, 1 << 40, (1 << 63) - 1, 1 << 63, u64::MAX] {
+            let divider = DividerU64::divide_by(d);
+            for n in (-300i64..300).chain([i64::MIN, i64::MIN + 1, i64::MAX]) {
+                let expected = (n as i128 / d as i128) as i64;
+                assert_eq!(divider.divide_i64(n), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_is_valid() {
+        assert!(!DividerU64::BitShift(64).is_valid());
+        assert!(!DividerU64::Compare(7).is_valid());
+        assert!(!DividerU64::Fast { magic: 1, shift: 0 }.is_valid());
+        assert!(!DividerU64::Fast {
+            magic: 0,
+            shift: 70
+        }
+        .is_valid());
+        match DividerU64::divide_by(7) {
+            DividerU64::General { magic_low, shift } => {
+                let tampered = DividerU64::General {
+                    magic_low: magic_low + 1,
+                    shift,
+                };
+                assert!(!tampered.is_valid());
+            }
+            divider => panic!("{:?}", divider),
+        }
+    }
 
     proptest! {
         #![proptest_config(ProptestConfig::with_cases(100000))]
@@ -240,6 +865,34 @@ mod tests {
         }
     }
 
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(100000))]
+        #[test]
+        fn test_proptest_128_div_64_to_64(numhi in any::<u64>(), numlo in any::<u64>(), den in 1..u64::MAX) {
+            check_128_div_64_to_64(numhi % den, numlo, den);
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(100000))]
+        #[test]
+        fn test_proptest_divide_u32_numerator(n in any::<u32>(), d in 1..u64::MAX) {
+            let divider = DividerU64::divide_by(d);
+            assert_eq!(divider.divide_u32_numerator(n) as u64, n as u64 / d);
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(100000))]
+        #[test]
+        fn test_proptest_even_divisor(n in any::<u64>(), c in 1..u64::MAX, pre_shift in 1..63u32) {
+            let d = (c << pre_shift).max(2);
+            let divider = DividerU64::divide_by(d);
+            assert_eq!(divider.divide(n), n / d);
+            assert_eq!(divider.divide_u32_numerator(n as u32) as u64, (n as u32) as u64 / d);
+        }
+    }
+
     proptest! {
         #![proptest_config(ProptestConfig::with_cases(100000))]
         #[test]
@@ -262,6 +915,13 @@ mod tests {
 
     proptest! {
         #![proptest_config(ProptestConfig::with_cases(10000))]
+        #[test]
+        fn test_proptest_divisor(d in 1..u64::MAX, n in any::<u64>()) {
+            let divider = DividerU64::divide_by(d);
+            assert_eq!(divider.divisor(), d);
+            assert_eq!(n % divider, n % d);
+        }
+
         #[test]
         fn test_proptest_divide_by_any(d in 1..u64::MAX) {
             DividerU64::divide_by(d);
@@ -271,11 +931,11 @@ mod tests {
     #[test]
     fn test_libdivide() {
         for d in (1u64..100u64)
-            .chain(vec![2048, 234234131223u64].into_iter())
+            .chain(vec![2048, 234234131223u64])
             .chain((5..63).map(|i| 1 << i))
         {
             let divider = DividerU64::divide_by(d);
-            for i in (0u64..10_000).chain(vec![2048, 234234131223u64, 1 << 43, 1 << 43 + 1]) {
+            for i in (0u64..10_000).chain(vec![2048, 234234131223u64, 1 << 43, 1 << (43 + 1)]) {
                 assert_eq!(divider.divide(i), i / d);
             }
         }
@@ -0,0 +1,176 @@
+//! Conversions from and to the dividers of libdivide.
+//!
+//! `DividerU64` and `BranchFreeDividerU64` use the same magic numbers
+//! as libdivide, so that their precomputed state can be exchanged with
+//! C code without being recomputed.
+
+use crate::{BranchFreeDividerU64, DividerU64};
+
+/// The shift, in the low bits of `more`.
+const SHIFT_MASK: u8 = 0x3F;
+/// Set in `more` when the magic number is the low 64 bits of a 65-bit one.
+const ADD_MARKER: u8 = 0x40;
+
+/// The layout of libdivide's `struct libdivide_u64_t`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct LibdivideU64 {
+    pub magic: u64,
+    pub more: u8,
+}
+
+/// The layout of libdivide's `struct libdivide_u64_branchfree_t`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct LibdivideU64BranchFree {
+    pub magic: u64,
+    pub more: u8,
+}
+
+impl DividerU64 {
+    /// Returns the libdivide divider for the same divisor.
+    ///
+    /// libdivide has no equivalent for the `PreShift` and `Compare` dividers:
+    /// the libdivide magic number is computed for them.
+    pub fn to_libdivide(&self) -> LibdivideU64 {
+        let (magic, more) = match *self {
+            DividerU64::Identity => (0, 0),
+            DividerU64::BitShift(shift) => (0, shift),
+            DividerU64::Fast { magic, shift } => (magic, shift),
+            DividerU64::General { magic_low, shift } => (magic_low, shift | ADD_MARKER),
+            DividerU64::PreShift { .. } | DividerU64::Compare(_) => {
+                let divisor = self.divisor();
+                return DividerU64::fast_path(divisor)
+                    .unwrap_or_else(|| {
+                        let (magic_low, shift) = DividerU64::general_magic(divisor);
+                        DividerU64::General { magic_low, shift }
+                    })
+                    .to_libdivide();
+            }
+        };
+        LibdivideU64 { magic, more }
+    }
+
+    /// Returns the divider equivalent to a libdivide divider,
+    /// or `None` if it is not a valid unsigned libdivide divider.
+    ///
+    /// Only the flags of `more` are checked, the magic number is trusted.
+    /// The result may differ from what `divide_by` returns for the
+    /// same divisor, as libdivide does not use the `PreShift` and `Compare` dividers.
+    pub fn from_libdivide(divider: LibdivideU64) -> Option<DividerU64> {
+        let LibdivideU64 { magic, more } = divider;
+        if more & !(SHIFT_MASK | ADD_MARKER) != 0 {
+            return None;
+        }
+        let shift = more & SHIFT_MASK;
+        let divider = match (magic, more & ADD_MARKER != 0) {
+            (0, true) => return None,
+            (0, false) if shift == 0 => DividerU64::Identity,
+            (0, false) => DividerU64::BitShift(shift),
+            (magic, false) => DividerU64::Fast { magic, shift },
+            (magic_low, true) => DividerU64::General { magic_low, shift },
+        };
+        Some(divider)
+    }
+}
+
+impl BranchFreeDividerU64 {
+    /// Returns the libdivide branchfree divider for the same divisor,
+    /// or `None` for a divisor of 1, which libdivide does not support.
+    pub fn to_libdivide(&self) -> Option<LibdivideU64BranchFree> {
+        if self.half_shift == 0 {
+            return None;
+        }
+        Some(LibdivideU64BranchFree {
+            magic: self.magic,
+            more: self.shift,
+        })
+    }
+
+    /// Returns the divider equivalent to a libdivide branchfree divider,
+    /// or `None` if its shift is out of range.
+    ///
+    /// The magic number is trusted.
+    pub fn from_libdivide(divider: LibdivideU64BranchFree) -> Option<BranchFreeDividerU64> {
+        if divider.more & !SHIFT_MASK != 0 {
+            return None;
+        }
+        Some(BranchFreeDividerU64 {
+            magic: divider.magic,
+            half_shift: 1,
+            shift: divider.more,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LibdivideU64, LibdivideU64BranchFree, ADD_MARKER, SHIFT_MASK};
+    use crate::{libdivide_mullhi_u64, BranchFreeDividerU64, DividerU64};
+
+    // `libdivide_u64_do`
+    fn libdivide_u64_do(numer: u64, denom: &LibdivideU64) -> u64 {
+        if denom.magic == 0 {
+            return numer >> denom.more;
+        }
+        let q = libdivide_mullhi_u64(denom.magic, numer);
+        if denom.more & ADD_MARKER != 0 {
+            let t = ((numer - q) >> 1) + q;
+            t >> (denom.more & SHIFT_MASK)
+        } else {
+            q >> denom.more
+        }
+    }
+
+    // `libdivide_u64_branchfree_do`
+    fn libdivide_u64_branchfree_do(numer: u64, denom: &LibdivideU64BranchFree) -> u64 {
+        let q = libdivide_mullhi_u64(denom.magic, numer);
+        let t = ((numer - q) >> 1) + q;
+        t >> denom.more
+    }
+
+    #[test]
+    fn test_libdivide_round_trip() {
+        let divisors = (1u64..1_000).chain((0..64).map(|i| 1 << i)).chain([
+            234234131223u64,
+            (1 << 63) + 1,
+            u64::MAX - 1,
+            u64::MAX,
+        ]);
+        let numerators = [0u64, 1, 7, 999, 1 << 32, 1 << 63, u64::MAX - 1, u64::MAX];
+        for d in divisors {
+            let divider = DividerU64::divide_by(d);
+            let libdivide = divider.to_libdivide();
+            let back = DividerU64::from_libdivide(libdivide).unwrap();
+            assert_eq!(back.to_libdivide(), libdivide);
+            let branchfree = BranchFreeDividerU64::divide_by(d);
+            let libdivide_branchfree = branchfree.to_libdivide();
+            assert_eq!(libdivide_branchfree.is_none(), d == 1);
+            for &n in &numerators {
+                assert_eq!(libdivide_u64_do(n, &libdivide), n / d);
+                assert_eq!(back.divide(n), n / d);
+                if let Some(libdivide_branchfree) = libdivide_branchfree {
+                    assert_eq!(libdivide_u64_branchfree_do(n, &libdivide_branchfree), n / d);
+                    let back = BranchFreeDividerU64::from_libdivide(libdivide_branchfree).unwrap();
+                    assert_eq!(back, branchfree);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_libdivide_invalid() {
+        let invalid = LibdivideU64 {
+            magic: 0,
+            more: ADD_MARKER,
+        };
+        assert_eq!(DividerU64::from_libdivide(invalid), None);
+        let negative = LibdivideU64 {
+            magic: 1,
+            more: 0x80,
+        };
+        assert_eq!(DividerU64::from_libdivide(negative), None);
+        let invalid = LibdivideU64BranchFree { magic: 1, more: 64 };
+        assert_eq!(BranchFreeDividerU64::from_libdivide(invalid), None);
+    }
+}
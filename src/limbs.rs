@@ -0,0 +1,141 @@
+//! Division of multi-limb integers by a `u64`.
+
+use crate::{libdivide_128_div_64_to_64, libdivide_mullhi_u64, DivRemU64};
+
+/// Returns the quotient and the remainder of `(u1 * 2^64 + u0) / d`, for a
+/// normalized `d` (its most significant bit is set) and `u1 < d`.
+///
+/// `v` is the reciprocal `(2^128 - 1) / d - 2^64`. This is algorithm 4 of
+/// Möller and Granlund, "Improved division by invariant integers".
+#[inline(always)]
+fn div_2by1(u1: u64, u0: u64, d: u64, v: u64) -> (u64, u64) {
+    let q0 = v.wrapping_mul(u1);
+    let q1 = libdivide_mullhi_u64(v, u1);
+    let (q0, carry) = q0.overflowing_add(u0);
+    let q1 = q1
+        .wrapping_add(u1)
+        .wrapping_add(carry as u64)
+        .wrapping_add(1);
+    let mut q = q1;
+    let mut r = u0.wrapping_sub(q1.wrapping_mul(d));
+    if r > q0 {
+        q = q.wrapping_sub(1);
+        r = r.wrapping_add(d);
+    }
+    if r >= d {
+        q += 1;
+        r -= d;
+    }
+    (q, r)
+}
+
+/// The normalized divisor and its reciprocal, for `div_2by1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Reciprocal {
+    d: u64,
+    v: u64,
+    shift: u32,
+}
+
+impl Reciprocal {
+    pub(crate) fn new(divisor: u64) -> Reciprocal {
+        assert!(divisor > 0);
+        let shift = divisor.leading_zeros();
+        let d = divisor << shift;
+        let (v, _) = libdivide_128_div_64_to_64(!d, u64::MAX, d);
+        Reciprocal { d, v, shift }
+    }
+
+    /// Returns `(hi * 2^64 + lo) % divisor`, for `hi < divisor`.
+    #[inline(always)]
+    pub(crate) fn rem_2by1(&self, hi: u64, lo: u64) -> u64 {
+        let (u1, u0) = if self.shift == 0 {
+            (hi, lo)
+        } else {
+            (
+                (hi << self.shift) | (lo >> (64 - self.shift)),
+                lo << self.shift,
+            )
+        };
+        div_2by1(u1, u0, self.d, self.v).1 >> self.shift
+    }
+}
+
+impl DivRemU64 {
+    /// Divides in place the integer whose 64-bit limbs are `limbs`,
+    /// least significant first, and returns the remainder.
+    ///
+    /// This is the layout of `BigUint::to_u64_digits`. Converting a big
+    /// integer to decimal repeatedly divides it by `10^19` this way.
+    /// The reciprocal of the divisor is computed once per call, and each limb
+    /// then costs two multiplications.
+    pub fn divide_limbs(&self, limbs: &mut [u64]) -> u64 {
+        let Reciprocal { d, v, shift } = Reciprocal::new(self.divisor());
+        // Divides `limbs << shift` by `d`, which yields the same quotient.
+        let mut r = match limbs.last() {
+            Some(&top) if shift != 0 => top >> (64 - shift),
+            _ => 0,
+        };
+        for i in (0..limbs.len()).rev() {
+            let mut u0 = limbs[i] << shift;
+            if shift != 0 && i > 0 {
+                u0 |= limbs[i - 1] >> (64 - shift);
+            }
+            let (q, rem) = div_2by1(r, u0, d, v);
+            limbs[i] = q;
+            r = rem;
+        }
+        r >> shift
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::DivRemU64;
+    use proptest::prelude::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_divide_limbs_u128() {
+        let divisors = (1u64..100).chain((0..64).map(|i| 1 << i)).chain([
+            10_000_000_000_000_000_000,
+            (1 << 63) + 1,
+            u64::MAX,
+        ]);
+        for d in divisors {
+            let divider = DivRemU64::divide_by(d);
+            for n in [0u128, 1, 99, u64::MAX as u128 + 3, u128::MAX, 1 << 127] {
+                let mut limbs = [n as u64, (n >> 64) as u64];
+                let r = divider.divide_limbs(&mut limbs);
+                let q = limbs[0] as u128 | (limbs[1] as u128) << 64;
+                assert_eq!((q, r as u128), (n / d as u128, n % d as u128));
+            }
+        }
+    }
+
+    #[test]
+    fn test_divide_limbs_empty() {
+        assert_eq!(DivRemU64::divide_by(7).divide_limbs(&mut []), 0);
+    }
+
+    proptest! {
+        #[test]
+        fn test_proptest_divide_limbs(limbs in proptest::collection::vec(any::<u64>(), 0..8), d in 1..u64::MAX) {
+            let mut quotient = limbs.clone();
+            let r = DivRemU64::divide_by(d).divide_limbs(&mut quotient);
+            assert!(r < d);
+            // Checks that quotient * d + r == limbs.
+            let mut carry = r as u128;
+            let product: Vec<u64> = quotient
+                .iter()
+                .map(|&q| {
+                    let t = q as u128 * d as u128 + carry;
+                    carry = t >> 64;
+                    t as u64
+                })
+                .collect();
+            assert_eq!(carry, 0);
+            assert_eq!(product, limbs);
+        }
+    }
+}
@@ -0,0 +1,304 @@
+//! Modular arithmetic by a runtime modulus.
+
+use core::convert::TryFrom;
+
+use crate::divrem::DivisibilityCheck;
+use crate::limbs::Reciprocal;
+use crate::{libdivide_mullhi_u64, DivRemU64};
+
+/// Modular arithmetic context for a fixed modulus.
+///
+/// `reduce` goes through a [`DivRemU64`], and `mul_mod` reduces the 128-bit
+/// product with the reciprocal of the modulus, with two multiplications.
+/// Neither of them performs any hardware division.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mod64 {
+    divider: DivRemU64,
+    reciprocal: Reciprocal,
+}
+
+impl Mod64 {
+    /// Precomputes the context for `modulus`.
+    ///
+    /// Panics if `modulus` is 0.
+    pub fn new(modulus: u64) -> Mod64 {
+        Mod64 {
+            divider: DivRemU64::divide_by(modulus),
+            reciprocal: Reciprocal::new(modulus),
+        }
+    }
+
+    /// Returns the modulus.
+    #[inline(always)]
+    pub fn modulus(&self) -> u64 {
+        self.divider.divisor()
+    }
+
+    /// Returns `n % modulus`.
+    #[inline(always)]
+    pub fn reduce(&self, n: u64) -> u64 {
+        self.divider.remainder(n)
+    }
+
+    /// Returns `(a + b) % modulus`, for `a` and `b` lower than the modulus.
+    #[inline(always)]
+    pub fn add_mod(&self, a: u64, b: u64) -> u64 {
+        debug_assert!(a < self.modulus() && b < self.modulus());
+        let (sum, overflow) = a.overflowing_add(b);
+        if overflow || sum >= self.modulus() {
+            sum.wrapping_sub(self.modulus())
+        } else {
+            sum
+        }
+    }
+
+    /// Returns `(a - b) % modulus`, for `a` and `b` lower than the modulus.
+    #[inline(always)]
+    pub fn sub_mod(&self, a: u64, b: u64) -> u64 {
+        debug_assert!(a < self.modulus() && b < self.modulus());
+        if a >= b {
+            a - b
+        } else {
+            a.wrapping_sub(b).wrapping_add(self.modulus())
+        }
+    }
+
+    /// Returns `(a * b) % modulus`.
+    #[inline(always)]
+    pub fn mul_mod(&self, a: u64, b: u64) -> u64 {
+        let lo = a.wrapping_mul(b);
+        let mut hi = libdivide_mullhi_u64(a, b);
+        // This only happens when neither `a` nor `b` is reduced.
+        if hi >= self.modulus() {
+            hi = self.reduce(hi);
+        }
+        self.reciprocal.rem_2by1(hi, lo)
+    }
+
+    /// Returns `base^exp % modulus`, by square-and-multiply.
+    ///
+    /// `0^0` is 1, reduced modulo the modulus.
+    pub fn mod_pow(&self, base: u64, mut exp: u64) -> u64 {
+        let mut base = self.reduce(base);
+        let mut result = self.reduce(1);
+        while exp != 0 {
+            if exp & 1 == 1 {
+                result = self.mul_mod(result, base);
+            }
+            base = self.mul_mod(base, base);
+            exp >>= 1;
+        }
+        result
+    }
+
+    /// Returns the inverse of `a` modulo the modulus, or `None` if `a` and
+    /// the modulus are not coprime.
+    ///
+    /// This is the extended Euclidean algorithm, with the Bézout coefficient
+    /// kept reduced by `mul_mod` and `sub_mod`.
+    pub fn mod_inverse(&self, a: u64) -> Option<u64> {
+        let (mut r0, mut r1) = (self.modulus(), self.reduce(a));
+        let (mut t0, mut t1) = (0, self.reduce(1));
+        while r1 != 0 {
+            let q = r0 / r1;
+            (r0, r1) = (r1, r0 - q * r1);
+            (t0, t1) = (t1, self.sub_mod(t0, self.mul_mod(q, t1)));
+        }
+        if r0 == 1 {
+            Some(t0)
+        } else {
+            None
+        }
+    }
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Solves the system `x = r1 mod m1`, `x = r2 mod m2` by the Chinese
+/// remainder theorem, with `m1` and `m2` the moduli of the contexts.
+///
+/// Returns `(x, lcm(m1, m2))`, `x` being the smallest non-negative solution,
+/// or `None` if the system has no solution, or if the least common multiple
+/// does not fit in a `u64`. The moduli do not need to be coprime, but the
+/// reductions only go through the precomputed `m2` context when they are.
+pub fn crt_combine((r1, m1): (u64, &Mod64), (r2, m2): (u64, &Mod64)) -> Option<(u64, u64)> {
+    let r1 = m1.reduce(r1);
+    let diff = m2.sub_mod(m2.reduce(r2), m2.reduce(r1));
+    let g = gcd(m1.modulus(), m2.modulus());
+    let reduced_m1 = m1.modulus() / g;
+    let lcm = u64::try_from(reduced_m1 as u128 * m2.modulus() as u128).ok()?;
+    // Solves `r1 + m1 * t = r2 mod m2`, that is
+    // `(m1 / g) * t = (r2 - r1) / g mod (m2 / g)`.
+    let t = if g == 1 {
+        m2.mul_mod(diff, m2.mod_inverse(m1.modulus())?)
+    } else {
+        if !DivisibilityCheck::new(g).is_divisible(diff) {
+            return None;
+        }
+        let modulus = Mod64::new(m2.modulus() / g);
+        modulus.mul_mod(diff / g, modulus.mod_inverse(reduced_m1)?)
+    };
+    // t < m2 / g, so this is lower than `lcm`.
+    Some((r1 + m1.modulus() * t, lcm))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{crt_combine, gcd, Mod64};
+    use proptest::prelude::*;
+
+    fn moduli() -> impl Iterator<Item = u64> {
+        (1u64..50).chain([
+            1 << 32,
+            (1 << 61) - 1,
+            998_244_353,
+            (1 << 63) + 1,
+            u64::MAX - 58,
+            u64::MAX,
+        ])
+    }
+
+    #[test]
+    fn test_mod64() {
+        for m in moduli() {
+            let modulus = Mod64::new(m);
+            assert_eq!(modulus.modulus(), m);
+            let vals = [0, 1, 2, m / 2, m.saturating_sub(2), m - 1].map(|v| v.min(m - 1));
+            for &a in &vals {
+                assert_eq!(modulus.reduce(a), a);
+                for &b in &vals {
+                    let (a128, b128, m128) = (a as u128, b as u128, m as u128);
+                    assert_eq!(modulus.add_mod(a, b) as u128, (a128 + b128) % m128);
+                    assert_eq!(modulus.sub_mod(a, b) as u128, (a128 + m128 - b128) % m128);
+                    assert_eq!(modulus.mul_mod(a, b) as u128, a128 * b128 % m128);
+                }
+            }
+            assert_eq!(modulus.reduce(u64::MAX), u64::MAX % m);
+            let max = u64::MAX as u128;
+            assert_eq!(
+                modulus.mul_mod(u64::MAX, u64::MAX) as u128,
+                max * max % m as u128
+            );
+        }
+    }
+
+    #[test]
+    fn test_mod_pow() {
+        let modulus = Mod64::new(1_000_000_007);
+        assert_eq!(modulus.mod_pow(2, 10), 1024);
+        assert_eq!(modulus.mod_pow(0, 0), 1);
+        assert_eq!(modulus.mod_pow(0, 5), 0);
+        // Fermat's little theorem.
+        for a in 1..100 {
+            assert_eq!(modulus.mod_pow(a, 1_000_000_006), 1);
+        }
+        let mersenne = Mod64::new((1 << 61) - 1);
+        assert_eq!(mersenne.mod_pow(3, (1 << 61) - 2), 1);
+        assert_eq!(mersenne.mod_pow(u64::MAX, 1), u64::MAX % ((1 << 61) - 1));
+        assert_eq!(Mod64::new(1).mod_pow(5, 0), 0);
+    }
+
+    #[test]
+    fn test_mod_inverse() {
+        let modulus = Mod64::new(10);
+        assert_eq!(modulus.mod_inverse(3), Some(7));
+        assert_eq!(modulus.mod_inverse(13), Some(7));
+        assert_eq!(modulus.mod_inverse(4), None);
+        assert_eq!(modulus.mod_inverse(0), None);
+        assert_eq!(Mod64::new(1).mod_inverse(5), Some(0));
+        for m in moduli() {
+            let modulus = Mod64::new(m);
+            for a in (0..100).chain([u64::MAX - 1, u64::MAX]) {
+                match modulus.mod_inverse(a) {
+                    Some(inverse) => {
+                        assert!(inverse < m);
+                        assert_eq!(modulus.mul_mod(a, inverse), 1 % m);
+                    }
+                    None => assert_ne!(gcd(a, m), 1),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_crt_combine() {
+        let (m3, m5, m4, m6) = (Mod64::new(3), Mod64::new(5), Mod64::new(4), Mod64::new(6));
+        assert_eq!(crt_combine((2, &m3), (3, &m5)), Some((8, 15)));
+        assert_eq!(crt_combine((3, &m4), (5, &m6)), Some((11, 12)));
+        assert_eq!(crt_combine((1, &m4), (2, &m6)), None);
+        assert_eq!(crt_combine((14, &m3), (23, &m5)), Some((8, 15)));
+        let big = Mod64::new(1 << 63);
+        assert_eq!(crt_combine((1, &big), (0, &m3)), None);
+        assert_eq!(
+            crt_combine((1, &big), (1, &Mod64::new(1 << 40))),
+            Some((1, 1 << 63))
+        );
+        for a in 1..30u64 {
+            for b in 1..30u64 {
+                let (ma, mb) = (Mod64::new(a), Mod64::new(b));
+                for (ra, rb) in [(0, 0), (1, 2), (a - 1, b - 1), (7, 11)] {
+                    let brute_force = (0..a * b).find(|x| x % a == ra % a && x % b == rb % b);
+                    let lcm = a * b / gcd(a, b);
+                    assert_eq!(
+                        crt_combine((ra, &ma), (rb, &mb)),
+                        brute_force.map(|x| (x, lcm))
+                    );
+                }
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_proptest_mul_mod(a in any::<u64>(), b in any::<u64>(), m in 1..u64::MAX) {
+            let expected = (a as u128 * b as u128 % m as u128) as u64;
+            assert_eq!(Mod64::new(m).mul_mod(a, b), expected);
+        }
+
+        #[test]
+        fn test_proptest_add_sub_mod(a in any::<u64>(), b in any::<u64>(), m in 1..u64::MAX) {
+            let (a, b) = (a % m, b % m);
+            let modulus = Mod64::new(m);
+            assert_eq!(modulus.add_mod(a, b), ((a as u128 + b as u128) % m as u128) as u64);
+            assert_eq!(modulus.sub_mod(modulus.add_mod(a, b), b), a);
+        }
+
+        #[test]
+        fn test_proptest_mod_pow(base in any::<u64>(), exp in 0u64..300, m in 1..u64::MAX) {
+            let mut expected = 1 % m as u128;
+            for _ in 0..exp {
+                expected = expected * base as u128 % m as u128;
+            }
+            assert_eq!(Mod64::new(m).mod_pow(base, exp) as u128, expected);
+        }
+
+        #[test]
+        fn test_proptest_crt_combine(x in any::<u64>(), a in 1..u64::MAX, b in 1..(1u64 << 32)) {
+            let (ma, mb) = (Mod64::new(a), Mod64::new(b));
+            // x is a solution, so there is one unless the lcm does not fit.
+            let lcm = a as u128 * b as u128 / gcd(a, b) as u128;
+            match crt_combine((x % a, &ma), (x % b, &mb)) {
+                Some((solution, combined)) => {
+                    assert_eq!(combined as u128, lcm);
+                    assert_eq!((solution % a, solution % b), (x % a, x % b));
+                    assert!(solution < combined);
+                }
+                None => assert!(lcm > u64::MAX as u128),
+            }
+        }
+
+        #[test]
+        fn test_proptest_mod_inverse(a in any::<u64>(), m in 1..u64::MAX) {
+            let modulus = Mod64::new(m);
+            match modulus.mod_inverse(a) {
+                Some(inverse) => assert_eq!(modulus.mul_mod(a, inverse), 1 % m),
+                None => assert_ne!(gcd(a, m), 1),
+            }
+        }
+    }
+}
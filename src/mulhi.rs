@@ -0,0 +1,292 @@
+//! High half of the 64 bits x 64 bits -> 128 bits multiplication,
+//! which is the core operation of all of our dividers.
+
+/// Returns the 64 high bits of `x * y`.
+#[inline(always)]
+pub(crate) fn libdivide_mullhi_u64(x: u64, y: u64) -> u64 {
+    #[cfg(feature = "soft-mul")]
+    {
+        mullhi_u64_soft(x, y)
+    }
+    #[cfg(all(
+        not(feature = "soft-mul"),
+        feature = "mulx",
+        target_arch = "x86_64",
+        target_feature = "bmi2"
+    ))]
+    {
+        mullhi_u64_mulx(x, y)
+    }
+    #[cfg(all(
+        not(feature = "soft-mul"),
+        any(target_pointer_width = "32", target_family = "wasm"),
+        not(all(feature = "mulx", target_arch = "x86_64", target_feature = "bmi2"))
+    ))]
+    {
+        mullhi_u64_32x32(x, y)
+    }
+    #[cfg(all(
+        feature = "nightly",
+        not(any(
+            feature = "soft-mul",
+            all(feature = "mulx", target_arch = "x86_64", target_feature = "bmi2"),
+            target_pointer_width = "32",
+            target_family = "wasm"
+        ))
+    ))]
+    {
+        mullhi_u64_widening(x, y)
+    }
+    #[cfg(not(any(
+        feature = "soft-mul",
+        all(feature = "mulx", target_arch = "x86_64", target_feature = "bmi2"),
+        target_pointer_width = "32",
+        target_family = "wasm",
+        feature = "nightly"
+    )))]
+    {
+        mullhi_u64_u128(x, y)
+    }
+}
+
+/// Returns the 64 high bits of `x * y`.
+#[inline(always)]
+pub(crate) fn mullhi_u64_by_u32(x: u64, y: u32) -> u64 {
+    #[cfg(all(
+        not(feature = "soft-mul"),
+        any(target_pointer_width = "32", target_family = "wasm"),
+        not(all(feature = "mulx", target_arch = "x86_64", target_feature = "bmi2"))
+    ))]
+    {
+        mullhi_u64_by_u32_32x32(x, y)
+    }
+    #[cfg(not(all(
+        not(feature = "soft-mul"),
+        any(target_pointer_width = "32", target_family = "wasm"),
+        not(all(feature = "mulx", target_arch = "x86_64", target_feature = "bmi2"))
+    )))]
+    {
+        libdivide_mullhi_u64(x, y as u64)
+    }
+}
+
+// `mulx` does not touch the flags and takes its operands in any register,
+// which gives the scheduler more freedom in unrolled loops.
+//
+// We only rely on it if `bmi2` is enabled at compile time (e.g. with
+// `-C target-cpu=native`): a runtime detection would cost more than
+// what we are trying to save.
+#[cfg(all(
+    not(feature = "soft-mul"),
+    feature = "mulx",
+    target_arch = "x86_64",
+    target_feature = "bmi2"
+))]
+#[inline(always)]
+fn mullhi_u64_mulx(x: u64, y: u64) -> u64 {
+    let mut hi = 0u64;
+    // Safety: the `bmi2` target feature is enabled.
+    unsafe {
+        core::arch::x86_64::_mulx_u64(x, y, &mut hi);
+    }
+    hi
+}
+
+// Asks the compiler for a widening multiplication directly instead of
+// going through u128 casts, which gives LLVM an easier job on targets
+// where the u128 path spills.
+#[cfg(all(
+    feature = "nightly",
+    not(any(
+        feature = "soft-mul",
+        all(feature = "mulx", target_arch = "x86_64", target_feature = "bmi2"),
+        target_pointer_width = "32",
+        target_family = "wasm"
+    ))
+))]
+#[inline(always)]
+fn mullhi_u64_widening(x: u64, y: u64) -> u64 {
+    (x.widening_mul(y) >> 64) as u64
+}
+
+#[cfg(any(
+    not(any(
+        feature = "soft-mul",
+        all(feature = "mulx", target_arch = "x86_64", target_feature = "bmi2"),
+        target_pointer_width = "32",
+        target_family = "wasm",
+        feature = "nightly"
+    )),
+    test
+))]
+#[inline(always)]
+fn mullhi_u64_u128(x: u64, y: u64) -> u64 {
+    let xl = x as u128;
+    let yl = y as u128;
+    ((xl * yl) >> 64) as u64
+}
+
+// On 32-bit CPUs, the u128 multiplication lowers to a chain of libcalls.
+// On WebAssembly, it lowers to a call to `__multi3`.
+// We build it out of 32 bits x 32 bits -> 64 bits partial products instead,
+// which are natively supported on both.
+#[cfg(any(
+    all(
+        not(feature = "soft-mul"),
+        any(target_pointer_width = "32", target_family = "wasm"),
+        not(all(feature = "mulx", target_arch = "x86_64", target_feature = "bmi2"))
+    ),
+    test
+))]
+#[inline(always)]
+fn mullhi_u64_32x32(x: u64, y: u64) -> u64 {
+    let x0 = x as u32 as u64;
+    let x1 = x >> 32;
+    let y0 = y as u32 as u64;
+    let y1 = y >> 32;
+    let x0y0_hi = (x0 * y0) >> 32;
+    let x0y1 = x0 * y1;
+    let x1y0 = x1 * y0;
+    let x1y1 = x1 * y1;
+    // None of these additions can overflow.
+    let temp = x1y0 + x0y0_hi;
+    let temp_lo = temp as u32 as u64;
+    let temp_hi = temp >> 32;
+    x1y1 + temp_hi + ((temp_lo + x0y1) >> 32)
+}
+
+// Same as `mullhi_u64_32x32`, knowing that the high half of `y` is 0.
+#[cfg(any(
+    all(
+        not(feature = "soft-mul"),
+        any(target_pointer_width = "32", target_family = "wasm"),
+        not(all(feature = "mulx", target_arch = "x86_64", target_feature = "bmi2"))
+    ),
+    test
+))]
+#[inline(always)]
+fn mullhi_u64_by_u32_32x32(x: u64, y: u32) -> u64 {
+    let x0 = x as u32 as u64;
+    let x1 = x >> 32;
+    let y = y as u64;
+    // The 96 bits product is x1 * y * 2^32 + x0 * y. None of these additions can overflow.
+    (x1 * y + ((x0 * y) >> 32)) >> 32
+}
+
+// For cores without a hardware multiplier (RV32I/RV32E without the M
+// extension, ...), the compiler turns each of the four partial products
+// of `mullhi_u64_32x32` into a call to `__muldi3`, which is itself a
+// shift-and-add loop over 32-bit halves.
+//
+// This shift-and-add implementation takes 64 iterations regardless of
+// the operands, and does not contain any data-dependent branch.
+// It compiles to about 21 instructions per iteration on RV32I as on
+// thumbv6m, so roughly 1400 instructions per call.
+//
+// Only enable it on cores without any multiply instruction.
+// Cores with `MULS` (every Cortex-M0/M0+, even with the small 32-cycle
+// multiplier) or `UMULL` (Cortex-M3 and above) compute the four partial
+// products in a few hundred cycles at most, and are faster without it.
+#[cfg(any(feature = "soft-mul", test))]
+#[inline]
+fn mullhi_u64_soft(x: u64, y: u64) -> u64 {
+    let mut hi = 0u64;
+    let mut y = y;
+    for _ in 0..64 {
+        // Add `x` if the lowest bit of y is set, and shift the 65-bits
+        // accumulator right by one bit.
+        // The low half of the product is shifted out, we do not need it.
+        // Shifting `y` by a constant avoids a 64-bit variable shift,
+        // which is a libcall on 32-bit targets.
+        let mask = 0u64.wrapping_sub(y & 1);
+        let (sum, carry) = hi.overflowing_add(x & mask);
+        hi = (sum >> 1) | ((carry as u64) << 63);
+        y >>= 1;
+    }
+    hi
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_mullhi_u64_32x32_edge_cases() {
+        let vals = [
+            0u64,
+            1,
+            2,
+            u32::MAX as u64,
+            1 << 32,
+            1 << 63,
+            u64::MAX - 1,
+            u64::MAX,
+        ];
+        for &x in &vals {
+            for &y in &vals {
+                assert_eq!(mullhi_u64_32x32(x, y), mullhi_u64_u128(x, y));
+                assert_eq!(mullhi_u64_soft(x, y), mullhi_u64_u128(x, y));
+                assert_eq!(
+                    mullhi_u64_by_u32_32x32(x, y as u32),
+                    mullhi_u64_u128(x, y as u32 as u64)
+                );
+            }
+        }
+    }
+
+    #[cfg(all(
+        not(feature = "soft-mul"),
+        feature = "mulx",
+        target_arch = "x86_64",
+        target_feature = "bmi2"
+    ))]
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(100000))]
+        #[test]
+        fn test_proptest_mullhi_u64_mulx(x in any::<u64>(), y in any::<u64>()) {
+            assert_eq!(mullhi_u64_mulx(x, y), mullhi_u64_u128(x, y));
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(100000))]
+        #[test]
+        fn test_proptest_mullhi_u64_32x32(x in any::<u64>(), y in any::<u64>()) {
+            assert_eq!(mullhi_u64_32x32(x, y), mullhi_u64_u128(x, y));
+        }
+    }
+
+    #[cfg(all(
+        feature = "nightly",
+        not(any(
+            feature = "soft-mul",
+            all(feature = "mulx", target_arch = "x86_64", target_feature = "bmi2"),
+            target_pointer_width = "32",
+            target_family = "wasm"
+        ))
+    ))]
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(100000))]
+        #[test]
+        fn test_proptest_mullhi_u64_widening(x in any::<u64>(), y in any::<u64>()) {
+            assert_eq!(mullhi_u64_widening(x, y), mullhi_u64_u128(x, y));
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(100000))]
+        #[test]
+        fn test_proptest_mullhi_u64_by_u32_32x32(x in any::<u64>(), y in any::<u32>()) {
+            assert_eq!(mullhi_u64_by_u32_32x32(x, y), mullhi_u64_u128(x, y as u64));
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(10000))]
+        #[test]
+        fn test_proptest_mullhi_u64_soft(x in any::<u64>(), y in any::<u64>()) {
+            assert_eq!(mullhi_u64_soft(x, y), mullhi_u64_u128(x, y));
+        }
+    }
+}
@@ -0,0 +1,265 @@
+//! The division algorithm, generic over the integer type.
+//!
+//! `DividerU64` and `DividerU32` are specialized for their widths. [`MulHiDivider`]
+//! only requires a high multiplication and a handful of wrapping operations,
+//! which makes it usable with `u128`, or with the wide integers of other crates.
+
+use core::ops::{BitAnd, BitOr, Shl, Shr};
+
+use crate::{libdivide_mullhi_u64, Divider};
+
+/// The operations an integer type needs to support to be divided by a [`MulHiDivider`].
+pub trait MulHi:
+    Copy
+    + Ord
+    + Shl<u32, Output = Self>
+    + Shr<u32, Output = Self>
+    + BitAnd<Output = Self>
+    + BitOr<Output = Self>
+{
+    /// The number of bits of the type, which is expected to be at most 256.
+    const BITS: u32;
+    const ZERO: Self;
+    const ONE: Self;
+
+    /// Returns the high half of the full product `self * other`.
+    fn mulhi(self, other: Self) -> Self;
+
+    fn wrapping_add(self, other: Self) -> Self;
+
+    fn wrapping_sub(self, other: Self) -> Self;
+}
+
+macro_rules! impl_mulhi_by_widening {
+    ($t:ty, $wide:ty) => {
+        impl MulHi for $t {
+            const BITS: u32 = <$t>::BITS;
+            const ZERO: $t = 0;
+            const ONE: $t = 1;
+
+            #[inline(always)]
+            fn mulhi(self, other: $t) -> $t {
+                ((self as $wide * other as $wide) >> <$t>::BITS) as $t
+            }
+
+            #[inline(always)]
+            fn wrapping_add(self, other: $t) -> $t {
+                <$t>::wrapping_add(self, other)
+            }
+
+            #[inline(always)]
+            fn wrapping_sub(self, other: $t) -> $t {
+                <$t>::wrapping_sub(self, other)
+            }
+        }
+    };
+}
+
+impl_mulhi_by_widening!(u8, u16);
+impl_mulhi_by_widening!(u16, u32);
+impl_mulhi_by_widening!(u32, u64);
+
+impl MulHi for u64 {
+    const BITS: u32 = u64::BITS;
+    const ZERO: u64 = 0;
+    const ONE: u64 = 1;
+
+    #[inline(always)]
+    fn mulhi(self, other: u64) -> u64 {
+        libdivide_mullhi_u64(self, other)
+    }
+
+    #[inline(always)]
+    fn wrapping_add(self, other: u64) -> u64 {
+        u64::wrapping_add(self, other)
+    }
+
+    #[inline(always)]
+    fn wrapping_sub(self, other: u64) -> u64 {
+        u64::wrapping_sub(self, other)
+    }
+}
+
+impl MulHi for u128 {
+    const BITS: u32 = u128::BITS;
+    const ZERO: u128 = 0;
+    const ONE: u128 = 1;
+
+    #[inline]
+    fn mulhi(self, other: u128) -> u128 {
+        const MASK: u128 = (1 << 64) - 1;
+        let (x_hi, x_lo) = (self >> 64, self & MASK);
+        let (y_hi, y_lo) = (other >> 64, other & MASK);
+        let lo_lo = x_lo * y_lo;
+        let hi_lo = x_hi * y_lo;
+        let lo_hi = x_lo * y_hi;
+        let hi_hi = x_hi * y_hi;
+        // Cannot overflow: lo_hi <= (2^64 - 1)^2 = 2^128 - 2^65 + 1.
+        let cross = (lo_lo >> 64) + (hi_lo & MASK) + lo_hi;
+        hi_hi + (hi_lo >> 64) + (cross >> 64)
+    }
+
+    #[inline(always)]
+    fn wrapping_add(self, other: u128) -> u128 {
+        u128::wrapping_add(self, other)
+    }
+
+    #[inline(always)]
+    fn wrapping_sub(self, other: u128) -> u128 {
+        u128::wrapping_sub(self, other)
+    }
+}
+
+/// Precomputed divider for any integer type implementing [`MulHi`].
+///
+/// ```
+/// use fastdivide::MulHiDivider;
+///
+/// let divider = MulHiDivider::divide_by(10u128.pow(20));
+/// assert_eq!(divider.divide(u128::MAX), u128::MAX / 10u128.pow(20));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MulHiDivider<T> {
+    /// Dividing by 1. `divide` returns its argument untouched.
+    Identity,
+    Fast {
+        magic: T,
+        shift: u8,
+    },
+    BitShift(u8),
+    General {
+        magic_low: T,
+        shift: u8,
+    },
+}
+
+fn floor_log2<T: MulHi>(n: T) -> u32 {
+    let mut log2 = T::BITS - 1;
+    while n >> log2 == T::ZERO {
+        log2 -= 1;
+    }
+    log2
+}
+
+impl<T: MulHi> MulHiDivider<T> {
+    /// Precomputes a divider for `divisor`.
+    ///
+    /// Panics if `divisor` is 0.
+    pub fn divide_by(divisor: T) -> MulHiDivider<T> {
+        assert!(divisor != T::ZERO);
+        if divisor == T::ONE {
+            return MulHiDivider::Identity;
+        }
+        let floor_log_2_d = floor_log2(divisor);
+        if divisor & divisor.wrapping_sub(T::ONE) == T::ZERO {
+            return MulHiDivider::BitShift(floor_log_2_d as u8);
+        }
+        // Long division of 2^{BITS + floor_log_2_d} by the divisor, one bit at a time,
+        // as the type has no double width to do it in one go. The quotient fits in
+        // BITS bits, as the divisor is greater than 2^floor_log_2_d.
+        let mut quotient = T::ZERO;
+        let mut reminder = T::ONE << floor_log_2_d;
+        for _ in 0..T::BITS {
+            let carry = reminder >> (T::BITS - 1) != T::ZERO;
+            reminder = reminder << 1;
+            quotient = quotient << 1;
+            if carry || reminder >= divisor {
+                reminder = reminder.wrapping_sub(divisor);
+                quotient = quotient | T::ONE;
+            }
+        }
+        // See `DividerU64`.
+        let e = divisor.wrapping_sub(reminder);
+        if e < T::ONE << floor_log_2_d {
+            return MulHiDivider::Fast {
+                magic: quotient.wrapping_add(T::ONE),
+                shift: floor_log_2_d as u8,
+            };
+        }
+        let twice_reminder = reminder.wrapping_add(reminder);
+        let carry = if twice_reminder >= divisor || twice_reminder < reminder {
+            T::ONE
+        } else {
+            T::ZERO
+        };
+        let magic_low = quotient
+            .wrapping_add(quotient)
+            .wrapping_add(carry)
+            .wrapping_add(T::ONE);
+        MulHiDivider::General {
+            magic_low,
+            shift: floor_log_2_d as u8,
+        }
+    }
+
+    #[inline]
+    pub fn divide(&self, n: T) -> T {
+        match *self {
+            MulHiDivider::Identity => n,
+            MulHiDivider::BitShift(shift) => n >> shift as u32,
+            MulHiDivider::Fast { magic, shift } => magic.mulhi(n) >> shift as u32,
+            MulHiDivider::General { magic_low, shift } => {
+                let q = magic_low.mulhi(n);
+                let t = (n.wrapping_sub(q) >> 1).wrapping_add(q);
+                t >> shift as u32
+            }
+        }
+    }
+}
+
+impl<T: MulHi> Divider<T> for MulHiDivider<T> {
+    #[inline(always)]
+    fn divide_by(divisor: T) -> Self {
+        MulHiDivider::divide_by(divisor)
+    }
+
+    #[inline(always)]
+    fn divide(&self, n: T) -> T {
+        MulHiDivider::divide(self, n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MulHi, MulHiDivider};
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_mulhi_divider_u16_exhaustive() {
+        for d in (1u16..=300).chain([u16::MAX - 1, u16::MAX]) {
+            let divider = MulHiDivider::divide_by(d);
+            for n in 0..=u16::MAX {
+                assert_eq!(divider.divide(n), n / d);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mulhi_divider_u128() {
+        for d in [3u128, 7, 10u128.pow(19), 10u128.pow(20), u128::MAX] {
+            let divider = MulHiDivider::divide_by(d);
+            for n in [0u128, 1, d - 1, d, 1 << 127, u128::MAX] {
+                assert_eq!(divider.divide(n), n / d);
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_mulhi_u128_proptest(x: u128, a: u64, b: u64, k in 1u32..128) {
+            prop_assert_eq!(x.mulhi(1 << k), x >> (128 - k));
+            let (a, b) = (a as u128, b as u128);
+            prop_assert_eq!((a << 64).mulhi(b << 64), a * b);
+        }
+
+        #[test]
+        fn test_mulhi_divider_proptest(n: u128, d in 1u128..) {
+            prop_assert_eq!(MulHiDivider::divide_by(d).divide(n), n / d);
+        }
+
+        #[test]
+        fn test_mulhi_divider_u64_proptest(n: u64, d in 1u64..) {
+            prop_assert_eq!(MulHiDivider::divide_by(d).divide(n), n / d);
+        }
+    }
+}
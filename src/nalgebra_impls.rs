@@ -0,0 +1,91 @@
+//! Elementwise division of the nalgebra integer matrices, behind the `nalgebra` feature.
+//!
+//! The helpers accept any matrix or vector, including views with strides:
+//! the elements are visited in column-major order.
+//!
+//! ```
+//! use fastdivide::DividerU64;
+//! use nalgebra::{Matrix2x3, Vector3};
+//!
+//! let row_len = DividerU64::divide_by(10);
+//! let rows = row_len.divide_matrix(&Vector3::new(9u64, 10, 123));
+//! assert_eq!(rows, Vector3::new(0, 1, 12));
+//!
+//! let mut m = Matrix2x3::new(1u64, 20, 30, 40, 50, 60);
+//! row_len.divide_matrix_in_place(&mut m.column_mut(1));
+//! assert_eq!(m, Matrix2x3::new(1, 2, 30, 40, 5, 60));
+//! ```
+
+use nalgebra::allocator::Allocator;
+use nalgebra::{DefaultAllocator, Dim, Matrix, OMatrix, RawStorage, RawStorageMut};
+
+use crate::{DividerU32, DividerU64};
+
+macro_rules! impl_divide_matrix {
+    ($divider:ty, $t:ty) => {
+        impl $divider {
+            /// Returns the matrix of the quotients of the elements of `matrix`,
+            /// as `matrix.component_div(..)` would with a matrix filled with the divisor.
+            pub fn divide_matrix<R: Dim, C: Dim, S: RawStorage<$t, R, C>>(
+                &self,
+                matrix: &Matrix<$t, R, C, S>,
+            ) -> OMatrix<$t, R, C>
+            where
+                DefaultAllocator: Allocator<R, C>,
+            {
+                matrix.map(|n| self.divide(n))
+            }
+
+            /// Divides in place all of the elements of `matrix`.
+            pub fn divide_matrix_in_place<R: Dim, C: Dim, S: RawStorageMut<$t, R, C>>(
+                &self,
+                matrix: &mut Matrix<$t, R, C, S>,
+            ) {
+                matrix.apply(|n| *n = self.divide(*n));
+            }
+        }
+    };
+}
+
+impl_divide_matrix!(DividerU32, u32);
+impl_divide_matrix!(DividerU64, u64);
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{Matrix3, Matrix5x3, Vector4};
+
+    use crate::{DividerU32, DividerU64};
+
+    #[test]
+    fn test_divide_matrix() {
+        for d in [1u64, 7, 16, u64::MAX] {
+            let divider = DividerU64::divide_by(d);
+            let m =
+                Matrix5x3::from_fn(|i, j| (i as u64 * 1_000 + j as u64).wrapping_mul(152342341));
+            assert_eq!(divider.divide_matrix(&m), m.map(|n| n / d));
+            let v = Vector4::new(0u32, 1, u32::MAX - 1, u32::MAX);
+            let divider = DividerU32::divide_by(d as u32);
+            assert_eq!(divider.divide_matrix(&v), v.map(|n| n / d as u32));
+        }
+    }
+
+    #[test]
+    fn test_divide_matrix_in_place() {
+        let divider = DividerU32::divide_by(3);
+        let mut m = Matrix3::from_fn(|i, j| (i * 3 + j) as u32 * 10);
+        let expected = Matrix3::from_fn(|i, j| {
+            let n = (i * 3 + j) as u32 * 10;
+            if j == 0 {
+                n
+            } else {
+                n / 3
+            }
+        });
+        let mut view = m.view_mut((0, 1), (3, 2));
+        divider.divide_matrix_in_place(&mut view);
+        assert_eq!(m, expected);
+        let mut row = m.row_mut(2);
+        divider.divide_matrix_in_place(&mut row);
+        assert_eq!(m.row(2), expected.row(2).map(|n| n / 3));
+    }
+}
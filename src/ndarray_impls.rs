@@ -0,0 +1,74 @@
+//! Division of ndarray arrays, behind the `ndarray` feature.
+
+use ndarray::{ArrayBase, DataMut, Dimension};
+
+use crate::DividerU64;
+
+impl DividerU64 {
+    /// Divides in place all of the elements of `array`, which can be an
+    /// `Array` or an `ArrayViewMut` with any layout.
+    ///
+    /// Arrays whose elements are contiguous in memory, in any order, go through
+    /// [`DividerU64::divide_slice_in_place`]. The others, such as views of
+    /// every other column, are divided element by element, following their strides.
+    ///
+    /// ```
+    /// use fastdivide::DividerU64;
+    /// use ndarray::{array, s};
+    ///
+    /// let mut a = array![[10u64, 20, 30], [40, 50, 60]];
+    /// let divider = DividerU64::divide_by(10);
+    /// divider.divide_ndarray(&mut a.slice_mut(s![.., ..;2]));
+    /// assert_eq!(a, array![[1, 20, 3], [4, 50, 6]]);
+    /// ```
+    pub fn divide_ndarray<S, D>(&self, array: &mut ArrayBase<S, D>)
+    where
+        S: DataMut<Elem = u64>,
+        D: Dimension,
+    {
+        match array.as_slice_memory_order_mut() {
+            Some(values) => self.divide_slice_in_place(values),
+            None => self.divide_each(array.iter_mut()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::DividerU64;
+    use ndarray::{s, Array, Array3};
+
+    fn numerators() -> Array3<u64> {
+        Array::from_shape_fn((4, 5, 6), |(i, j, k)| {
+            ((i * 100 + j * 10 + k) as u64).wrapping_mul(152342341u64 << 20)
+        })
+    }
+
+    #[test]
+    fn test_divide_ndarray() {
+        for d in [1u64, 7, 1 << 20, u64::MAX] {
+            let divider = DividerU64::divide_by(d);
+            let expected = numerators().mapv(|n| n / d);
+            let mut a = numerators();
+            divider.divide_ndarray(&mut a);
+            assert_eq!(a, expected);
+            // Contiguous, but not in the standard order.
+            let mut a = numerators();
+            divider.divide_ndarray(&mut a.view_mut().reversed_axes());
+            assert_eq!(a, expected);
+        }
+    }
+
+    #[test]
+    fn test_divide_ndarray_strided() {
+        let divider = DividerU64::divide_by(7);
+        let mut a = numerators();
+        let mut expected = numerators();
+        let slice = s![1..3, ..;-2, ..;3];
+        expected.slice_mut(slice).mapv_inplace(|n| n / 7);
+        let mut view = a.slice_mut(slice);
+        assert!(view.as_slice_memory_order_mut().is_none());
+        divider.divide_ndarray(&mut view);
+        assert_eq!(a, expected);
+    }
+}
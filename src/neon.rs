@@ -0,0 +1,269 @@
+//! NEON implementation of the slice kernels.
+//!
+//! NEON has no 64-bit multiplication, so the high multiplication is emulated
+//! with four `vmull_u32` 32x32 bits multiplications. The scalar kernels
+//! already compile to `umulh` on aarch64, and are used for the tails.
+
+use core::arch::aarch64::*;
+
+use crate::DividerU64;
+
+const LANES: usize = 2;
+
+/// Returns true if the NEON kernels can be used on the current CPU.
+///
+/// Without the `std` feature, this is only known at compile time.
+#[inline]
+pub(crate) fn is_available() -> bool {
+    #[cfg(any(feature = "std", test))]
+    {
+        std::arch::is_aarch64_feature_detected!("neon")
+    }
+    #[cfg(not(any(feature = "std", test)))]
+    {
+        cfg!(target_feature = "neon")
+    }
+}
+
+/// A 64-bits scalar factor, split into its 32-bits halves
+/// once and for all.
+#[derive(Clone, Copy)]
+struct SplitFactor {
+    lo: uint32x2_t,
+    hi: uint32x2_t,
+}
+
+#[inline]
+#[target_feature(enable = "neon")]
+unsafe fn split(factor: u64) -> SplitFactor {
+    SplitFactor {
+        lo: vdup_n_u32(factor as u32),
+        hi: vdup_n_u32((factor >> 32) as u32),
+    }
+}
+
+/// Returns the high 64 bits of the product of each lane of `n` with `factor`.
+#[inline]
+#[target_feature(enable = "neon")]
+unsafe fn mullhi(factor: SplitFactor, n: uint64x2_t) -> uint64x2_t {
+    let n_lo = vmovn_u64(n);
+    let n_hi = vshrn_n_u64::<32>(n);
+    let lo_lo = vmull_u32(factor.lo, n_lo);
+    let hi_lo = vmull_u32(factor.hi, n_lo);
+    let lo_hi = vmull_u32(factor.lo, n_hi);
+    let hi_hi = vmull_u32(factor.hi, n_hi);
+    // None of these additions can overflow.
+    let cross = vaddq_u64(
+        vaddq_u64(
+            vshrq_n_u64::<32>(lo_lo),
+            vandq_u64(hi_lo, vdupq_n_u64(u32::MAX as u64)),
+        ),
+        lo_hi,
+    );
+    vaddq_u64(
+        vaddq_u64(hi_hi, vshrq_n_u64::<32>(hi_lo)),
+        vshrq_n_u64::<32>(cross),
+    )
+}
+
+/// Returns the low 64 bits of the product of each lane of `n` with `factor`.
+#[inline]
+#[target_feature(enable = "neon")]
+unsafe fn mullo(factor: SplitFactor, n: uint64x2_t) -> uint64x2_t {
+    let n_lo = vmovn_u64(n);
+    let n_hi = vshrn_n_u64::<32>(n);
+    let cross = vmlal_u32(vmull_u32(factor.hi, n_lo), factor.lo, n_hi);
+    vaddq_u64(vmull_u32(factor.lo, n_lo), vshlq_n_u64::<32>(cross))
+}
+
+/// NEON only shifts by a variable amount to the left.
+/// A negative amount shifts to the right.
+#[inline]
+#[target_feature(enable = "neon")]
+unsafe fn right_shift(shift: u8) -> int64x2_t {
+    vdupq_n_s64(-(shift as i64))
+}
+
+/// Runs `$body` with `$divide` bound to a closure dividing `LANES` values
+/// by `$divider`, specialized for the algorithm of the divider.
+///
+/// Must be expanded within a function with the `neon` target feature.
+macro_rules! with_neon_divide_fn {
+    ($divider:expr, $divide:ident => $body:expr) => {
+        match $divider {
+            DividerU64::Fast { magic, shift } => {
+                let magic = split(magic);
+                let shift = right_shift(shift);
+                let $divide = move |n: uint64x2_t| vshlq_u64(mullhi(magic, n), shift);
+                $body
+            }
+            DividerU64::BitShift(shift) => {
+                let shift = right_shift(shift);
+                let $divide = move |n: uint64x2_t| vshlq_u64(n, shift);
+                $body
+            }
+            DividerU64::Identity => {
+                let $divide = |n: uint64x2_t| n;
+                $body
+            }
+            DividerU64::Compare(divisor) => {
+                let divisor = vdupq_n_u64(divisor);
+                let $divide = move |n: uint64x2_t| vshrq_n_u64::<63>(vcgeq_u64(n, divisor));
+                $body
+            }
+            DividerU64::PreShift {
+                magic,
+                pre_shift,
+                shift,
+            } => {
+                let magic = split(magic);
+                let pre_shift = right_shift(pre_shift);
+                let shift = right_shift(shift);
+                let $divide =
+                    move |n: uint64x2_t| vshlq_u64(mullhi(magic, vshlq_u64(n, pre_shift)), shift);
+                $body
+            }
+            DividerU64::General { magic_low, shift } => {
+                let magic_low = split(magic_low);
+                let shift = right_shift(shift);
+                let $divide = move |n: uint64x2_t| {
+                    let q = mullhi(magic_low, n);
+                    let t = vaddq_u64(vshrq_n_u64::<1>(vsubq_u64(n, q)), q);
+                    vshlq_u64(t, shift)
+                };
+                $body
+            }
+        }
+    };
+}
+
+#[inline]
+#[target_feature(enable = "neon")]
+unsafe fn map_slice(
+    f: impl Fn(uint64x2_t) -> uint64x2_t,
+    f_scalar: impl Fn(u64) -> u64,
+    src: &[u64],
+    dst: &mut [u64],
+) {
+    assert_eq!(src.len(), dst.len());
+    let mut src_chunks = src.chunks_exact(LANES);
+    let mut dst_chunks = dst.chunks_exact_mut(LANES);
+    for (src_chunk, dst_chunk) in (&mut src_chunks).zip(&mut dst_chunks) {
+        vst1q_u64(dst_chunk.as_mut_ptr(), f(vld1q_u64(src_chunk.as_ptr())));
+    }
+    let src_tail = src_chunks.remainder();
+    for (dst_val, &src_val) in dst_chunks.into_remainder().iter_mut().zip(src_tail) {
+        *dst_val = f_scalar(src_val);
+    }
+}
+
+#[inline]
+#[target_feature(enable = "neon")]
+unsafe fn map_slice_in_place(
+    f: impl Fn(uint64x2_t) -> uint64x2_t,
+    f_scalar: impl Fn(u64) -> u64,
+    values: &mut [u64],
+) {
+    let mut chunks = values.chunks_exact_mut(LANES);
+    for chunk in &mut chunks {
+        let ptr = chunk.as_mut_ptr();
+        vst1q_u64(ptr, f(vld1q_u64(ptr)));
+    }
+    for val in chunks.into_remainder() {
+        *val = f_scalar(*val);
+    }
+}
+
+/// # Safety
+///
+/// The CPU must support NEON.
+#[target_feature(enable = "neon")]
+pub(crate) unsafe fn divide_slice(divider: DividerU64, src: &[u64], dst: &mut [u64]) {
+    with_neon_divide_fn!(divider, divide => map_slice(divide, |n| divider.divide(n), src, dst))
+}
+
+/// # Safety
+///
+/// The CPU must support NEON.
+#[target_feature(enable = "neon")]
+pub(crate) unsafe fn divide_slice_in_place(divider: DividerU64, values: &mut [u64]) {
+    with_neon_divide_fn!(divider, divide => map_slice_in_place(divide, |n| divider.divide(n), values))
+}
+
+/// # Safety
+///
+/// The CPU must support NEON.
+#[target_feature(enable = "neon")]
+pub(crate) unsafe fn mod_slice(divider: DividerU64, divisor: u64, src: &[u64], dst: &mut [u64]) {
+    let divisors = split(divisor);
+    with_neon_divide_fn!(divider, divide => map_slice(
+        |n| vsubq_u64(n, mullo(divisors, divide(n))),
+        |n| n - divider.divide(n) * divisor,
+        src,
+        dst,
+    ))
+}
+
+/// # Safety
+///
+/// The CPU must support NEON.
+#[target_feature(enable = "neon")]
+pub(crate) unsafe fn mod_slice_in_place(divider: DividerU64, divisor: u64, values: &mut [u64]) {
+    let divisors = split(divisor);
+    with_neon_divide_fn!(divider, divide => map_slice_in_place(
+        |n| vsubq_u64(n, mullo(divisors, divide(n))),
+        |n| n - divider.divide(n) * divisor,
+        values,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    fn numerators() -> Vec<u64> {
+        (0u64..1_000)
+            .chain([
+                u64::MAX - 1,
+                u64::MAX,
+                1 << 63,
+                (1 << 63) - 1,
+                234234131223u64,
+            ])
+            .collect()
+    }
+
+    #[test]
+    fn test_neon_kernels() {
+        let src = numerators();
+        let divisors = (1u64..300).chain((0..64).map(|i| 1 << i)).chain([
+            14,
+            234234131223u64,
+            (1 << 63) + 1,
+            u64::MAX,
+        ]);
+        for d in divisors {
+            let divider = DividerU64::divide_by(d);
+            let mut quotients = vec![0u64; src.len()];
+            unsafe { divide_slice(divider, &src, &mut quotients) };
+            let expected: Vec<u64> = src.iter().map(|n| n / d).collect();
+            assert_eq!(quotients, expected);
+            let mut remainders = src.clone();
+            unsafe { mod_slice_in_place(divider, d, &mut remainders) };
+            let expected: Vec<u64> = src.iter().map(|n| n % d).collect();
+            assert_eq!(remainders, expected);
+        }
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_neon_mullhi_proptest(factor: u64, n: [u64; LANES]) {
+            let mut res = [0u64; LANES];
+            unsafe { vst1q_u64(res.as_mut_ptr(), mullhi(split(factor), vld1q_u64(n.as_ptr()))) };
+            for (&n, &res) in n.iter().zip(&res) {
+                assert_eq!(res, ((factor as u128 * n as u128) >> 64) as u64);
+            }
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! Integration with `num-traits`.
+//!
+//! The methods of [`IntegerDivider`] mirror the division operations of
+//! `num_integer::Integer`, with a precomputed divisor.
+
+use num_traits::{PrimInt, Unsigned};
+
+use crate::{DivRemU64, Divider, DividerU32};
+
+/// The `num_integer::Integer` division operations, for a precomputed divisor.
+pub trait IntegerDivider<T: PrimInt + Unsigned>: Divider<T> {
+    /// Returns the divisor.
+    fn divisor(&self) -> T;
+
+    /// Returns `(n / divisor, n % divisor)`.
+    #[inline]
+    fn div_rem(&self, n: T) -> (T, T) {
+        let quotient = self.divide(n);
+        (quotient, n - quotient * self.divisor())
+    }
+
+    /// Returns `n / divisor`, rounded down.
+    #[inline]
+    fn div_floor(&self, n: T) -> T {
+        self.divide(n)
+    }
+
+    /// Returns `n % divisor`.
+    #[inline]
+    fn mod_floor(&self, n: T) -> T {
+        self.div_rem(n).1
+    }
+
+    /// Returns `n / divisor`, rounded up.
+    #[inline]
+    fn div_ceil(&self, n: T) -> T {
+        let (quotient, remainder) = self.div_rem(n);
+        if remainder.is_zero() {
+            quotient
+        } else {
+            quotient + T::one()
+        }
+    }
+}
+
+impl IntegerDivider<u64> for DivRemU64 {
+    #[inline]
+    fn divisor(&self) -> u64 {
+        DivRemU64::divisor(self)
+    }
+
+    #[inline]
+    fn div_rem(&self, n: u64) -> (u64, u64) {
+        DivRemU64::div_rem(self, n)
+    }
+}
+
+/// A divider alongside its divisor, implementing [`IntegerDivider`]
+/// for the integers narrower than `u64`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WithDivisor<T, D> {
+    divider: D,
+    divisor: T,
+}
+
+impl<T: PrimInt + Unsigned, D: Divider<T>> Divider<T> for WithDivisor<T, D> {
+    #[inline]
+    fn divide_by(divisor: T) -> Self {
+        WithDivisor {
+            divider: D::divide_by(divisor),
+            divisor,
+        }
+    }
+
+    #[inline]
+    fn divide(&self, n: T) -> T {
+        self.divider.divide(n)
+    }
+}
+
+impl<T: PrimInt + Unsigned, D: Divider<T>> IntegerDivider<T> for WithDivisor<T, D> {
+    #[inline]
+    fn divisor(&self) -> T {
+        self.divisor
+    }
+}
+
+/// The unsigned primitive integers, with their precomputed divider.
+///
+/// ```
+/// use fastdivide::{Divider, FastDivisible, IntegerDivider};
+///
+/// fn pages<T: FastDivisible>(lens: &[T], page_len: T) -> T {
+///     let divider = T::Divider::divide_by(page_len);
+///     lens.iter().fold(T::zero(), |total, &len| total + divider.div_ceil(len))
+/// }
+///
+/// assert_eq!(pages(&[10u16, 20, 30], 8), 2 + 3 + 4);
+/// ```
+pub trait FastDivisible: PrimInt + Unsigned {
+    type Divider: IntegerDivider<Self>;
+}
+
+impl FastDivisible for u8 {
+    type Divider = WithDivisor<u8, DividerU32>;
+}
+
+impl FastDivisible for u16 {
+    type Divider = WithDivisor<u16, DividerU32>;
+}
+
+impl FastDivisible for u32 {
+    type Divider = WithDivisor<u32, DividerU32>;
+}
+
+impl FastDivisible for u64 {
+    type Divider = DivRemU64;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FastDivisible, IntegerDivider};
+    use crate::Divider;
+    use core::fmt::Debug;
+
+    fn check<T: FastDivisible + Debug>(divisors: &[T], numerators: &[T]) {
+        for &d in divisors {
+            let divider = T::Divider::divide_by(d);
+            assert_eq!(divider.divisor(), d);
+            for &n in numerators {
+                let (quotient, remainder) = (n / d, n % d);
+                assert_eq!(divider.div_floor(n), quotient);
+                assert_eq!(divider.mod_floor(n), remainder);
+                assert_eq!(divider.div_rem(n), (quotient, remainder));
+                let ceil = if remainder.is_zero() {
+                    quotient
+                } else {
+                    quotient + T::one()
+                };
+                assert_eq!(divider.div_ceil(n), ceil);
+            }
+        }
+    }
+
+    #[test]
+    fn test_integer_divider() {
+        check::<u8>(&[1, 3, 8, u8::MAX], &[0, 1, 7, 8, 9, u8::MAX]);
+        check::<u16>(&[1, 3, 8, u16::MAX], &[0, 1, 7, 8, 9, u16::MAX]);
+        check::<u32>(&[1, 3, 8, u32::MAX], &[0, 1, 7, 8, 9, u32::MAX]);
+        check::<u64>(
+            &[1, 3, 8, (1 << 61) - 1, u64::MAX],
+            &[0, 1, 7, 8, 9, 1 << 63, u64::MAX],
+        );
+    }
+}
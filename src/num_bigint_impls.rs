@@ -0,0 +1,62 @@
+//! Division of `num_bigint::BigUint` by a `u64`, behind the `num-bigint` feature.
+
+use alloc::vec::Vec;
+
+use num_bigint::BigUint;
+
+use crate::DivRemU64;
+
+impl DivRemU64 {
+    /// Returns the quotient and the remainder of the division of `n`
+    /// by the divisor, with [`DivRemU64::divide_limbs`].
+    ///
+    /// ```
+    /// use fastdivide::DivRemU64;
+    /// use num_bigint::BigUint;
+    ///
+    /// // Converting to decimal extracts 19 digits per division.
+    /// let ten_pow_19 = DivRemU64::divide_by(10_000_000_000_000_000_000);
+    /// let n = BigUint::from(u128::MAX);
+    /// let (q, r) = ten_pow_19.divide_biguint(&n);
+    /// assert_eq!(q, BigUint::from(u128::MAX / 10u128.pow(19)));
+    /// assert_eq!(r, 3_374_607_431_768_211_455);
+    /// ```
+    pub fn divide_biguint(&self, n: &BigUint) -> (BigUint, u64) {
+        let mut limbs = n.to_u64_digits();
+        let remainder = self.divide_limbs(&mut limbs);
+        let digits: Vec<u32> = limbs
+            .iter()
+            .flat_map(|&limb| [limb as u32, (limb >> 32) as u32])
+            .collect();
+        (BigUint::new(digits), remainder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::DivRemU64;
+    use num_bigint::BigUint;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_divide_biguint() {
+        let n = BigUint::from(3u32).pow(200) + 12345u32;
+        for d in [1u64, 3, 7, 10_000_000_000_000_000_000, 1 << 63, u64::MAX] {
+            let (q, r) = DivRemU64::divide_by(d).divide_biguint(&n);
+            assert_eq!(q, &n / d);
+            assert_eq!(BigUint::from(r), &n % d);
+        }
+        let (q, r) = DivRemU64::divide_by(7).divide_biguint(&BigUint::default());
+        assert_eq!((q, r), (BigUint::default(), 0));
+    }
+
+    proptest! {
+        #[test]
+        fn test_proptest_divide_biguint(digits in proptest::collection::vec(any::<u32>(), 0..12), d in 1..u64::MAX) {
+            let n = BigUint::new(digits);
+            let (q, r) = DivRemU64::divide_by(d).divide_biguint(&n);
+            assert_eq!(q, &n / d);
+            assert_eq!(BigUint::from(r), &n % d);
+        }
+    }
+}
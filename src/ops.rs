@@ -0,0 +1,46 @@
+//! Method-call syntax for the divisions.
+
+use crate::{DivRemU64, DividerU64};
+
+/// Extension trait dividing a primitive integer by a precomputed divider.
+///
+/// ```
+/// use fastdivide::{DivRemU64, DividerU64, FastDiv};
+///
+/// assert_eq!(100u64.fast_div(&DividerU64::divide_by(7)), 14);
+/// let divider = DivRemU64::divide_by(7);
+/// assert_eq!(100u64.fast_rem(&divider), 2);
+/// assert_eq!(100u64.fast_div_rem(&divider), (14, 2));
+/// ```
+pub trait FastDiv: Sized {
+    /// The divider computing quotients.
+    type Divider;
+    /// The divider computing remainders, which needs to know its divisor.
+    type RemDivider;
+
+    fn fast_div(self, divider: &Self::Divider) -> Self;
+
+    fn fast_rem(self, divider: &Self::RemDivider) -> Self;
+
+    fn fast_div_rem(self, divider: &Self::RemDivider) -> (Self, Self);
+}
+
+impl FastDiv for u64 {
+    type Divider = DividerU64;
+    type RemDivider = DivRemU64;
+
+    #[inline(always)]
+    fn fast_div(self, divider: &DividerU64) -> u64 {
+        divider.divide(self)
+    }
+
+    #[inline(always)]
+    fn fast_rem(self, divider: &DivRemU64) -> u64 {
+        divider.remainder(self)
+    }
+
+    #[inline(always)]
+    fn fast_div_rem(self, divider: &DivRemU64) -> (u64, u64) {
+        divider.div_rem(self)
+    }
+}
@@ -0,0 +1,126 @@
+//! Parallel slice division, over scoped threads.
+//!
+//! With the `rayon` feature, the chunks are processed on the global rayon
+//! thread pool instead, which avoids spawning threads on every call.
+
+#[cfg(not(feature = "rayon"))]
+use core::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(not(feature = "rayon"))]
+use std::thread;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::DividerU64;
+
+/// Below this number of values per thread, spawning threads costs more
+/// than it saves.
+const MIN_CHUNK_LEN: usize = 1 << 16;
+
+/// `available_parallelism` reads files under `/proc` and `/sys` on Linux:
+/// we only call it once. 0 means it has not been called yet.
+#[cfg(not(feature = "rayon"))]
+static NUM_THREADS: AtomicUsize = AtomicUsize::new(0);
+
+#[cfg(not(feature = "rayon"))]
+fn num_threads() -> usize {
+    let cached = NUM_THREADS.load(Ordering::Relaxed);
+    if cached != 0 {
+        return cached;
+    }
+    let num_threads = thread::available_parallelism().map_or(1, |n| n.get());
+    NUM_THREADS.store(num_threads, Ordering::Relaxed);
+    num_threads
+}
+
+#[cfg(feature = "rayon")]
+fn num_threads() -> usize {
+    rayon::current_num_threads()
+}
+
+/// Returns the number of values each thread should process.
+/// If it is not smaller than `len`, the work should not be split.
+fn chunk_len(len: usize) -> usize {
+    if len <= MIN_CHUNK_LEN {
+        return len;
+    }
+    len.div_ceil(num_threads()).max(MIN_CHUNK_LEN)
+}
+
+impl DividerU64 {
+    /// Same as [`DividerU64::divide_slice`], but splits the work
+    /// over as many threads as there are CPUs.
+    ///
+    /// Panics if `src` and `dst` do not have the same length.
+    pub fn par_divide_slice(&self, src: &[u64], dst: &mut [u64]) {
+        assert_eq!(src.len(), dst.len());
+        let chunk_len = chunk_len(src.len());
+        if chunk_len >= src.len() {
+            return self.divide_slice(src, dst);
+        }
+        #[cfg(feature = "rayon")]
+        {
+            src.par_chunks(chunk_len)
+                .zip(dst.par_chunks_mut(chunk_len))
+                .for_each(|(src_chunk, dst_chunk)| self.divide_slice(src_chunk, dst_chunk));
+        }
+        #[cfg(not(feature = "rayon"))]
+        thread::scope(|scope| {
+            for (src_chunk, dst_chunk) in src.chunks(chunk_len).zip(dst.chunks_mut(chunk_len)) {
+                scope.spawn(move || self.divide_slice(src_chunk, dst_chunk));
+            }
+        });
+    }
+
+    /// Same as [`DividerU64::divide_slice_in_place`], but splits the work
+    /// over as many threads as there are CPUs.
+    pub fn par_divide_slice_in_place(&self, values: &mut [u64]) {
+        let chunk_len = chunk_len(values.len());
+        if chunk_len >= values.len() {
+            return self.divide_slice_in_place(values);
+        }
+        #[cfg(feature = "rayon")]
+        {
+            values
+                .par_chunks_mut(chunk_len)
+                .for_each(|chunk| self.divide_slice_in_place(chunk));
+        }
+        #[cfg(not(feature = "rayon"))]
+        thread::scope(|scope| {
+            for chunk in values.chunks_mut(chunk_len) {
+                scope.spawn(move || self.divide_slice_in_place(chunk));
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{chunk_len, MIN_CHUNK_LEN};
+    use crate::DividerU64;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_chunk_len() {
+        assert_eq!(chunk_len(0), 0);
+        assert_eq!(chunk_len(MIN_CHUNK_LEN), MIN_CHUNK_LEN);
+        assert!(chunk_len(10 * MIN_CHUNK_LEN) >= MIN_CHUNK_LEN);
+    }
+
+    #[test]
+    fn test_par_divide_slice() {
+        let src: Vec<u64> = (0..1_000_003u64)
+            .map(|i| i.wrapping_mul(152342341u64))
+            .collect();
+        for d in [1u64, 7, 14, 1 << 20, u64::MAX] {
+            let divider = DividerU64::divide_by(d);
+            let expected: Vec<u64> = src.iter().map(|n| n / d).collect();
+            let mut dst = vec![0u64; src.len()];
+            divider.par_divide_slice(&src, &mut dst);
+            assert_eq!(dst, expected);
+            let mut values = src.clone();
+            divider.par_divide_slice_in_place(&mut values);
+            assert_eq!(values, expected);
+        }
+    }
+}
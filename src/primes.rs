@@ -0,0 +1,149 @@
+//! Primality testing.
+
+use crate::divrem::DivisibilityCheck;
+use crate::{DividerU64, Montgomery64};
+
+/// The first 12 primes, which are enough bases for the Miller-Rabin test
+/// to be exact for all of the `u64`s.
+const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+/// Returns whether `n` is prime, according to `rounds` rounds of the
+/// Miller-Rabin test.
+///
+/// The bases are the first `rounds` primes, rather than random numbers, so that
+/// the test is deterministic. With 12 rounds or more, it is exact: a composite
+/// `n` passing the test is then impossible. The exponentiations are performed
+/// in Montgomery form.
+pub fn is_probable_prime(n: u64, rounds: usize) -> bool {
+    if n < 2 {
+        return false;
+    }
+    for &p in &BASES {
+        if DivisibilityCheck::new(p).is_divisible(n) {
+            return n == p;
+        }
+    }
+    let shift = (n - 1).trailing_zeros();
+    let d = (n - 1) >> shift;
+    let montgomery = Montgomery64::new(n);
+    let one = montgomery.to_mont(1);
+    let minus_one = montgomery.to_mont(n - 1);
+    'bases: for &a in BASES.iter().take(rounds) {
+        let mut x = montgomery.pow(montgomery.to_mont(a), d);
+        if x == one || x == minus_one {
+            continue;
+        }
+        for _ in 1..shift {
+            x = montgomery.mul(x, x);
+            if x == minus_one {
+                continue 'bases;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+/// Returns the smallest prime greater than or equal to `at_least`,
+/// along with the divider for it.
+///
+/// This is meant for hash tables and Bloom filters with a prime number
+/// of buckets: the divider cannot get out of sync with the prime.
+///
+/// Panics if the prime does not fit in a `u64`, that is, if `at_least` is
+/// greater than `2^64 - 59`.
+pub fn next_prime_divider(at_least: u64) -> (u64, DividerU64) {
+    let prime = if at_least <= 2 {
+        2
+    } else {
+        // Only the odd numbers need testing.
+        (at_least | 1..=u64::MAX)
+            .step_by(2)
+            .find(|&n| is_probable_prime(n, BASES.len()))
+            .expect("no prime greater than or equal to `at_least` fits in a u64")
+    };
+    (prime, DividerU64::divide_by(prime))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_probable_prime, next_prime_divider};
+    use crate::DividerU64;
+    use proptest::prelude::*;
+
+    fn is_prime_reference(n: u64) -> bool {
+        n >= 2 && (2..).take_while(|k| k * k <= n).all(|k| n / k * k != n)
+    }
+
+    #[test]
+    fn test_is_probable_prime_small() {
+        for n in 0..10_000 {
+            assert_eq!(is_probable_prime(n, 12), is_prime_reference(n), "{}", n);
+        }
+    }
+
+    #[test]
+    fn test_is_probable_prime() {
+        let primes = [
+            998_244_353,
+            1_000_000_007,
+            (1 << 61) - 1,
+            18_446_744_073_709_551_557,
+        ];
+        for p in primes {
+            assert!(is_probable_prime(p, 12));
+        }
+        // 3215031751 is a strong pseudoprime to the bases 2, 3, 5 and 7,
+        // and 3825123056546413051 to the bases up to 23.
+        assert!(is_probable_prime(3_215_031_751, 4));
+        assert!(!is_probable_prime(3_215_031_751, 5));
+        assert!(is_probable_prime(3_825_123_056_546_413_051, 9));
+        assert!(!is_probable_prime(3_825_123_056_546_413_051, 12));
+        assert!(!is_probable_prime(u64::MAX, 12));
+        assert!(!is_probable_prime(1_000_000_007 * 998_244_353, 12));
+        // The small primes do not depend on the number of rounds.
+        assert!(is_probable_prime(37, 0));
+    }
+
+    #[test]
+    fn test_next_prime_divider() {
+        let expected = [
+            (0, 2),
+            (2, 2),
+            (3, 3),
+            (4, 5),
+            (14, 17),
+            (1_000_000_000, 1_000_000_007),
+        ];
+        for (at_least, prime) in expected {
+            assert_eq!(
+                next_prime_divider(at_least),
+                (prime, DividerU64::divide_by(prime))
+            );
+        }
+        let largest = 18_446_744_073_709_551_557;
+        assert_eq!(next_prime_divider(largest - 1).0, largest);
+        assert_eq!(next_prime_divider(largest).0, largest);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_next_prime_divider_overflow() {
+        next_prime_divider(u64::MAX - 57);
+    }
+
+    proptest! {
+        #[test]
+        fn test_proptest_is_probable_prime(n in 0u64..(1 << 40)) {
+            assert_eq!(is_probable_prime(n, 12), is_prime_reference(n));
+        }
+
+        #[test]
+        fn test_proptest_next_prime_divider(at_least in 0u64..(1 << 40)) {
+            let (prime, divider) = next_prime_divider(at_least);
+            assert!(prime >= at_least && is_prime_reference(prime));
+            assert!((at_least..prime).all(|n| !is_prime_reference(n)));
+            assert_eq!(divider.divisor(), prime);
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Python bindings, with the `python` feature.
+//!
+//! [`Divider`] is exported as a Python class by the `fastdivide` module.
+//! Building the extension module takes a `cdylib`, with pyo3's
+//! `extension-module` feature, e.g. `maturin build --features python,pyo3/extension-module`.
+//!
+//! ```python
+//! from fastdivide import Divider
+//!
+//! divider = Divider(7)
+//! divider.divide(100)  # 14
+//! divider.divmod(100)  # (14, 2)
+//! divider.divide_many(numpy.array([7, 8, 15], dtype=numpy.uint64))  # [1, 1, 2]
+//! ```
+
+use std::string::String;
+use std::vec::Vec;
+
+use pyo3::buffer::PyBuffer;
+use pyo3::exceptions::{PyValueError, PyZeroDivisionError};
+use pyo3::prelude::*;
+
+use crate::DivRemU64;
+
+/// A precomputed divider, exported to Python.
+#[pyclass(name = "Divider", module = "fastdivide", frozen)]
+#[derive(Debug, Clone, Copy)]
+pub struct Divider {
+    divider: DivRemU64,
+}
+
+#[pymethods]
+impl Divider {
+    /// Precomputes a divider for `divisor`.
+    ///
+    /// Raises `ZeroDivisionError` if `divisor` is 0.
+    #[new]
+    pub fn new(divisor: u64) -> PyResult<Divider> {
+        if divisor == 0 {
+            return Err(PyZeroDivisionError::new_err("the divisor must not be 0"));
+        }
+        Ok(Divider {
+            divider: DivRemU64::divide_by(divisor),
+        })
+    }
+
+    #[getter]
+    pub fn divisor(&self) -> u64 {
+        self.divider.divisor()
+    }
+
+    /// Returns `n // divisor`.
+    pub fn divide(&self, n: u64) -> u64 {
+        self.divider.divide(n)
+    }
+
+    /// Returns `(n // divisor, n % divisor)`.
+    pub fn divmod(&self, n: u64) -> (u64, u64) {
+        self.divider.div_rem(n)
+    }
+
+    /// Returns the quotients of the values of a buffer of `uint64`,
+    /// such as a numpy array, as a list.
+    pub fn divide_many(&self, py: Python<'_>, values: PyBuffer<u64>) -> PyResult<Vec<u64>> {
+        self.quotients(py, &values)
+    }
+
+    /// Divides the values of a writable buffer of `uint64` in place.
+    pub fn divide_in_place(&self, py: Python<'_>, values: PyBuffer<u64>) -> PyResult<()> {
+        if values.readonly() {
+            return Err(PyValueError::new_err("the buffer is read-only"));
+        }
+        let quotients = self.quotients(py, &values)?;
+        values.copy_from_slice(py, &quotients)
+    }
+
+    fn __repr__(&self) -> String {
+        std::format!("Divider({})", self.divisor())
+    }
+}
+
+impl Divider {
+    /// The GIL is released while dividing.
+    fn quotients(&self, py: Python<'_>, values: &PyBuffer<u64>) -> PyResult<Vec<u64>> {
+        let mut quotients = values.to_vec(py)?;
+        let divider = self.divider.divider();
+        py.detach(|| divider.divide_slice_in_place(&mut quotients));
+        Ok(quotients)
+    }
+}
+
+/// The `fastdivide` Python module.
+#[pymodule]
+pub fn fastdivide(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<Divider>()
+}
+
+#[cfg(test)]
+mod tests {
+    use pyo3::ffi::c_str;
+    use pyo3::prelude::*;
+    use pyo3::types::PyDict;
+
+    use super::Divider;
+
+    #[test]
+    fn test_python_divider() {
+        Python::attach(|py| {
+            let globals = PyDict::new(py);
+            globals
+                .set_item("Divider", py.get_type::<Divider>())
+                .unwrap();
+            py.run(
+                c_str!(
+                    r#"
+from array import array
+
+for d in [1, 7, 8, 11, 2**63 + 1, 2**64 - 1]:
+    divider = Divider(d)
+    assert divider.divisor == d
+    assert repr(divider) == f"Divider({d})"
+    for n in [0, 1, d - 1, d, 234234131223, 2**64 - 1]:
+        assert divider.divide(n) == n // d
+        assert divider.divmod(n) == divmod(n, d)
+    values = array("Q", [0, 1, 100, 234234131223, 2**64 - 1])
+    expected = [n // d for n in values]
+    assert divider.divide_many(values) == expected
+    divider.divide_in_place(values)
+    assert list(values) == expected
+
+for invalid, error in [(0, ZeroDivisionError), (-1, OverflowError), (2**64, OverflowError)]:
+    try:
+        Divider(invalid)
+    except error:
+        pass
+    else:
+        raise AssertionError(invalid)
+
+try:
+    Divider(7).divide_in_place(memoryview(array("Q", [7])).toreadonly())
+except ValueError:
+    pass
+else:
+    raise AssertionError("read-only buffer")
+"#
+                ),
+                Some(&globals),
+                None,
+            )
+            .unwrap();
+        });
+    }
+}
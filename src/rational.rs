@@ -0,0 +1,212 @@
+//! Continued fractions and rational approximations.
+
+use crate::DivRemU64;
+
+/// Iterator over the terms of the continued fraction of `n / divisor`,
+/// returned by [`DivRemU64::continued_fraction`].
+#[derive(Debug, Clone)]
+pub struct ContinuedFraction {
+    num: u64,
+    den: u64,
+    divider: Option<DivRemU64>,
+}
+
+/// Returns `(n / d, n % d)`.
+///
+/// The partial quotients of continued fractions follow the Gauss-Kuzmin
+/// distribution, which makes them lower than 4 about two thirds of the time:
+/// subtracting `d` a few times is cheaper than dividing.
+#[inline]
+fn small_div_rem(n: u64, d: u64) -> (u64, u64) {
+    let mut r = n;
+    for q in 0..4 {
+        if r < d {
+            return (q, r);
+        }
+        r -= d;
+    }
+    (n / d, n % d)
+}
+
+impl Iterator for ContinuedFraction {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if self.den == 0 {
+            return None;
+        }
+        let (q, r) = match self.divider.take() {
+            Some(divider) => divider.div_rem(self.num),
+            None => small_div_rem(self.num, self.den),
+        };
+        self.num = self.den;
+        self.den = r;
+        Some(q)
+    }
+}
+
+impl core::iter::FusedIterator for ContinuedFraction {}
+
+impl DivRemU64 {
+    /// Returns the terms `[a0; a1, a2, ..]` of the continued fraction of
+    /// `n / divisor`, that is, the quotients of the Euclidean algorithm.
+    ///
+    /// The first term is computed with the divider. The next ones have
+    /// different divisors, and are mostly small.
+    ///
+    /// ```
+    /// use fastdivide::DivRemU64;
+    ///
+    /// let terms: Vec<u64> = DivRemU64::divide_by(93).continued_fraction(415).collect();
+    /// assert_eq!(terms, vec![4, 2, 6, 7]);
+    /// ```
+    pub fn continued_fraction(&self, n: u64) -> ContinuedFraction {
+        ContinuedFraction {
+            num: n,
+            den: self.divisor(),
+            divider: Some(*self),
+        }
+    }
+}
+
+/// Returns the fraction `p / q` closest to `num / den`, with `q <= max_den`,
+/// such as the ratio of two clocks.
+///
+/// The result is in lowest terms, and is `num / den` reduced if its reduced
+/// denominator fits. Ties go to the smallest denominator. This walks the
+/// convergents of the continued fraction of `num / den`, and checks the last
+/// semiconvergent.
+///
+/// Panics if `den` or `max_den` is 0.
+pub fn approximate_ratio(num: u64, den: u64, max_den: u64) -> (u64, u64) {
+    assert!(max_den > 0);
+    // The convergents before the current one, starting with 0 / 1 and 1 / 0.
+    let (mut p0, mut q0, mut p1, mut q1) = (0u128, 1u128, 1u128, 0u128);
+    for term in DivRemU64::divide_by(den).continued_fraction(num) {
+        let q2 = q0 + term as u128 * q1;
+        if q2 > max_den as u128 {
+            // The semiconvergent with the largest allowed denominator. q1 >= 1,
+            // as the first convergent, term / 1, always fits.
+            let k = (max_den as u128 - q0) / q1;
+            let (ps, qs) = (p0 + k * p1, q0 + k * q1);
+            // |p / q - num / den| = |p * den - q * num| / (q * den), and the
+            // numerators are lower than `den`, while the product of two u128s
+            // does not fit: the products wrap around, but not their difference.
+            let error = |p: u128, q: u128| {
+                let diff = (p * den as u128).wrapping_sub(q * num as u128);
+                diff.min(diff.wrapping_neg())
+            };
+            return if error(ps, qs) * q1 < error(p1, q1) * qs {
+                (ps as u64, qs as u64)
+            } else {
+                (p1 as u64, q1 as u64)
+            };
+        }
+        let p2 = p0 + term as u128 * p1;
+        (p0, q0, p1, q1) = (p1, q1, p2, q2);
+    }
+    (p1 as u64, q1 as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{approximate_ratio, small_div_rem};
+    use crate::DivRemU64;
+    use proptest::prelude::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_small_div_rem() {
+        for d in 1..20 {
+            for n in (0..100).chain([u64::MAX]) {
+                assert_eq!(small_div_rem(n, d), (n / d, n % d));
+            }
+        }
+    }
+
+    #[test]
+    fn test_continued_fraction() {
+        let terms = |n, d| -> Vec<u64> { DivRemU64::divide_by(d).continued_fraction(n).collect() };
+        assert_eq!(terms(0, 7), vec![0]);
+        assert_eq!(terms(7, 1), vec![7]);
+        assert_eq!(terms(3, 7), vec![0, 2, 3]);
+        assert_eq!(terms(u64::MAX, u64::MAX), vec![1]);
+        // The ratio of consecutive Fibonacci numbers only has 1s, except for the last term.
+        assert_eq!(terms(89, 55), vec![1, 1, 1, 1, 1, 1, 1, 1, 2]);
+        let mut iter = DivRemU64::divide_by(2).continued_fraction(1);
+        assert_eq!(iter.by_ref().count(), 2);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn test_approximate_ratio() {
+        // 355 / 113 approximates pi.
+        let pi = (3_141_592_653_589_793, 1_000_000_000_000_000);
+        assert_eq!(approximate_ratio(pi.0, pi.1, 1), (3, 1));
+        assert_eq!(approximate_ratio(pi.0, pi.1, 10), (22, 7));
+        assert_eq!(approximate_ratio(pi.0, pi.1, 200), (355, 113));
+        assert_eq!(approximate_ratio(90_000, 48_000, 100), (15, 8));
+        assert_eq!(approximate_ratio(0, 5, 3), (0, 1));
+        assert_eq!(approximate_ratio(u64::MAX, 1, 1), (u64::MAX, 1));
+        assert_eq!(approximate_ratio(1, u64::MAX, 1_000), (0, 1));
+        assert_eq!(
+            approximate_ratio(u64::MAX - 1, u64::MAX, u64::MAX),
+            (u64::MAX - 1, u64::MAX)
+        );
+        // 1/1 is as close to 3/4 as 1/2, and has a smaller denominator.
+        assert_eq!(approximate_ratio(3, 4, 2), (1, 1));
+    }
+
+    // The best fraction, by trying all of the denominators.
+    fn approximate_ratio_reference(num: u64, den: u64, max_den: u64) -> (u64, u64) {
+        let error = |p: u64, q: u64| (p as i128 * den as i128 - q as i128 * num as i128).abs();
+        let mut best = (0, 1);
+        for q in 1..=max_den {
+            let p = ((num as u128 * q as u128 + den as u128 / 2) / den as u128) as u64;
+            for p in [p.saturating_sub(1), p, p + 1] {
+                if error(p, q) * (best.1 as i128) < error(best.0, best.1) * q as i128 {
+                    best = (p, q);
+                }
+            }
+        }
+        best
+    }
+
+    #[test]
+    fn test_approximate_ratio_reference() {
+        for num in 0..40 {
+            for den in 1..40 {
+                for max_den in 1..20 {
+                    assert_eq!(
+                        approximate_ratio(num, den, max_den),
+                        approximate_ratio_reference(num, den, max_den),
+                        "{} / {} with at most {}",
+                        num,
+                        den,
+                        max_den
+                    );
+                }
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_proptest_continued_fraction(n in any::<u64>(), d in 1..u64::MAX) {
+            // Evaluates the continued fraction back, as the fraction p / q.
+            let terms: Vec<u64> = DivRemU64::divide_by(d).continued_fraction(n).collect();
+            let (mut p, mut q) = (1u128, 0u128);
+            for &term in terms.iter().rev() {
+                let (p_next, q_next) = (term as u128 * p + q, p);
+                p = p_next;
+                q = q_next;
+            }
+            assert_eq!(p * d as u128, q * n as u128);
+        }
+
+        #[test]
+        fn test_proptest_approximate_ratio(num in 0..u32::MAX as u64, den in 1..u32::MAX as u64, max_den in 1u64..300) {
+            assert_eq!(approximate_ratio(num, den, max_den), approximate_ratio_reference(num, den, max_den));
+        }
+    }
+}
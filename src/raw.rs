@@ -0,0 +1,237 @@
+//! Stable binary representation of the dividers.
+
+use crate::DividerU64;
+
+/// A `DividerU64` with a stable, platform independent layout,
+/// meant to be stored in files and memory-mapped.
+///
+/// It takes 16 bytes and has no implicit padding: all of its bit patterns
+/// are valid `RawDividerU64`s, which [`RawDividerU64::to_divider`] validates.
+/// The magic number is stored in little endian. The all-zeros value,
+/// which is the default, is the representation of `DividerU64::Identity`.
+///
+/// With the `bytemuck` feature, `RawDividerU64` implements `Pod` and
+/// `Zeroable`, so that tables of dividers can be cast from and to bytes
+/// with `bytemuck::cast_slice`. With the `zerocopy` feature, it implements
+/// `FromBytes`, `IntoBytes`, `KnownLayout` and `Immutable`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(
+    feature = "zerocopy",
+    derive(
+        zerocopy::FromBytes,
+        zerocopy::IntoBytes,
+        zerocopy::KnownLayout,
+        zerocopy::Immutable
+    )
+)]
+#[repr(C)]
+pub struct RawDividerU64 {
+    magic: u64,
+    kind: u8,
+    pre_shift: u8,
+    shift: u8,
+    padding: [u8; 5],
+}
+
+const _: () = assert!(core::mem::size_of::<RawDividerU64>() == 16);
+const _: () = assert!(core::mem::align_of::<RawDividerU64>() == 8);
+
+const IDENTITY: u8 = 0;
+const FAST: u8 = 1;
+const BIT_SHIFT: u8 = 2;
+const GENERAL: u8 = 3;
+const PRE_SHIFT: u8 = 4;
+const COMPARE: u8 = 5;
+
+impl RawDividerU64 {
+    fn new(kind: u8, magic: u64, pre_shift: u8, shift: u8) -> RawDividerU64 {
+        RawDividerU64 {
+            magic: magic.to_le(),
+            kind,
+            pre_shift,
+            shift,
+            padding: [0; 5],
+        }
+    }
+
+    /// Returns the divider, or `None` if the representation is not one
+    /// that `DividerU64::to_raw` returns.
+    ///
+    /// This checks that the magic numbers are those `DividerU64::divide_by`
+    /// computes, so that untrusted data cannot yield wrong quotients.
+    pub fn to_divider(&self) -> Option<DividerU64> {
+        if self.padding != [0; 5] {
+            return None;
+        }
+        let divider = self.decode()?;
+        if divider.to_raw() != *self || !divider.is_valid() {
+            return None;
+        }
+        Some(divider)
+    }
+
+    /// Returns `n / divisor`, without rebuilding the `DividerU64`.
+    ///
+    /// The representation is expected to be valid, as checked by
+    /// [`RawDividerU64::to_divider`] or by `rkyv::access`. Otherwise, the
+    /// quotient is unspecified and `divide` may panic.
+    #[inline]
+    pub fn divide(&self, n: u64) -> u64 {
+        self.decode().map_or(0, |divider| divider.divide(n))
+    }
+
+    #[inline(always)]
+    pub(crate) fn decode(&self) -> Option<DividerU64> {
+        let magic = u64::from_le(self.magic);
+        let (pre_shift, shift) = (self.pre_shift, self.shift);
+        let divider = match self.kind {
+            IDENTITY => DividerU64::Identity,
+            FAST => DividerU64::Fast { magic, shift },
+            BIT_SHIFT => DividerU64::BitShift(shift),
+            GENERAL => DividerU64::General {
+                magic_low: magic,
+                shift,
+            },
+            PRE_SHIFT => DividerU64::PreShift {
+                magic,
+                pre_shift,
+                shift,
+            },
+            COMPARE => DividerU64::Compare(magic),
+            _ => return None,
+        };
+        Some(divider)
+    }
+}
+
+impl DividerU64 {
+    /// Returns the stable binary representation of the divider.
+    pub fn to_raw(&self) -> RawDividerU64 {
+        match *self {
+            DividerU64::Identity => RawDividerU64::new(IDENTITY, 0, 0, 0),
+            DividerU64::Fast { magic, shift } => RawDividerU64::new(FAST, magic, 0, shift),
+            DividerU64::BitShift(shift) => RawDividerU64::new(BIT_SHIFT, 0, 0, shift),
+            DividerU64::General { magic_low, shift } => {
+                RawDividerU64::new(GENERAL, magic_low, 0, shift)
+            }
+            DividerU64::PreShift {
+                magic,
+                pre_shift,
+                shift,
+            } => RawDividerU64::new(PRE_SHIFT, magic, pre_shift, shift),
+            DividerU64::Compare(divisor) => RawDividerU64::new(COMPARE, divisor, 0, 0),
+        }
+    }
+}
+
+impl From<DividerU64> for RawDividerU64 {
+    fn from(divider: DividerU64) -> RawDividerU64 {
+        divider.to_raw()
+    }
+}
+
+// SAFETY: `RawDividerU64` is `#[repr(C)]` and only made of integers,
+// and the all-zeros value is valid.
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Zeroable for RawDividerU64 {}
+
+// SAFETY: `RawDividerU64` has no implicit padding, and any bit pattern
+// is a valid value, which `to_divider` may reject.
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Pod for RawDividerU64 {}
+
+#[cfg(test)]
+mod tests {
+    use super::RawDividerU64;
+    use crate::DividerU64;
+
+    #[test]
+    fn test_raw_round_trip() {
+        let divisors = (1u64..300).chain((0..64).map(|i| 1 << i)).chain([
+            14,
+            234234131223u64,
+            (1 << 63) + 1,
+            u64::MAX,
+        ]);
+        for d in divisors {
+            let divider = DividerU64::divide_by(d);
+            let raw = divider.to_raw();
+            assert_eq!(raw.to_divider(), Some(divider));
+            for n in [0u64, 1, d - 1, d, 234234131223u64, u64::MAX] {
+                assert_eq!(raw.divide(n), n / d);
+            }
+        }
+    }
+
+    #[test]
+    fn test_raw_zeroed() {
+        let zeroed: RawDividerU64 = unsafe { core::mem::zeroed() };
+        assert_eq!(zeroed, RawDividerU64::default());
+        assert_eq!(zeroed.to_divider(), Some(DividerU64::Identity));
+        assert_eq!(RawDividerU64::from(DividerU64::Identity), zeroed);
+    }
+
+    #[cfg(feature = "bytemuck")]
+    #[test]
+    fn test_raw_bytemuck() {
+        let raws = [7u64, 11, 1 << 40, u64::MAX].map(|d| DividerU64::divide_by(d).to_raw());
+        let bytes: &[u8] = bytemuck::cast_slice(&raws);
+        assert_eq!(bytes.len(), 64);
+        assert_eq!(bytes[..8], raws[0].magic.to_ne_bytes());
+        let cast: &[RawDividerU64] = bytemuck::cast_slice(bytes);
+        assert_eq!(cast, &raws[..]);
+        assert!(bytemuck::try_cast_slice::<u8, RawDividerU64>(&bytes[..24]).is_err());
+        assert_eq!(
+            <RawDividerU64 as bytemuck::Zeroable>::zeroed().to_divider(),
+            Some(DividerU64::Identity)
+        );
+    }
+
+    #[cfg(feature = "zerocopy")]
+    #[test]
+    fn test_raw_zerocopy() {
+        use zerocopy::{FromBytes, IntoBytes};
+
+        let raws = [7u64, 11, 1 << 40, u64::MAX].map(|d| DividerU64::divide_by(d).to_raw());
+        // An 8-byte header, followed by the dividers.
+        let mut words = [0u64; 9];
+        let frame = words.as_mut_bytes();
+        frame[..8].copy_from_slice(b"dividers");
+        frame[8..].copy_from_slice(raws.as_bytes());
+        let (parsed, rest) = <[RawDividerU64]>::ref_from_prefix_with_elems(&frame[8..], 4)
+            .map_err(|_| ())
+            .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(parsed, &raws[..]);
+        // References require aligned bytes, copies do not.
+        assert!(RawDividerU64::ref_from_bytes(&frame[1..17]).is_err());
+        let mut unaligned = [0u8; 17];
+        unaligned[1..].copy_from_slice(raws[1].as_bytes());
+        let copy = RawDividerU64::read_from_bytes(&unaligned[1..]).unwrap();
+        assert_eq!(copy.to_divider(), Some(DividerU64::divide_by(11)));
+        assert!(RawDividerU64::read_from_bytes(&unaligned[..15]).is_err());
+    }
+
+    #[test]
+    fn test_raw_invalid() {
+        let raw = DividerU64::divide_by(7).to_raw();
+        let tampered = RawDividerU64 { kind: 6, ..raw };
+        assert_eq!(tampered.to_divider(), None);
+        let tampered = RawDividerU64 {
+            magic: raw.magic ^ 1,
+            ..raw
+        };
+        assert_eq!(tampered.to_divider(), None);
+        let tampered = RawDividerU64 {
+            padding: [1, 0, 0, 0, 0],
+            ..raw
+        };
+        assert_eq!(tampered.to_divider(), None);
+        let tampered = RawDividerU64 {
+            pre_shift: 1,
+            ..raw
+        };
+        assert_eq!(tampered.to_divider(), None);
+    }
+}
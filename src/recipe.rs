@@ -0,0 +1,142 @@
+//! Description of the instruction sequence of a divider, for code generators.
+
+use crate::{libdivide_mullhi_u64, DividerU64};
+
+/// An operation of a [`Recipe`].
+///
+/// The operations work on `u64` values: the numerator `n`, an accumulator
+/// `acc` initialized to `n`, and `q`, the result of the last `MulHi`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecipeOp {
+    /// `acc = q = (acc * magic) >> 64`, with a 128-bit product.
+    MulHi(u64),
+    /// `acc = acc >> shift`.
+    Shr(u8),
+    /// `acc = n - q`.
+    Sub,
+    /// `acc = acc + q`.
+    Add,
+    /// `acc = (acc >= divisor) as u64`.
+    GreaterEq(u64),
+}
+
+/// The sequence of operations computing the quotient of a numerator by a divisor.
+///
+/// A JIT compiler can emit it inline, rather than calling `DividerU64::divide`.
+///
+/// ```
+/// use fastdivide::{DividerU64, RecipeOp};
+///
+/// let recipe = DividerU64::divide_by(7).recipe();
+/// assert_eq!(
+///     recipe.ops(),
+///     &[
+///         RecipeOp::MulHi(2635249153387078803),
+///         RecipeOp::Sub,
+///         RecipeOp::Shr(1),
+///         RecipeOp::Add,
+///         RecipeOp::Shr(2),
+///     ]
+/// );
+/// assert_eq!(recipe.eval(100), 14);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Recipe {
+    ops: [RecipeOp; 5],
+    len: usize,
+}
+
+impl Recipe {
+    fn new(recipe: &[RecipeOp]) -> Recipe {
+        let mut ops = [RecipeOp::Shr(0); 5];
+        ops[..recipe.len()].copy_from_slice(recipe);
+        Recipe {
+            ops,
+            len: recipe.len(),
+        }
+    }
+
+    /// Returns the operations, in the order they are executed.
+    /// The quotient is the final value of the accumulator.
+    pub fn ops(&self) -> &[RecipeOp] {
+        &self.ops[..self.len]
+    }
+
+    /// Executes the operations, returning `n / divisor`.
+    pub fn eval(&self, n: u64) -> u64 {
+        let (mut acc, mut q) = (n, 0);
+        for op in self.ops() {
+            acc = match *op {
+                RecipeOp::MulHi(magic) => {
+                    q = libdivide_mullhi_u64(acc, magic);
+                    q
+                }
+                RecipeOp::Shr(shift) => acc >> shift,
+                RecipeOp::Sub => n - q,
+                RecipeOp::Add => acc + q,
+                RecipeOp::GreaterEq(divisor) => (acc >= divisor) as u64,
+            };
+        }
+        acc
+    }
+}
+
+impl DividerU64 {
+    /// Returns the sequence of operations `divide` executes.
+    pub fn recipe(&self) -> Recipe {
+        match *self {
+            DividerU64::Identity => Recipe::new(&[]),
+            DividerU64::BitShift(shift) => Recipe::new(&[RecipeOp::Shr(shift)]),
+            DividerU64::Fast { magic, shift } => {
+                Recipe::new(&[RecipeOp::MulHi(magic), RecipeOp::Shr(shift)])
+            }
+            DividerU64::PreShift {
+                magic,
+                pre_shift,
+                shift,
+            } => Recipe::new(&[
+                RecipeOp::Shr(pre_shift),
+                RecipeOp::MulHi(magic),
+                RecipeOp::Shr(shift),
+            ]),
+            DividerU64::General { magic_low, shift } => Recipe::new(&[
+                RecipeOp::MulHi(magic_low),
+                RecipeOp::Sub,
+                RecipeOp::Shr(1),
+                RecipeOp::Add,
+                RecipeOp::Shr(shift),
+            ]),
+            DividerU64::Compare(divisor) => Recipe::new(&[RecipeOp::GreaterEq(divisor)]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::DividerU64;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_recipe() {
+        let divisors = (1u64..300).chain((0..64).map(|i| 1 << i)).chain([
+            14,
+            234234131223u64,
+            (1 << 63) + 1,
+            u64::MAX,
+        ]);
+        for d in divisors {
+            let recipe = DividerU64::divide_by(d).recipe();
+            for n in [0u64, 1, d - 1, d, 1 << 63, u64::MAX] {
+                assert_eq!(recipe.eval(n), n / d);
+            }
+        }
+        assert!(DividerU64::divide_by(1).recipe().ops().is_empty());
+    }
+
+    proptest! {
+        #[test]
+        fn test_recipe_proptest(n: u64, d in 1u64..) {
+            prop_assert_eq!(DividerU64::divide_by(d).recipe().eval(n), n / d);
+        }
+    }
+}
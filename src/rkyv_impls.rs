@@ -0,0 +1,118 @@
+//! rkyv `Archive`, `Serialize` and `Deserialize` implementations,
+//! behind the `rkyv` feature.
+//!
+//! A `DividerU64` is archived as its [`RawDividerU64`] representation, whose
+//! layout is the same on all platforms. `rkyv::access` validates the archived
+//! dividers, which can then divide straight from the memory-mapped bytes
+//! with [`RawDividerU64::divide`].
+//!
+//! `RawDividerU64` is 8-byte aligned, even with rkyv's `unaligned` feature.
+
+use core::fmt;
+
+use rkyv::bytecheck::CheckBytes;
+use rkyv::rancor::{Fallible, Source};
+use rkyv::traits::NoUndef;
+use rkyv::{Archive, Deserialize, Place, Portable, Serialize};
+
+use crate::{DividerU64, RawDividerU64};
+
+/// The error returned when an archived `RawDividerU64` is not
+/// one that `DividerU64::to_raw` returns.
+#[derive(Debug)]
+struct InvalidRawDivider;
+
+impl fmt::Display for InvalidRawDivider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid divider representation")
+    }
+}
+
+impl core::error::Error for InvalidRawDivider {}
+
+// SAFETY: `RawDividerU64` is `#[repr(C)]`, stores its magic number in
+// little endian, and has no interior mutability.
+unsafe impl Portable for RawDividerU64 {}
+
+// SAFETY: the padding of `RawDividerU64` is an explicit, zeroed field.
+unsafe impl NoUndef for RawDividerU64 {}
+
+// SAFETY: `check_bytes` only succeeds if `to_divider` accepts the divider.
+unsafe impl<C: Fallible + ?Sized> CheckBytes<C> for RawDividerU64
+where
+    C::Error: Source,
+{
+    unsafe fn check_bytes(value: *const Self, _context: &mut C) -> Result<(), C::Error> {
+        // SAFETY: the caller guarantees that `value` points to an aligned
+        // and initialized `RawDividerU64`, and all bit patterns are valid.
+        let raw = &*value;
+        match raw.to_divider() {
+            Some(_) => Ok(()),
+            None => Err(C::Error::new(InvalidRawDivider)),
+        }
+    }
+}
+
+impl Archive for DividerU64 {
+    type Archived = RawDividerU64;
+    type Resolver = ();
+
+    fn resolve(&self, _resolver: (), out: Place<RawDividerU64>) {
+        out.write(self.to_raw());
+    }
+}
+
+impl<S: Fallible + ?Sized> Serialize<S> for DividerU64 {
+    fn serialize(&self, _serializer: &mut S) -> Result<(), S::Error> {
+        Ok(())
+    }
+}
+
+impl<D: Fallible + ?Sized> Deserialize<DividerU64, D> for RawDividerU64
+where
+    D::Error: Source,
+{
+    fn deserialize(&self, _deserializer: &mut D) -> Result<DividerU64, D::Error> {
+        self.to_divider()
+            .ok_or_else(|| D::Error::new(InvalidRawDivider))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rkyv::rancor::Error;
+    use rkyv::vec::ArchivedVec;
+    use std::vec::Vec;
+
+    use crate::{DividerU64, RawDividerU64};
+
+    #[test]
+    fn test_rkyv_access() {
+        let divisors = [1u64, 7, 11, 14, 1 << 40, (1 << 63) + 1, u64::MAX];
+        let dividers: Vec<DividerU64> = divisors
+            .iter()
+            .copied()
+            .map(DividerU64::divide_by)
+            .collect();
+        let bytes = rkyv::to_bytes::<Error>(&dividers).unwrap();
+        let archived = rkyv::access::<ArchivedVec<RawDividerU64>, Error>(&bytes).unwrap();
+        for (raw, d) in archived.iter().zip(divisors) {
+            for n in [0u64, 1, d - 1, d, 234234131223u64, u64::MAX] {
+                assert_eq!(raw.divide(n), n / d);
+            }
+        }
+        let deserialized = rkyv::deserialize::<Vec<DividerU64>, Error>(archived).unwrap();
+        assert_eq!(deserialized, dividers);
+    }
+
+    #[test]
+    fn test_rkyv_rejects_invalid_dividers() {
+        let bytes = rkyv::to_bytes::<Error>(&DividerU64::divide_by(7)).unwrap();
+        assert!(rkyv::access::<RawDividerU64, Error>(&bytes).is_ok());
+        for i in 0..bytes.len() {
+            let mut tampered = bytes.clone();
+            tampered[i] ^= 1;
+            assert!(rkyv::access::<RawDividerU64, Error>(&tampered).is_err());
+        }
+    }
+}
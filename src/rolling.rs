@@ -0,0 +1,125 @@
+//! Rabin-Karp rolling hash.
+
+use crate::Mod64;
+
+/// Rabin-Karp hash of a sliding window, `sum(x_i * base^(len - 1 - i)) % modulus`
+/// for the values `x_0, .., x_{len - 1}` of the window.
+///
+/// The reductions go through a precomputed [`Mod64`], which also computes
+/// `base^window_len` once, at construction.
+///
+/// ```
+/// use fastdivide::RollingHash;
+///
+/// let text = b"abcabc";
+/// let mut rolling = RollingHash::new(256, 1_000_000_007, 3);
+/// for &byte in &text[..3] {
+///     rolling.append(byte as u64);
+/// }
+/// let abc = rolling.value();
+/// for i in 3..6 {
+///     rolling.roll(text[i - 3] as u64, text[i] as u64);
+/// }
+/// assert_eq!(rolling.value(), abc);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RollingHash {
+    modulus: Mod64,
+    base: u64,
+    // base^window_len % modulus.
+    base_pow: u64,
+    value: u64,
+}
+
+impl RollingHash {
+    /// Returns the hash of an empty window, which will hold `window_len` values.
+    ///
+    /// Panics if `modulus` is 0.
+    pub fn new(base: u64, modulus: u64, window_len: u64) -> RollingHash {
+        let modulus = Mod64::new(modulus);
+        let base = modulus.reduce(base);
+        RollingHash {
+            modulus,
+            base,
+            base_pow: modulus.mod_pow(base, window_len),
+            value: 0,
+        }
+    }
+
+    /// Returns the hash of the window.
+    #[inline(always)]
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
+    /// Appends `x` to the back of the window.
+    #[inline]
+    pub fn append(&mut self, x: u64) {
+        let shifted = self.modulus.mul_mod(self.value, self.base);
+        self.value = self.modulus.add_mod(shifted, self.modulus.reduce(x));
+    }
+
+    /// Removes `x` from the front of a window holding `window_len + 1` values,
+    /// that is, after `append` made it exceed its length.
+    #[inline]
+    pub fn remove_front(&mut self, x: u64) {
+        let front = self.modulus.mul_mod(x, self.base_pow);
+        self.value = self.modulus.sub_mod(self.value, front);
+    }
+
+    /// Slides a full window by one value: `x_in` is appended, and `x_out`
+    /// removed from the front.
+    #[inline]
+    pub fn roll(&mut self, x_out: u64, x_in: u64) {
+        self.append(x_in);
+        self.remove_front(x_out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RollingHash;
+    use std::vec::Vec;
+
+    fn hash_reference(values: &[u64], base: u64, modulus: u64) -> u64 {
+        values.iter().fold(0, |hash, &x| {
+            ((hash as u128 * base as u128 + x as u128) % modulus as u128) as u64
+        })
+    }
+
+    #[test]
+    fn test_rolling_hash() {
+        let values: Vec<u64> = (0..200u64)
+            .map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+            .collect();
+        for modulus in [2, 1_000_000_007, (1 << 61) - 1, u64::MAX] {
+            for window_len in [1, 5, 64] {
+                let base = 0x1_0000_0001;
+                let mut rolling = RollingHash::new(base, modulus, window_len);
+                let window_len = window_len as usize;
+                for (i, &x) in values.iter().enumerate() {
+                    if i < window_len {
+                        rolling.append(x);
+                    } else {
+                        rolling.roll(values[i - window_len], x);
+                    }
+                    let start = (i + 1).saturating_sub(window_len);
+                    let expected = hash_reference(&values[start..=i], base % modulus, modulus);
+                    assert_eq!(rolling.value(), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_rolling_hash_remove_front() {
+        let mut rolling = RollingHash::new(10, 1_000, 2);
+        rolling.append(1);
+        rolling.append(2);
+        assert_eq!(rolling.value(), 12);
+        rolling.append(3);
+        assert_eq!(rolling.value(), 123);
+        rolling.remove_front(1);
+        assert_eq!(rolling.value(), 23);
+    }
+}
@@ -0,0 +1,323 @@
+//! RISC-V vector (RVV) implementation of the slice kernels.
+//!
+//! RVV intrinsics are not available in Rust yet, so the loops are written
+//! with `asm!`. RVV has a native 64-bit high multiplication (`vmulhu`), and
+//! the loops are vector-length agnostic.
+//!
+//! The `v` target feature is not stable, so the loops enable the V extension
+//! for the assembler only, with `.option arch, +v`. They are only called once
+//! `is_available` detected it.
+//!
+//! Only the divisions actually requiring a multiplication have an RVV loop.
+//! The other ones are plain shifts or comparisons, that the compiler
+//! vectorizes on its own.
+
+use core::arch::asm;
+
+use crate::DividerU64;
+
+/// Returns true if the RVV kernels can be used on the current CPU.
+///
+/// On Linux, this is detected at runtime with the `riscv_hwprobe` syscall.
+/// Elsewhere, this is only known at compile time.
+#[inline]
+pub(crate) fn is_available() -> bool {
+    cfg!(target_feature = "v") || hwprobe_v()
+}
+
+#[cfg(target_os = "linux")]
+fn hwprobe_v() -> bool {
+    use core::sync::atomic::{AtomicU8, Ordering};
+
+    const SYS_RISCV_HWPROBE: usize = 258;
+    const RISCV_HWPROBE_KEY_IMA_EXT_0: i64 = 4;
+    const RISCV_HWPROBE_IMA_V: u64 = 1 << 2;
+
+    const UNKNOWN: u8 = 0;
+    const AVAILABLE: u8 = 1;
+    const UNAVAILABLE: u8 = 2;
+    static DETECTED: AtomicU8 = AtomicU8::new(UNKNOWN);
+
+    match DETECTED.load(Ordering::Relaxed) {
+        AVAILABLE => return true,
+        UNAVAILABLE => return false,
+        _ => {}
+    }
+    #[repr(C)]
+    struct Pair {
+        key: i64,
+        value: u64,
+    }
+    let mut pair = Pair {
+        key: RISCV_HWPROBE_KEY_IMA_EXT_0,
+        value: 0,
+    };
+    let ret: isize;
+    // riscv_hwprobe(pairs, pair_count, cpusetsize, cpus, flags).
+    // A `cpus` null pointer queries the features common to all CPUs.
+    unsafe {
+        asm!(
+            "ecall",
+            in("a7") SYS_RISCV_HWPROBE,
+            inlateout("a0") &mut pair as *mut Pair as isize => ret,
+            in("a1") 1usize,
+            in("a2") 0usize,
+            in("a3") 0usize,
+            in("a4") 0usize,
+            options(nostack),
+        );
+    }
+    // Kernels older than 6.4 do not have the syscall, and set the key to -1
+    // if they do not know it.
+    let available = ret == 0
+        && pair.key == RISCV_HWPROBE_KEY_IMA_EXT_0
+        && pair.value & RISCV_HWPROBE_IMA_V != 0;
+    DETECTED.store(
+        if available { AVAILABLE } else { UNAVAILABLE },
+        Ordering::Relaxed,
+    );
+    available
+}
+
+#[cfg(not(target_os = "linux"))]
+fn hwprobe_v() -> bool {
+    false
+}
+
+/// Defines a function running an RVV loop over `len` values of `src`,
+/// writing the results to `dst`.
+///
+/// Within `$body`, the values are loaded in `v0`. The result is expected in
+/// `v2`. The `magic`, `pre_shift`, `shift` and `divisor` arguments are
+/// available as scalar registers.
+macro_rules! rvv_loop {
+    ($name:ident, $($body:literal),*) => {
+        /// # Safety
+        ///
+        /// The CPU must support the V extension, `src` and `dst` must be valid
+        /// for `len` values.
+        unsafe fn $name(
+            src: *const u64,
+            dst: *mut u64,
+            len: usize,
+            magic: u64,
+            pre_shift: u8,
+            shift: u8,
+            divisor: u64,
+        ) {
+            asm!(
+                // Not all of the loops use all of the arguments.
+                "/* {magic} {pre_shift} {shift} {divisor} */",
+                ".option push",
+                ".option arch, +v",
+                "beqz {len}, 2f",
+                "1:",
+                "vsetvli {vl}, {len}, e64, m1, ta, ma",
+                "vle64.v v0, ({src})",
+                $($body,)*
+                "vse64.v v2, ({dst})",
+                "sub {len}, {len}, {vl}",
+                "slli {vl}, {vl}, 3",
+                "add {src}, {src}, {vl}",
+                "add {dst}, {dst}, {vl}",
+                "bnez {len}, 1b",
+                "2:",
+                ".option pop",
+                src = inout(reg) src => _,
+                dst = inout(reg) dst => _,
+                len = inout(reg) len => _,
+                magic = in(reg) magic,
+                pre_shift = in(reg) pre_shift as u64,
+                shift = in(reg) shift as u64,
+                divisor = in(reg) divisor,
+                vl = out(reg) _,
+                out("v0") _, out("v1") _, out("v2") _,
+                options(nostack),
+            );
+        }
+    };
+}
+
+// q = mulhi(magic, n >> pre_shift) >> shift
+rvv_loop!(
+    divide_fast,
+    "vsrl.vx v1, v0, {pre_shift}",
+    "vmulhu.vx v1, v1, {magic}",
+    "vsrl.vx v2, v1, {shift}"
+);
+
+// q = (((n - t) >> 1) + t) >> shift, with t = mulhi(magic, n)
+rvv_loop!(
+    divide_general,
+    "vmulhu.vx v1, v0, {magic}",
+    "vsub.vv v2, v0, v1",
+    "vsrl.vi v2, v2, 1",
+    "vadd.vv v2, v2, v1",
+    "vsrl.vx v2, v2, {shift}"
+);
+
+// n - q * divisor, with q computed as in `divide_fast`.
+rvv_loop!(
+    mod_fast,
+    "vsrl.vx v1, v0, {pre_shift}",
+    "vmulhu.vx v1, v1, {magic}",
+    "vsrl.vx v2, v1, {shift}",
+    "vmul.vx v2, v2, {divisor}",
+    "vsub.vv v2, v0, v2"
+);
+
+// n - q * divisor, with q computed as in `divide_general`.
+rvv_loop!(
+    mod_general,
+    "vmulhu.vx v1, v0, {magic}",
+    "vsub.vv v2, v0, v1",
+    "vsrl.vi v2, v2, 1",
+    "vadd.vv v2, v2, v1",
+    "vsrl.vx v2, v2, {shift}",
+    "vmul.vx v2, v2, {divisor}",
+    "vsub.vv v2, v0, v2"
+);
+
+/// The RVV loops dividing and computing a remainder for `divider`,
+/// with their `magic`, `pre_shift` and `shift` arguments.
+type Loops = (
+    unsafe fn(*const u64, *mut u64, usize, u64, u8, u8, u64),
+    unsafe fn(*const u64, *mut u64, usize, u64, u8, u8, u64),
+    u64,
+    u8,
+    u8,
+);
+
+fn loops(divider: DividerU64) -> Option<Loops> {
+    match divider {
+        DividerU64::Fast { magic, shift } => Some((divide_fast, mod_fast, magic, 0, shift)),
+        DividerU64::PreShift {
+            magic,
+            pre_shift,
+            shift,
+        } => Some((divide_fast, mod_fast, magic, pre_shift, shift)),
+        DividerU64::General { magic_low, shift } => {
+            Some((divide_general, mod_general, magic_low, 0, shift))
+        }
+        DividerU64::Identity | DividerU64::BitShift(_) | DividerU64::Compare(_) => None,
+    }
+}
+
+/// # Safety
+///
+/// The CPU must support the V extension.
+pub(crate) unsafe fn divide_slice(divider: DividerU64, src: &[u64], dst: &mut [u64]) {
+    assert_eq!(src.len(), dst.len());
+    match loops(divider) {
+        Some((divide, _, magic, pre_shift, shift)) => divide(
+            src.as_ptr(),
+            dst.as_mut_ptr(),
+            src.len(),
+            magic,
+            pre_shift,
+            shift,
+            0,
+        ),
+        None => {
+            for (quotient, &n) in dst.iter_mut().zip(src) {
+                *quotient = divider.divide(n);
+            }
+        }
+    }
+}
+
+/// # Safety
+///
+/// The CPU must support the V extension.
+pub(crate) unsafe fn divide_slice_in_place(divider: DividerU64, values: &mut [u64]) {
+    match loops(divider) {
+        Some((divide, _, magic, pre_shift, shift)) => {
+            let ptr = values.as_mut_ptr();
+            divide(ptr, ptr, values.len(), magic, pre_shift, shift, 0)
+        }
+        None => {
+            for n in values.iter_mut() {
+                *n = divider.divide(*n);
+            }
+        }
+    }
+}
+
+/// # Safety
+///
+/// The CPU must support the V extension.
+pub(crate) unsafe fn mod_slice(divider: DividerU64, divisor: u64, src: &[u64], dst: &mut [u64]) {
+    assert_eq!(src.len(), dst.len());
+    match loops(divider) {
+        Some((_, remainder, magic, pre_shift, shift)) => remainder(
+            src.as_ptr(),
+            dst.as_mut_ptr(),
+            src.len(),
+            magic,
+            pre_shift,
+            shift,
+            divisor,
+        ),
+        None => {
+            for (remainder, &n) in dst.iter_mut().zip(src) {
+                *remainder = n - divider.divide(n) * divisor;
+            }
+        }
+    }
+}
+
+/// # Safety
+///
+/// The CPU must support the V extension.
+pub(crate) unsafe fn mod_slice_in_place(divider: DividerU64, divisor: u64, values: &mut [u64]) {
+    match loops(divider) {
+        Some((_, remainder, magic, pre_shift, shift)) => {
+            let ptr = values.as_mut_ptr();
+            remainder(ptr, ptr, values.len(), magic, pre_shift, shift, divisor)
+        }
+        None => {
+            for n in values.iter_mut() {
+                *n -= divider.divide(*n) * divisor;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_rvv_kernels() {
+        if !is_available() {
+            return;
+        }
+        let src: Vec<u64> = (0u64..1_000)
+            .chain([
+                u64::MAX - 1,
+                u64::MAX,
+                1 << 63,
+                (1 << 63) - 1,
+                234234131223u64,
+            ])
+            .collect();
+        let divisors = (1u64..300).chain((0..64).map(|i| 1 << i)).chain([
+            14,
+            234234131223u64,
+            (1 << 63) + 1,
+            u64::MAX,
+        ]);
+        for d in divisors {
+            let divider = DividerU64::divide_by(d);
+            let mut quotients = vec![0u64; src.len()];
+            unsafe { divide_slice(divider, &src, &mut quotients) };
+            let expected: Vec<u64> = src.iter().map(|n| n / d).collect();
+            assert_eq!(quotients, expected);
+            let mut remainders = src.clone();
+            unsafe { mod_slice_in_place(divider, d, &mut remainders) };
+            let expected: Vec<u64> = src.iter().map(|n| n % d).collect();
+            assert_eq!(remainders, expected);
+        }
+    }
+}
@@ -0,0 +1,98 @@
+//! Generation of dividers from random or fuzzer-provided data.
+
+#[cfg(feature = "arbitrary")]
+use crate::DivRemU64;
+use crate::DividerU64;
+
+impl DividerU64 {
+    /// Maps any `u64` onto a divider.
+    ///
+    /// Fuzzers and random generators rarely produce divisors such as 1,
+    /// powers of 2, or small divisors, when picking them uniformly.
+    /// `from_seed` reaches all of the kinds of dividers with similar odds.
+    /// It is the mapping used by the `arbitrary::Arbitrary` impl, behind the
+    /// `arbitrary` feature.
+    pub fn from_seed(seed: u64) -> DividerU64 {
+        let divisor = match seed >> 61 {
+            0 => 1,
+            1 => 1 << (seed % 64),
+            2 => seed % 1024 + 1,
+            3 => seed | 1 << 63,
+            4 => ((seed as u32 as u64) | 1) << ((seed >> 32) % 32),
+            // `seed >= 5 << 61`
+            _ => seed,
+        };
+        DividerU64::divide_by(divisor)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for DividerU64 {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary().map(DividerU64::from_seed)
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        <u64 as arbitrary::Arbitrary>::size_hint(depth)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for DivRemU64 {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let divider: DividerU64 = u.arbitrary()?;
+        Ok(DivRemU64::divide_by(divider.divisor()))
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        <u64 as arbitrary::Arbitrary>::size_hint(depth)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::DividerU64;
+    use core::mem::discriminant;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_from_seed() {
+        let mut kinds = Vec::new();
+        let mut seed = 0x9E37_79B9_7F4A_7C15u64;
+        for _ in 0..1_000 {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            let divider = DividerU64::from_seed(seed);
+            assert!(divider.is_valid());
+            if !kinds.contains(&discriminant(&divider)) {
+                kinds.push(discriminant(&divider));
+            }
+        }
+        assert_eq!(kinds.len(), 6);
+        assert_eq!(DividerU64::from_seed(0), DividerU64::Identity);
+        assert_eq!(DividerU64::from_seed(u64::MAX).divisor(), u64::MAX);
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn test_arbitrary() {
+        use crate::DivRemU64;
+        use arbitrary::{Arbitrary, Unstructured};
+
+        let bytes: Vec<u8> = (0..4_000u32)
+            .map(|i| (i.wrapping_mul(2654435761) >> 13) as u8)
+            .collect();
+        let mut u = Unstructured::new(&bytes);
+        while !u.is_empty() {
+            let divider = DividerU64::arbitrary(&mut u).unwrap();
+            assert!(divider.is_valid());
+            let divrem = DivRemU64::arbitrary(&mut u).unwrap();
+            assert_eq!(
+                divrem.div_rem(u64::MAX),
+                (u64::MAX / divrem.divisor(), u64::MAX % divrem.divisor())
+            );
+        }
+        // Running out of data still yields a valid divider.
+        let divider = DividerU64::arbitrary(&mut Unstructured::new(&[])).unwrap();
+        assert!(divider.is_valid());
+    }
+}
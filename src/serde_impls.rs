@@ -0,0 +1,80 @@
+//! `Serialize` and `Deserialize` implementations, behind the `serde` feature.
+//!
+//! The dividers are serialized as their divisor, and rebuilt with `divide_by`
+//! on deserialization. The magic numbers never go through the serializer:
+//! a payload cannot produce a divider returning wrong quotients, and the
+//! format does not change when the algorithm does.
+
+use serde::de::{Error, Unexpected};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{DivRemU64, DividerU64};
+
+fn deserialize_divisor<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    let divisor = u64::deserialize(deserializer)?;
+    if divisor == 0 {
+        return Err(D::Error::invalid_value(
+            Unexpected::Unsigned(0),
+            &"a non-zero divisor",
+        ));
+    }
+    Ok(divisor)
+}
+
+impl Serialize for DividerU64 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.divisor())
+    }
+}
+
+impl<'de> Deserialize<'de> for DividerU64 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_divisor(deserializer).map(DividerU64::divide_by)
+    }
+}
+
+impl Serialize for DivRemU64 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.divisor())
+    }
+}
+
+impl<'de> Deserialize<'de> for DivRemU64 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_divisor(deserializer).map(DivRemU64::divide_by)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_test::{assert_de_tokens_error, assert_tokens, Token};
+
+    use crate::{DivRemU64, DividerU64};
+
+    const DIVISORS: [u64; 6] = [1, 7, 11, 1 << 40, (1 << 63) + 1, u64::MAX];
+
+    #[test]
+    fn test_serde_divider_u64() {
+        for d in DIVISORS {
+            assert_tokens(&DividerU64::divide_by(d), &[Token::U64(d)]);
+        }
+    }
+
+    #[test]
+    fn test_serde_div_rem_u64() {
+        for d in DIVISORS {
+            assert_tokens(&DivRemU64::divide_by(d), &[Token::U64(d)]);
+        }
+    }
+
+    #[test]
+    fn test_serde_rejects_invalid_divisors() {
+        let error = "invalid value: integer `0`, expected a non-zero divisor";
+        assert_de_tokens_error::<DividerU64>(&[Token::U64(0)], error);
+        assert_de_tokens_error::<DivRemU64>(&[Token::U64(0)], error);
+        assert_de_tokens_error::<DividerU64>(
+            &[Token::Str("Fast")],
+            "invalid type: string \"Fast\", expected u64",
+        );
+    }
+}
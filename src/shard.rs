@@ -0,0 +1,215 @@
+//! Assignment of hashes to buckets and shards.
+
+use crate::DivRemU64;
+
+impl DivRemU64 {
+    /// Returns the bucket of `hash` in a table of `divisor` buckets, as `hash % divisor`.
+    ///
+    /// This is the drop-in replacement for `%` in hash tables whose capacity
+    /// is not a power of two. The divisor is expected to fit in a `usize`.
+    #[inline(always)]
+    pub fn bucket_of(&self, hash: u64) -> usize {
+        self.remainder(hash) as usize
+    }
+}
+
+/// Assigns hashes to a fixed number of shards, with `hash % num_shards`.
+///
+/// Keeping the shard count and its divider together avoids dividing by a
+/// stale divider after the count changes: changing it requires a new `Sharder`.
+///
+/// ```
+/// use fastdivide::Sharder;
+///
+/// let sharder = Sharder::new(12);
+/// assert_eq!(sharder.shard_for_key(100), 4);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sharder {
+    divider: DivRemU64,
+}
+
+impl Sharder {
+    /// Precomputes the divider for `num_shards`.
+    ///
+    /// Panics if `num_shards` is 0.
+    pub fn new(num_shards: usize) -> Sharder {
+        Sharder {
+            divider: DivRemU64::divide_by(num_shards as u64),
+        }
+    }
+
+    /// Returns the number of shards.
+    #[inline(always)]
+    pub fn num_shards(&self) -> usize {
+        self.divider.divisor() as usize
+    }
+
+    /// Returns the shard of the key whose hash is `hash`, in `0..num_shards`.
+    #[inline(always)]
+    pub fn shard_for_key(&self, hash: u64) -> usize {
+        self.divider.bucket_of(hash)
+    }
+}
+
+/// Returns the bucket of `key` in `0..num_buckets`, with the jump consistent
+/// hash of Lamping and Veach.
+///
+/// Unlike `key % num_buckets`, growing the number of buckets from `n` to `n + 1`
+/// only moves `1 / (n + 1)` of the keys, all of them to the new bucket.
+/// The results are those of the reference implementation, which computes
+/// with `f64`s. Its divisions are by pseudo-random numbers, which no divider
+/// can be precomputed for.
+///
+/// Panics if `num_buckets` is 0.
+pub fn jump_consistent_hash(key: u64, num_buckets: u64) -> u64 {
+    JumpHash::new(num_buckets).bucket_of(key)
+}
+
+/// The jump consistent hash of [`jump_consistent_hash`], for a fixed number of buckets.
+///
+/// The number of buckets is converted to an `f64` once, at construction, and
+/// the jumps are compared to it without converting them back to integers.
+///
+/// ```
+/// use fastdivide::JumpHash;
+///
+/// let jump_hash = JumpHash::new(10);
+/// assert!(jump_hash.bucket_of(0x9E37_79B9_7F4A_7C15) < 10);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct JumpHash {
+    num_buckets: u64,
+    // The smallest f64 greater than or equal to `num_buckets`.
+    limit: f64,
+}
+
+const TWO_POW_31: f64 = (1u64 << 31) as f64;
+
+impl JumpHash {
+    /// Panics if `num_buckets` is 0.
+    pub fn new(num_buckets: u64) -> JumpHash {
+        assert!(num_buckets > 0);
+        let mut limit = num_buckets as f64;
+        // Above 2^53, the conversion may round down. `as u64` saturates,
+        // so this does not overflow for `limit = 2^64`.
+        if (limit as u64) < num_buckets {
+            limit = f64::from_bits(limit.to_bits() + 1);
+        }
+        JumpHash { num_buckets, limit }
+    }
+
+    /// Returns the number of buckets.
+    #[inline(always)]
+    pub fn num_buckets(&self) -> u64 {
+        self.num_buckets
+    }
+
+    /// Returns the bucket of `key`, in `0..num_buckets`.
+    pub fn bucket_of(&self, mut key: u64) -> u64 {
+        let mut bucket = 0u64;
+        loop {
+            key = key.wrapping_mul(2_862_933_555_777_941_757).wrapping_add(1);
+            let jump = (bucket + 1) as f64 * (TWO_POW_31 / ((key >> 33) + 1) as f64);
+            // `jump as u64 >= num_buckets`, the jumps being non-negative.
+            if jump >= self.limit {
+                return bucket;
+            }
+            bucket = jump as u64;
+        }
+    }
+}
+
+impl PartialEq for JumpHash {
+    fn eq(&self, other: &JumpHash) -> bool {
+        self.num_buckets == other.num_buckets
+    }
+}
+
+impl Eq for JumpHash {}
+
+#[cfg(test)]
+mod tests {
+    use super::{jump_consistent_hash, JumpHash, Sharder};
+    use crate::DivRemU64;
+
+    #[test]
+    fn test_bucket_of() {
+        for d in [1u64, 2, 7, 12, 1000, (1 << 31) - 1] {
+            let divider = DivRemU64::divide_by(d);
+            for hash in (0..100).chain([u64::MAX, 0x9E37_79B9_7F4A_7C15]) {
+                assert_eq!(divider.bucket_of(hash), (hash % d) as usize);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sharder() {
+        let sharder = Sharder::new(12);
+        assert_eq!(sharder.num_shards(), 12);
+        assert_eq!(sharder.shard_for_key(11), 11);
+        assert_eq!(sharder.shard_for_key(u64::MAX), (u64::MAX % 12) as usize);
+        assert_eq!(Sharder::new(1).shard_for_key(123), 0);
+    }
+
+    #[test]
+    fn test_jump_consistent_hash() {
+        for key in (0..1_000u64).map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15)) {
+            assert_eq!(jump_consistent_hash(key, 1), 0);
+            let mut previous = 0;
+            for num_buckets in 2..100 {
+                let bucket = jump_consistent_hash(key, num_buckets);
+                // The keys either stay, or move to the new bucket.
+                assert!(bucket == previous || bucket == num_buckets - 1);
+                previous = bucket;
+            }
+        }
+        let jump_hash = JumpHash::new(10);
+        assert_eq!(jump_hash.num_buckets(), 10);
+        let mut counts = [0u32; 10];
+        for key in 0..10_000u64 {
+            counts[jump_hash.bucket_of(key) as usize] += 1;
+        }
+        assert!(counts.iter().all(|&count| (800..1_200).contains(&count)));
+        assert!(jump_consistent_hash(u64::MAX, u64::MAX) < u64::MAX);
+    }
+
+    // The reference implementation, converting the jumps back to integers.
+    fn jump_consistent_hash_reference(mut key: u64, num_buckets: u64) -> u64 {
+        let (mut b, mut j) = (0u64, 0u64);
+        while j < num_buckets {
+            b = j;
+            key = key.wrapping_mul(2_862_933_555_777_941_757).wrapping_add(1);
+            j = ((b + 1) as f64 * ((1u64 << 31) as f64 / ((key >> 33) + 1) as f64)) as u64;
+        }
+        b
+    }
+
+    #[test]
+    fn test_jump_consistent_hash_reference() {
+        let large = [(1 << 53) + 1, (1 << 60) + 1, u64::MAX - 1, u64::MAX];
+        for num_buckets in (1..200).chain(large) {
+            let jump_hash = JumpHash::new(num_buckets);
+            for key in (0..200u64).map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15)) {
+                assert_eq!(
+                    jump_hash.bucket_of(key),
+                    jump_consistent_hash_reference(key, num_buckets)
+                );
+            }
+        }
+        assert_eq!(JumpHash::new((1 << 60) + 1), JumpHash::new((1 << 60) + 1));
+        assert_ne!(JumpHash::new((1 << 60) + 1), JumpHash::new(1 << 60));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_jump_consistent_hash_zero() {
+        jump_consistent_hash(1, 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sharder_zero() {
+        Sharder::new(0);
+    }
+}
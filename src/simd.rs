@@ -0,0 +1,183 @@
+//! `core::simd` implementation of the slice kernels.
+//!
+//! There is no vector high multiplication for 64-bit lanes, so it is emulated
+//! with four 32x32 bits multiplications.
+
+use core::simd::cmp::SimdPartialOrd;
+use core::simd::{Select, Simd};
+
+use crate::DividerU64;
+
+const LANES: usize = 8;
+
+const LOW_MASK: u64 = u32::MAX as u64;
+
+type U64s = Simd<u64, LANES>;
+
+/// A 64-bits scalar factor, split into its 32-bits halves
+/// once and for all.
+#[derive(Clone, Copy)]
+struct SplitFactor {
+    lo: U64s,
+    hi: U64s,
+}
+
+impl SplitFactor {
+    #[inline(always)]
+    fn new(factor: u64) -> SplitFactor {
+        SplitFactor {
+            lo: U64s::splat(factor & LOW_MASK),
+            hi: U64s::splat(factor >> 32),
+        }
+    }
+
+    /// Returns the high 64 bits of the product of each lane with the factor.
+    #[inline(always)]
+    fn mullhi(self, n: U64s) -> U64s {
+        let mask = U64s::splat(LOW_MASK);
+        let n_lo = n & mask;
+        let n_hi = n >> 32;
+        let lo_lo = self.lo * n_lo;
+        let hi_lo = self.hi * n_lo;
+        let lo_hi = self.lo * n_hi;
+        let hi_hi = self.hi * n_hi;
+        // None of these additions can overflow.
+        let cross = (lo_lo >> 32) + (hi_lo & mask) + lo_hi;
+        hi_hi + (hi_lo >> 32) + (cross >> 32)
+    }
+}
+
+/// Runs `$body` with `$divide` bound to a closure dividing `LANES` values
+/// by `$divider`, specialized for the algorithm of the divider.
+macro_rules! with_simd_divide_fn {
+    ($divider:expr, $divide:ident => $body:expr) => {
+        match $divider {
+            DividerU64::Fast { magic, shift } => {
+                let magic = SplitFactor::new(magic);
+                let shift = U64s::splat(shift as u64);
+                let $divide = move |n: U64s| magic.mullhi(n) >> shift;
+                $body
+            }
+            DividerU64::BitShift(shift) => {
+                let shift = U64s::splat(shift as u64);
+                let $divide = move |n: U64s| n >> shift;
+                $body
+            }
+            DividerU64::Identity => {
+                let $divide = |n: U64s| n;
+                $body
+            }
+            DividerU64::Compare(divisor) => {
+                let divisor = U64s::splat(divisor);
+                let $divide =
+                    move |n: U64s| n.simd_ge(divisor).select(U64s::splat(1), U64s::splat(0));
+                $body
+            }
+            DividerU64::PreShift {
+                magic,
+                pre_shift,
+                shift,
+            } => {
+                let magic = SplitFactor::new(magic);
+                let pre_shift = U64s::splat(pre_shift as u64);
+                let shift = U64s::splat(shift as u64);
+                let $divide = move |n: U64s| magic.mullhi(n >> pre_shift) >> shift;
+                $body
+            }
+            DividerU64::General { magic_low, shift } => {
+                let magic_low = SplitFactor::new(magic_low);
+                let shift = U64s::splat(shift as u64);
+                let $divide = move |n: U64s| {
+                    let q = magic_low.mullhi(n);
+                    (((n - q) >> 1) + q) >> shift
+                };
+                $body
+            }
+        }
+    };
+}
+
+#[inline(always)]
+fn map_slice(
+    f: impl Fn(U64s) -> U64s,
+    f_scalar: impl Fn(u64) -> u64,
+    src: &[u64],
+    dst: &mut [u64],
+) {
+    assert_eq!(src.len(), dst.len());
+    let mut src_chunks = src.chunks_exact(LANES);
+    let mut dst_chunks = dst.chunks_exact_mut(LANES);
+    for (src_chunk, dst_chunk) in (&mut src_chunks).zip(&mut dst_chunks) {
+        f(U64s::from_slice(src_chunk)).copy_to_slice(dst_chunk);
+    }
+    let src_tail = src_chunks.remainder();
+    for (dst_val, &src_val) in dst_chunks.into_remainder().iter_mut().zip(src_tail) {
+        *dst_val = f_scalar(src_val);
+    }
+}
+
+#[inline(always)]
+fn map_slice_in_place(f: impl Fn(U64s) -> U64s, f_scalar: impl Fn(u64) -> u64, values: &mut [u64]) {
+    let mut chunks = values.chunks_exact_mut(LANES);
+    for chunk in &mut chunks {
+        f(U64s::from_slice(chunk)).copy_to_slice(chunk);
+    }
+    for val in chunks.into_remainder() {
+        *val = f_scalar(*val);
+    }
+}
+
+pub(crate) fn divide_slice(divider: DividerU64, src: &[u64], dst: &mut [u64]) {
+    with_simd_divide_fn!(divider, divide => map_slice(divide, |n| divider.divide(n), src, dst))
+}
+
+pub(crate) fn divide_slice_in_place(divider: DividerU64, values: &mut [u64]) {
+    with_simd_divide_fn!(divider, divide => map_slice_in_place(divide, |n| divider.divide(n), values))
+}
+
+pub(crate) fn mod_slice(divider: DividerU64, divisor: u64, src: &[u64], dst: &mut [u64]) {
+    let divisors = U64s::splat(divisor);
+    with_simd_divide_fn!(divider, divide => map_slice(
+        |n| n - divide(n) * divisors,
+        |n| n - divider.divide(n) * divisor,
+        src,
+        dst,
+    ))
+}
+
+pub(crate) fn mod_slice_in_place(divider: DividerU64, divisor: u64, values: &mut [u64]) {
+    let divisors = U64s::splat(divisor);
+    with_simd_divide_fn!(divider, divide => map_slice_in_place(
+        |n| n - divide(n) * divisors,
+        |n| n - divider.divide(n) * divisor,
+        values,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SplitFactor, U64s, LANES};
+
+    #[test]
+    fn test_mullhi() {
+        let factors = [0u64, 1, 7, u32::MAX as u64, 1 << 32, u64::MAX - 1, u64::MAX];
+        for &factor in &factors {
+            let mut n = [0u64; LANES];
+            n[..factors.len()].copy_from_slice(&factors);
+            let res = SplitFactor::new(factor).mullhi(U64s::from_array(n));
+            for (&n, &res) in n.iter().zip(res.as_array()) {
+                assert_eq!(res, ((factor as u128 * n as u128) >> 64) as u64);
+            }
+        }
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_mullhi_proptest(factor: u64, n: [u64; LANES]) {
+            let res = SplitFactor::new(factor).mullhi(U64s::from_array(n));
+            for (&n, &res) in n.iter().zip(res.as_array()) {
+                assert_eq!(res, ((factor as u128 * n as u128) >> 64) as u64);
+            }
+        }
+    }
+}
@@ -0,0 +1,643 @@
+//! Batch division of slices.
+//!
+//! The dispatch on the algorithm of the divider is hoisted out of the loops,
+//! so that each kernel is monomorphized for a given algorithm and processes
+//! its values in chunks of 8 with straight-line code. LLVM can then
+//! autovectorize the multiply / shift sequence.
+
+#[cfg(feature = "alloc")]
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::convert::TryFrom;
+#[cfg(target_has_atomic = "64")]
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use crate::divrem::{DivisibilityCheck, Fold};
+use crate::Backend;
+use crate::{libdivide_mullhi_u64, DivRemU64, DividerU64, FastDivider, GeneralDivider};
+
+const CHUNK_LEN: usize = 8;
+
+// The histograms divide their values by chunks of this length, in a stack buffer.
+const HISTOGRAM_CHUNK_LEN: usize = 256;
+
+/// Runs `$body` with `$divide` bound to a closure dividing by `$divider`,
+/// specialized for the algorithm of the divider.
+macro_rules! with_divide_fn {
+    ($divider:expr, $divide:ident => $body:expr) => {
+        match $divider {
+            DividerU64::Fast { magic, shift } => {
+                let $divide = move |n: u64| FastDivider { magic, shift }.divide(n);
+                $body
+            }
+            DividerU64::BitShift(shift) => {
+                let $divide = move |n: u64| n >> shift;
+                $body
+            }
+            DividerU64::Identity => {
+                let $divide = |n: u64| n;
+                $body
+            }
+            DividerU64::Compare(divisor) => {
+                let $divide = move |n: u64| (n >= divisor) as u64;
+                $body
+            }
+            DividerU64::PreShift {
+                magic,
+                pre_shift,
+                shift,
+            } => {
+                let $divide = move |n: u64| libdivide_mullhi_u64(magic, n >> pre_shift) >> shift;
+                $body
+            }
+            DividerU64::General { magic_low, shift } => {
+                let $divide = move |n: u64| GeneralDivider { magic_low, shift }.divide(n);
+                $body
+            }
+        }
+    };
+}
+
+/// Returns the result of the SIMD kernel `$kernel` of the current backend,
+/// or `$scalar` for the scalar backend.
+macro_rules! dispatch_kernel {
+    ($kernel:ident($($arg:expr),*), $scalar:expr) => {
+        // `Backend::current` is always available.
+        match Backend::current() {
+            #[cfg(all(feature = "avx512", target_arch = "x86_64"))]
+            Backend::Avx512 => unsafe { crate::avx512::$kernel($($arg),*) },
+            #[cfg(target_arch = "x86_64")]
+            Backend::Avx2 => unsafe { crate::avx2::$kernel($($arg),*) },
+            #[cfg(all(feature = "sve", target_arch = "aarch64"))]
+            Backend::Sve => unsafe { crate::sve::$kernel($($arg),*) },
+            #[cfg(all(feature = "neon", target_arch = "aarch64"))]
+            Backend::Neon => unsafe { crate::neon::$kernel($($arg),*) },
+            #[cfg(all(feature = "rvv", target_arch = "riscv64"))]
+            Backend::Rvv => unsafe { crate::rvv::$kernel($($arg),*) },
+            #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+            Backend::Simd128 => unsafe { crate::wasm_simd::$kernel($($arg),*) },
+            #[cfg(feature = "simd")]
+            Backend::PortableSimd => crate::simd::$kernel($($arg),*),
+            #[cfg(feature = "wide")]
+            Backend::Wide => crate::wide_impls::$kernel($($arg),*),
+            _ => $scalar,
+        }
+    };
+}
+
+/// What [`DividerU64::bin_into`] does with the values falling outside
+/// of its buckets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutOfRange {
+    /// The values are not counted.
+    Discard,
+    /// The values below the range are counted in the first bucket,
+    /// and those above it in the last bucket.
+    Clamp,
+}
+
+#[inline(always)]
+fn map_slice(f: impl Fn(u64) -> u64, src: &[u64], dst: &mut [u64]) {
+    assert_eq!(src.len(), dst.len());
+    let mut src_chunks = src.chunks_exact(CHUNK_LEN);
+    let mut dst_chunks = dst.chunks_exact_mut(CHUNK_LEN);
+    for (src_chunk, dst_chunk) in (&mut src_chunks).zip(&mut dst_chunks) {
+        for i in 0..CHUNK_LEN {
+            dst_chunk[i] = f(src_chunk[i]);
+        }
+    }
+    let src_tail = src_chunks.remainder();
+    for (dst_val, &src_val) in dst_chunks.into_remainder().iter_mut().zip(src_tail) {
+        *dst_val = f(src_val);
+    }
+}
+
+#[inline(always)]
+fn map_slice_in_place(f: impl Fn(u64) -> u64, values: &mut [u64]) {
+    let mut chunks = values.chunks_exact_mut(CHUNK_LEN);
+    for chunk in &mut chunks {
+        for val in chunk.iter_mut() {
+            *val = f(*val);
+        }
+    }
+    for val in chunks.into_remainder() {
+        *val = f(*val);
+    }
+}
+
+impl DividerU64 {
+    /// Calls `f(n, (n - min) / divisor)` for each of the values, the quotient
+    /// being garbage for `n < min`.
+    ///
+    /// The values are divided by chunks with `divide_slice_in_place`, so that
+    /// the histograms go through the SIMD kernels of the current backend.
+    #[inline(always)]
+    fn for_each_quotient(&self, values: &[u64], min: u64, mut f: impl FnMut(u64, u64)) {
+        let mut buffer = [0u64; HISTOGRAM_CHUNK_LEN];
+        for chunk in values.chunks(HISTOGRAM_CHUNK_LEN) {
+            let quotients = &mut buffer[..chunk.len()];
+            for (quotient, &n) in quotients.iter_mut().zip(chunk) {
+                *quotient = n.wrapping_sub(min);
+            }
+            self.divide_slice_in_place(quotients);
+            for (&n, &quotient) in chunk.iter().zip(quotients.iter()) {
+                f(n, quotient);
+            }
+        }
+    }
+
+    /// Divides all of the values of `src`, and writes the quotients in `dst`.
+    ///
+    /// Panics if `src` and `dst` do not have the same length.
+    pub fn divide_slice(&self, src: &[u64], dst: &mut [u64]) {
+        dispatch_kernel!(
+            divide_slice(*self, src, dst),
+            with_divide_fn!(*self, divide => map_slice(divide, src, dst))
+        )
+    }
+
+    /// Replaces all of the values of `values` by their quotient.
+    pub fn divide_slice_in_place(&self, values: &mut [u64]) {
+        dispatch_kernel!(
+            divide_slice_in_place(*self, values),
+            with_divide_fn!(*self, divide => map_slice_in_place(divide, values))
+        )
+    }
+
+    /// Divides all of the values of a fixed-size array.
+    ///
+    /// The division is selected once, and the number of values is known at
+    /// compile time: the loop can be fully unrolled, with no bound check nor
+    /// tail to handle, and without the runtime dispatch of the slice methods.
+    #[inline]
+    pub fn divide_array<const N: usize>(&self, values: [u64; N]) -> [u64; N] {
+        with_divide_fn!(*self, divide => values.map(divide))
+    }
+
+    /// Divides in place every `stride`-th value of `values`, starting at `offset`.
+    ///
+    /// This makes it possible to divide a single field of interleaved records,
+    /// without gathering it into a temporary buffer.
+    ///
+    /// Panics if `stride` is 0.
+    pub fn divide_strided(&self, values: &mut [u64], stride: usize, offset: usize) {
+        assert!(stride > 0);
+        if offset >= values.len() {
+            return;
+        }
+        with_divide_fn!(*self, divide => {
+            for n in values[offset..].iter_mut().step_by(stride) {
+                *n = divide(*n);
+            }
+        })
+    }
+
+    /// Divides in place all of the values that `values` yields.
+    ///
+    /// This covers containers whose elements are not contiguous, such as
+    /// an `ndarray::ArrayViewMut` with arbitrary strides, through its `iter_mut()`.
+    pub fn divide_each<'a>(&self, values: impl IntoIterator<Item = &'a mut u64>) {
+        with_divide_fn!(*self, divide => {
+            for n in values {
+                *n = divide(*n);
+            }
+        })
+    }
+
+    /// Divides in place the values of `values` whose bit is set in `validity`,
+    /// and leaves the others untouched.
+    ///
+    /// The bitmap has the layout of Arrow's validity buffers: the bit of
+    /// `values[i]` is the bit `i % 8` of `validity[i / 8]`. Runs of 64 valid
+    /// values go through `divide_slice_in_place`, and runs of 64 null values
+    /// are skipped.
+    ///
+    /// Panics if `validity` has less than `values.len()` bits.
+    pub fn divide_valid_in_place(&self, values: &mut [u64], validity: &[u8]) {
+        assert!(validity.len() * 8 >= values.len());
+        for (chunk, bits) in values.chunks_mut(64).zip(validity.chunks(8)) {
+            let mut mask = [0u8; 8];
+            mask[..bits.len()].copy_from_slice(bits);
+            let mask = u64::from_le_bytes(mask);
+            let full = u64::MAX >> (64 - chunk.len());
+            if mask & full == full {
+                self.divide_slice_in_place(chunk);
+            } else if mask & full != 0 {
+                for (i, n) in chunk.iter_mut().enumerate() {
+                    if mask & (1 << i) != 0 {
+                        *n = self.divide(*n);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the quotients of all of the values of `values`.
+    #[cfg(feature = "alloc")]
+    pub fn divide_to_vec(&self, values: &[u64]) -> Vec<u64> {
+        let mut quotients = values.to_vec();
+        self.divide_slice_in_place(&mut quotients);
+        quotients
+    }
+
+    /// Returns the number of values of each non-empty bucket, the bucket
+    /// of `n >= min` being `(n - min) / divisor`.
+    ///
+    /// Unlike [`DividerU64::bin_into`], the number of buckets does not need to
+    /// be bounded. Values lower than `min` are not counted.
+    #[cfg(feature = "alloc")]
+    pub fn sparse_histogram(&self, values: &[u64], min: u64) -> BTreeMap<u64, u64> {
+        let mut counts = BTreeMap::new();
+        self.for_each_quotient(values, min, |n, quotient| {
+            if n >= min {
+                *counts.entry(quotient).or_insert(0) += 1;
+            }
+        });
+        counts
+    }
+
+    /// Returns the sum of the quotients of all of the values of `values`,
+    /// without materializing the quotients.
+    pub fn sum_of_quotients(&self, values: &[u64]) -> u128 {
+        with_divide_fn!(*self, divide => values.iter().map(|&n| divide(n) as u128).sum())
+    }
+
+    /// Increments `counts[q]` for the quotient `q` of each of the values.
+    ///
+    /// Values whose quotient is not smaller than `counts.len()` are not counted.
+    /// Returns their number.
+    pub fn bucket_counts(&self, values: &[u64], counts: &mut [u64]) -> usize {
+        let mut num_out_of_range = 0;
+        self.for_each_quotient(values, 0, |_, quotient| {
+            let bucket = usize::try_from(quotient).ok();
+            match bucket.and_then(|bucket| counts.get_mut(bucket)) {
+                Some(count) => *count += 1,
+                None => num_out_of_range += 1,
+            }
+        });
+        num_out_of_range
+    }
+
+    /// Counts the values in `buckets`, the bucket of `n >= min` being
+    /// `(n - min) / divisor`.
+    ///
+    /// The counters saturate at `u32::MAX` instead of wrapping around, and
+    /// `policy` decides what to do with the values outside of the buckets.
+    /// Nothing is allocated, which suits fixed-size telemetry buffers.
+    /// Returns the number of values outside of the buckets, whether they were
+    /// counted or not.
+    pub fn bin_into(
+        &self,
+        values: &[u64],
+        min: u64,
+        buckets: &mut [u32],
+        policy: OutOfRange,
+    ) -> usize {
+        let last = match buckets.len().checked_sub(1) {
+            Some(last) => last,
+            None => return values.len(),
+        };
+        let mut num_out_of_range = 0;
+        self.for_each_quotient(values, min, |n, quotient| {
+            let bucket = if n >= min {
+                usize::try_from(quotient).unwrap_or(usize::MAX)
+            } else {
+                num_out_of_range += 1;
+                match policy {
+                    OutOfRange::Discard => return,
+                    OutOfRange::Clamp => 0,
+                }
+            };
+            let bucket = if bucket > last {
+                num_out_of_range += 1;
+                match policy {
+                    OutOfRange::Discard => return,
+                    OutOfRange::Clamp => last,
+                }
+            } else {
+                bucket
+            };
+            buckets[bucket] = buckets[bucket].saturating_add(1);
+        });
+        num_out_of_range
+    }
+
+    /// Same as [`DividerU64::bucket_counts`], but increments shared atomic
+    /// counters, so that several threads can fill the same histogram.
+    ///
+    /// The counters are incremented with a relaxed ordering.
+    #[cfg(target_has_atomic = "64")]
+    pub fn bucket_counts_atomic(&self, values: &[u64], counts: &[AtomicU64]) -> usize {
+        let mut num_out_of_range = 0;
+        self.for_each_quotient(values, 0, |_, quotient| {
+            let bucket = usize::try_from(quotient).ok();
+            match bucket.and_then(|bucket| counts.get(bucket)) {
+                Some(count) => {
+                    count.fetch_add(1, Ordering::Relaxed);
+                }
+                None => num_out_of_range += 1,
+            }
+        });
+        num_out_of_range
+    }
+}
+
+impl DivRemU64 {
+    /// Computes the remainder of all of the values of `src`, and writes them in `dst`.
+    ///
+    /// Panics if `src` and `dst` do not have the same length.
+    pub fn mod_slice(&self, src: &[u64], dst: &mut [u64]) {
+        if self.fold != Fold::None {
+            return map_slice(|n| self.remainder(n), src, dst);
+        }
+        let divisor = self.divisor();
+        dispatch_kernel!(
+            mod_slice(self.divider(), divisor, src, dst),
+            with_divide_fn!(self.divider(), divide => map_slice(|n| n - divide(n) * divisor, src, dst))
+        )
+    }
+
+    /// Computes both the quotient and the remainder of all of the values of `src`
+    /// in a single pass, and writes them in `quotients` and `remainders`.
+    ///
+    /// Panics if `src`, `quotients` and `remainders` do not have the same length.
+    pub fn div_rem_slice(&self, src: &[u64], quotients: &mut [u64], remainders: &mut [u64]) {
+        assert_eq!(src.len(), quotients.len());
+        assert_eq!(src.len(), remainders.len());
+        let divisor = self.divisor();
+        with_divide_fn!(self.divider(), divide => {
+            for ((quotient, remainder), &n) in quotients.iter_mut().zip(remainders.iter_mut()).zip(src) {
+                let q = divide(n);
+                *quotient = q;
+                *remainder = n - q * divisor;
+            }
+        })
+    }
+
+    /// Returns the number of values that are multiples of the divisor.
+    ///
+    /// This checks divisibility with a multiplication by the modular inverse
+    /// of the divisor, which is cheaper than computing remainders.
+    pub fn count_divisible(&self, values: &[u64]) -> usize {
+        let check = DivisibilityCheck::new(self.divisor());
+        values.iter().filter(|&&n| check.is_divisible(n)).count()
+    }
+
+    /// Replaces all of the values of `values` by their remainder.
+    pub fn mod_slice_in_place(&self, values: &mut [u64]) {
+        if self.fold != Fold::None {
+            return map_slice_in_place(|n| self.remainder(n), values);
+        }
+        let divisor = self.divisor();
+        dispatch_kernel!(
+            mod_slice_in_place(self.divider(), divisor, values),
+            with_divide_fn!(self.divider(), divide => map_slice_in_place(|n| n - divide(n) * divisor, values))
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OutOfRange;
+    use crate::{DivRemU64, DividerU64};
+    use std::vec::Vec;
+
+    fn numerators() -> Vec<u64> {
+        (0u64..1_000)
+            .chain([u64::MAX - 1, u64::MAX, 1 << 63, 234234131223u64])
+            .collect()
+    }
+
+    fn divisors() -> Vec<u64> {
+        (1u64..100)
+            .chain((0..64).map(|i| 1 << i))
+            .chain([14, 234234131223u64, (1 << 63) + 1, u64::MAX])
+            .collect()
+    }
+
+    #[test]
+    fn test_divide_slice() {
+        let src = numerators();
+        for d in divisors() {
+            let divider = DividerU64::divide_by(d);
+            let mut dst = vec![0u64; src.len()];
+            divider.divide_slice(&src, &mut dst);
+            let expected: Vec<u64> = src.iter().map(|n| n / d).collect();
+            assert_eq!(dst, expected);
+        }
+    }
+
+    #[test]
+    fn test_divide_slice_in_place() {
+        for d in divisors() {
+            let divider = DividerU64::divide_by(d);
+            let mut values = numerators();
+            divider.divide_slice_in_place(&mut values);
+            let expected: Vec<u64> = numerators().iter().map(|n| n / d).collect();
+            assert_eq!(values, expected);
+        }
+    }
+
+    #[test]
+    fn test_divide_array() {
+        for d in divisors() {
+            let divider = DividerU64::divide_by(d);
+            let values = [
+                0u64,
+                1,
+                7,
+                1 << 32,
+                1 << 63,
+                u64::MAX - 1,
+                u64::MAX,
+                234234131223u64,
+            ];
+            assert_eq!(divider.divide_array(values), values.map(|n| n / d));
+            assert_eq!(divider.divide_array([]), [0u64; 0]);
+            // Neither a multiple of the SIMD lane counts nor of the unrolling.
+            let odd = [u64::MAX, 3, 1 << 40];
+            assert_eq!(divider.divide_array(odd), odd.map(|n| n / d));
+            let odd: [u64; 13] = core::array::from_fn(|i| u64::MAX / (i as u64 + 1));
+            assert_eq!(divider.divide_array(odd), odd.map(|n| n / d));
+        }
+    }
+
+    #[test]
+    fn test_divide_strided() {
+        let divider = DividerU64::divide_by(10);
+        let mut values = vec![10, 20, 30, 40, 50, 60, 70];
+        divider.divide_strided(&mut values, 3, 1);
+        assert_eq!(values, vec![10, 2, 30, 40, 5, 60, 70]);
+        divider.divide_strided(&mut values, 1, 7);
+        assert_eq!(values, vec![10, 2, 30, 40, 5, 60, 70]);
+        divider.divide_strided(&mut values, 2, 0);
+        assert_eq!(values, vec![1, 2, 3, 40, 0, 60, 7]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_divide_strided_zero_stride() {
+        DividerU64::divide_by(3).divide_strided(&mut [1, 2, 3], 0, 0);
+    }
+
+    #[test]
+    fn test_divide_each() {
+        // The columns of a 3x3 row-major matrix.
+        let mut matrix = vec![10, 20, 30, 40, 50, 60, 70, 80, 90];
+        let column = matrix.chunks_mut(3).map(|row| &mut row[1]);
+        DividerU64::divide_by(10).divide_each(column);
+        assert_eq!(matrix, vec![10, 2, 30, 40, 5, 60, 70, 8, 90]);
+    }
+
+    #[test]
+    fn test_divide_valid_in_place() {
+        let src: Vec<u64> = (0..150).map(|i| i * 7).collect();
+        // All valid, then all null, then one value out of three.
+        let mut validity = vec![0xFF; 8];
+        validity.extend([0; 8]);
+        validity.extend((0..3).map(|i| [0b0100_1001, 0b1001_0010, 0b0010_0100][i % 3]));
+        let mut values = src.clone();
+        DividerU64::divide_by(7).divide_valid_in_place(&mut values, &validity);
+        for (i, (&n, &q)) in src.iter().zip(&values).enumerate() {
+            let valid = validity[i / 8] & (1 << (i % 8)) != 0;
+            assert_eq!(q, if valid { n / 7 } else { n });
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_divide_valid_in_place_short_bitmap() {
+        DividerU64::divide_by(7).divide_valid_in_place(&mut [0; 9], &[0xFF]);
+    }
+
+    #[test]
+    fn test_bin_into() {
+        let divider = DividerU64::divide_by(3);
+        let values = [0u64, 1, 4, 36, 2, 1, 9];
+        let mut buckets = [0u32; 3];
+        assert_eq!(
+            divider.bin_into(&values, 1, &mut buckets, OutOfRange::Discard),
+            2
+        );
+        assert_eq!(buckets, [3, 1, 1]);
+        let mut buckets = [0u32; 3];
+        assert_eq!(
+            divider.bin_into(&values, 1, &mut buckets, OutOfRange::Clamp),
+            2
+        );
+        assert_eq!(buckets, [4, 1, 2]);
+        let mut buckets = [u32::MAX - 1, 0];
+        divider.bin_into(&[0, 0, 0], 0, &mut buckets, OutOfRange::Discard);
+        assert_eq!(buckets, [u32::MAX, 0]);
+        assert_eq!(divider.bin_into(&values, 0, &mut [], OutOfRange::Clamp), 7);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_divide_to_vec() {
+        let src = numerators();
+        for d in divisors() {
+            let expected: Vec<u64> = src.iter().map(|&n| n / d).collect();
+            assert_eq!(DividerU64::divide_by(d).divide_to_vec(&src), expected);
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_sparse_histogram() {
+        let divider = DividerU64::divide_by(3);
+        let histogram = divider.sparse_histogram(&[0, 1, 4, 36, 2, 1, u64::MAX], 1);
+        let expected = [(0, 3), (1, 1), (11, 1), ((u64::MAX - 1) / 3, 1)];
+        assert_eq!(histogram.into_iter().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn test_sum_of_quotients() {
+        let src = numerators();
+        for d in divisors() {
+            let expected: u128 = src.iter().map(|&n| (n / d) as u128).sum();
+            assert_eq!(DividerU64::divide_by(d).sum_of_quotients(&src), expected);
+        }
+        assert_eq!(DividerU64::divide_by(3).sum_of_quotients(&[]), 0);
+    }
+
+    #[test]
+    fn test_bucket_counts() {
+        let divider = DividerU64::divide_by(10);
+        let mut counts = vec![0u64; 3];
+        let values = [0, 9, 10, 25, 29, 30, u64::MAX, 5];
+        assert_eq!(divider.bucket_counts(&values, &mut counts), 2);
+        assert_eq!(counts, vec![3, 1, 2]);
+        // Counts accumulate over calls.
+        assert_eq!(divider.bucket_counts(&[1], &mut counts), 0);
+        assert_eq!(counts, vec![4, 1, 2]);
+        assert_eq!(divider.bucket_counts(&values, &mut []), values.len());
+    }
+
+    #[test]
+    fn test_count_divisible() {
+        let src = numerators();
+        for d in divisors() {
+            let expected = src.iter().filter(|&&n| n % d == 0).count();
+            assert_eq!(DivRemU64::divide_by(d).count_divisible(&src), expected);
+        }
+    }
+
+    #[test]
+    fn test_bucket_counts_atomic() {
+        use core::sync::atomic::{AtomicU64, Ordering};
+
+        let divider = DividerU64::divide_by(10);
+        let counts: Vec<AtomicU64> = (0..3).map(|_| AtomicU64::new(0)).collect();
+        let values: Vec<u64> = (0..40).collect();
+        let num_out_of_range: usize = std::thread::scope(|scope| {
+            let threads: Vec<_> = (0..4)
+                .map(|_| scope.spawn(|| divider.bucket_counts_atomic(&values, &counts)))
+                .collect();
+            threads
+                .into_iter()
+                .map(|thread| thread.join().unwrap())
+                .sum()
+        });
+        assert_eq!(num_out_of_range, 40);
+        let counts: Vec<u64> = counts
+            .iter()
+            .map(|count| count.load(Ordering::Relaxed))
+            .collect();
+        assert_eq!(counts, vec![40, 40, 40]);
+    }
+
+    #[test]
+    fn test_mod_slice() {
+        let src = numerators();
+        for d in divisors().into_iter().chain([(1 << 61) - 1, (1 << 40) + 1]) {
+            let divider = DivRemU64::divide_by(d);
+            let expected: Vec<u64> = src.iter().map(|n| n % d).collect();
+            let mut dst = vec![0u64; src.len()];
+            divider.mod_slice(&src, &mut dst);
+            assert_eq!(dst, expected);
+            let mut values = src.clone();
+            divider.mod_slice_in_place(&mut values);
+            assert_eq!(values, expected);
+        }
+    }
+
+    #[test]
+    fn test_div_rem_slice() {
+        let src = numerators();
+        for d in divisors() {
+            let divider = DivRemU64::divide_by(d);
+            let mut quotients = vec![0u64; src.len()];
+            let mut remainders = vec![0u64; src.len()];
+            divider.div_rem_slice(&src, &mut quotients, &mut remainders);
+            for i in 0..src.len() {
+                assert_eq!((quotients[i], remainders[i]), (src[i] / d, src[i] % d));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_divide_slice_length_mismatch() {
+        DividerU64::divide_by(3).divide_slice(&[1, 2, 3], &mut [0, 0]);
+    }
+}
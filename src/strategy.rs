@@ -0,0 +1,56 @@
+//! proptest strategies generating dividers, for the tests of downstream crates.
+
+use core::ops::{Bound, RangeBounds};
+
+use proptest::prelude::*;
+
+use crate::DividerU64;
+
+/// Returns a strategy generating dividers of all kinds, see `DividerU64::from_seed`.
+pub fn any_divider() -> impl Strategy<Value = DividerU64> {
+    any::<u64>().prop_map(DividerU64::from_seed)
+}
+
+/// Returns a strategy generating the dividers of the divisors within `divisors`.
+///
+/// A divisor of 0 is never generated.
+///
+/// Panics if `divisors` does not contain any non-zero divisor.
+pub fn divider_for_range(divisors: impl RangeBounds<u64>) -> impl Strategy<Value = DividerU64> {
+    let lo = match divisors.start_bound() {
+        Bound::Included(&lo) => lo,
+        Bound::Excluded(&lo) => lo.checked_add(1).expect("empty range of divisors"),
+        Bound::Unbounded => 0,
+    };
+    let hi = match divisors.end_bound() {
+        Bound::Included(&hi) => hi,
+        Bound::Excluded(&hi) => hi.checked_sub(1).expect("empty range of divisors"),
+        Bound::Unbounded => u64::MAX,
+    };
+    let lo = lo.max(1);
+    assert!(lo <= hi, "empty range of divisors");
+    (lo..=hi).prop_map(DividerU64::divide_by)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{any_divider, divider_for_range};
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn test_any_divider(divider in any_divider(), n: u64) {
+            prop_assert_eq!(divider.divide(n), n / divider.divisor());
+        }
+
+        #[test]
+        fn test_divider_for_range(divider in divider_for_range(..10)) {
+            prop_assert!((1..10).contains(&divider.divisor()));
+        }
+
+        #[test]
+        fn test_divider_for_large_range(divider in divider_for_range(1 << 63..)) {
+            prop_assert!(divider.divisor() >= 1 << 63);
+        }
+    }
+}
@@ -0,0 +1,271 @@
+//! SVE implementation of the slice kernels.
+//!
+//! SVE intrinsics are not available in Rust yet, so the loops are written
+//! with `asm!`. SVE has a native 64-bit high multiplication (`umulh`), and
+//! the loops are vector-length agnostic: they use the full width
+//! of the hardware, predicating the tail.
+//!
+//! Only the divisions actually requiring a multiplication have an SVE loop.
+//! The other ones are plain shifts or comparisons, that the compiler
+//! vectorizes on its own.
+
+use core::arch::asm;
+
+use crate::DividerU64;
+
+/// Returns true if the SVE kernels can be used on the current CPU.
+///
+/// Without the `std` feature, this is only known at compile time.
+#[inline]
+pub(crate) fn is_available() -> bool {
+    #[cfg(any(feature = "std", test))]
+    {
+        std::arch::is_aarch64_feature_detected!("sve")
+    }
+    #[cfg(not(any(feature = "std", test)))]
+    {
+        cfg!(target_feature = "sve")
+    }
+}
+
+/// Defines a function running an SVE loop over `len` values of `src`,
+/// writing the results to `dst`.
+///
+/// Within `$body`, the values are loaded in `z0`, under the predicate `p0`.
+/// The result is expected in `z5`. `z1`, `z2`, `z3` and `z6` hold the
+/// `magic`, `pre_shift`, `shift` and `divisor` arguments in all lanes.
+macro_rules! sve_loop {
+    ($name:ident, $($body:literal),*) => {
+        /// # Safety
+        ///
+        /// The CPU must support SVE, `src` and `dst` must be valid for `len` values.
+        #[target_feature(enable = "sve")]
+        unsafe fn $name(
+            src: *const u64,
+            dst: *mut u64,
+            len: usize,
+            magic: u64,
+            pre_shift: u8,
+            shift: u8,
+            divisor: u64,
+        ) {
+            asm!(
+                "mov {i}, #0",
+                "dup z1.d, {magic}",
+                "dup z2.d, {pre_shift}",
+                "dup z3.d, {shift}",
+                "dup z6.d, {divisor}",
+                "whilelo p0.d, {i}, {len}",
+                "b.none 2f",
+                "1:",
+                "ld1d {{z0.d}}, p0/z, [{src}, {i}, lsl #3]",
+                $($body,)*
+                "st1d {{z5.d}}, p0, [{dst}, {i}, lsl #3]",
+                "incd {i}",
+                "whilelo p0.d, {i}, {len}",
+                "b.first 1b",
+                "2:",
+                src = in(reg) src,
+                dst = in(reg) dst,
+                len = in(reg) len,
+                magic = in(reg) magic,
+                pre_shift = in(reg) pre_shift as u64,
+                shift = in(reg) shift as u64,
+                divisor = in(reg) divisor,
+                i = out(reg) _,
+                out("v0") _, out("v1") _, out("v2") _, out("v3") _,
+                out("v4") _, out("v5") _, out("v6") _,
+                out("p0") _,
+                options(nostack),
+            );
+        }
+    };
+}
+
+// q = mulhi(magic, n >> pre_shift) >> shift
+sve_loop!(
+    divide_fast,
+    "movprfx z5, z0",
+    "lsr z5.d, p0/m, z5.d, z2.d",
+    "umulh z5.d, p0/m, z5.d, z1.d",
+    "lsr z5.d, p0/m, z5.d, z3.d"
+);
+
+// q = (((n - t) >> 1) + t) >> shift, with t = mulhi(magic, n)
+sve_loop!(
+    divide_general,
+    "movprfx z4, z0",
+    "umulh z4.d, p0/m, z4.d, z1.d",
+    "sub z5.d, z0.d, z4.d",
+    "lsr z5.d, z5.d, #1",
+    "add z5.d, z5.d, z4.d",
+    "lsr z5.d, p0/m, z5.d, z3.d"
+);
+
+// n - q * divisor, with q computed as in `divide_fast`.
+sve_loop!(
+    mod_fast,
+    "movprfx z5, z0",
+    "lsr z5.d, p0/m, z5.d, z2.d",
+    "umulh z5.d, p0/m, z5.d, z1.d",
+    "lsr z5.d, p0/m, z5.d, z3.d",
+    "mul z5.d, p0/m, z5.d, z6.d",
+    "sub z5.d, z0.d, z5.d"
+);
+
+// n - q * divisor, with q computed as in `divide_general`.
+sve_loop!(
+    mod_general,
+    "movprfx z4, z0",
+    "umulh z4.d, p0/m, z4.d, z1.d",
+    "sub z5.d, z0.d, z4.d",
+    "lsr z5.d, z5.d, #1",
+    "add z5.d, z5.d, z4.d",
+    "lsr z5.d, p0/m, z5.d, z3.d",
+    "mul z5.d, p0/m, z5.d, z6.d",
+    "sub z5.d, z0.d, z5.d"
+);
+
+/// The SVE loops dividing and computing a remainder for `divider`,
+/// with their `magic`, `pre_shift` and `shift` arguments.
+type Loops = (
+    unsafe fn(*const u64, *mut u64, usize, u64, u8, u8, u64),
+    unsafe fn(*const u64, *mut u64, usize, u64, u8, u8, u64),
+    u64,
+    u8,
+    u8,
+);
+
+fn loops(divider: DividerU64) -> Option<Loops> {
+    match divider {
+        DividerU64::Fast { magic, shift } => Some((divide_fast, mod_fast, magic, 0, shift)),
+        DividerU64::PreShift {
+            magic,
+            pre_shift,
+            shift,
+        } => Some((divide_fast, mod_fast, magic, pre_shift, shift)),
+        DividerU64::General { magic_low, shift } => {
+            Some((divide_general, mod_general, magic_low, 0, shift))
+        }
+        DividerU64::Identity | DividerU64::BitShift(_) | DividerU64::Compare(_) => None,
+    }
+}
+
+/// # Safety
+///
+/// The CPU must support SVE.
+pub(crate) unsafe fn divide_slice(divider: DividerU64, src: &[u64], dst: &mut [u64]) {
+    assert_eq!(src.len(), dst.len());
+    match loops(divider) {
+        Some((divide, _, magic, pre_shift, shift)) => divide(
+            src.as_ptr(),
+            dst.as_mut_ptr(),
+            src.len(),
+            magic,
+            pre_shift,
+            shift,
+            0,
+        ),
+        None => {
+            for (quotient, &n) in dst.iter_mut().zip(src) {
+                *quotient = divider.divide(n);
+            }
+        }
+    }
+}
+
+/// # Safety
+///
+/// The CPU must support SVE.
+pub(crate) unsafe fn divide_slice_in_place(divider: DividerU64, values: &mut [u64]) {
+    match loops(divider) {
+        Some((divide, _, magic, pre_shift, shift)) => {
+            let ptr = values.as_mut_ptr();
+            divide(ptr, ptr, values.len(), magic, pre_shift, shift, 0)
+        }
+        None => {
+            for n in values.iter_mut() {
+                *n = divider.divide(*n);
+            }
+        }
+    }
+}
+
+/// # Safety
+///
+/// The CPU must support SVE.
+pub(crate) unsafe fn mod_slice(divider: DividerU64, divisor: u64, src: &[u64], dst: &mut [u64]) {
+    assert_eq!(src.len(), dst.len());
+    match loops(divider) {
+        Some((_, remainder, magic, pre_shift, shift)) => remainder(
+            src.as_ptr(),
+            dst.as_mut_ptr(),
+            src.len(),
+            magic,
+            pre_shift,
+            shift,
+            divisor,
+        ),
+        None => {
+            for (remainder, &n) in dst.iter_mut().zip(src) {
+                *remainder = n - divider.divide(n) * divisor;
+            }
+        }
+    }
+}
+
+/// # Safety
+///
+/// The CPU must support SVE.
+pub(crate) unsafe fn mod_slice_in_place(divider: DividerU64, divisor: u64, values: &mut [u64]) {
+    match loops(divider) {
+        Some((_, remainder, magic, pre_shift, shift)) => {
+            let ptr = values.as_mut_ptr();
+            remainder(ptr, ptr, values.len(), magic, pre_shift, shift, divisor)
+        }
+        None => {
+            for n in values.iter_mut() {
+                *n -= divider.divide(*n) * divisor;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_sve_kernels() {
+        if !is_available() {
+            return;
+        }
+        let src: Vec<u64> = (0u64..1_000)
+            .chain([
+                u64::MAX - 1,
+                u64::MAX,
+                1 << 63,
+                (1 << 63) - 1,
+                234234131223u64,
+            ])
+            .collect();
+        let divisors = (1u64..300).chain((0..64).map(|i| 1 << i)).chain([
+            14,
+            234234131223u64,
+            (1 << 63) + 1,
+            u64::MAX,
+        ]);
+        for d in divisors {
+            let divider = DividerU64::divide_by(d);
+            let mut quotients = vec![0u64; src.len()];
+            unsafe { divide_slice(divider, &src, &mut quotients) };
+            let expected: Vec<u64> = src.iter().map(|n| n / d).collect();
+            assert_eq!(quotients, expected);
+            let mut remainders = src.clone();
+            unsafe { mod_slice_in_place(divider, d, &mut remainders) };
+            let expected: Vec<u64> = src.iter().map(|n| n % d).collect();
+            assert_eq!(remainders, expected);
+        }
+    }
+}
@@ -0,0 +1,72 @@
+//! Bucketing of `time` date times, behind the `time` feature.
+
+use time::OffsetDateTime;
+
+use crate::DivRemU64;
+
+impl DivRemU64 {
+    /// Returns the index of the interval of `divisor` seconds containing `datetime`,
+    /// counting from the epoch. See [`DivRemU64::div_floor_i64`].
+    #[inline]
+    pub fn bucket_offset_datetime(&self, datetime: OffsetDateTime) -> i64 {
+        self.div_floor_i64(datetime.unix_timestamp())
+    }
+
+    /// Returns the start of the interval of `divisor` seconds containing `datetime`,
+    /// with the offset of `datetime`.
+    ///
+    /// The intervals are aligned on the epoch, in UTC, and date times before
+    /// the epoch are truncated towards the past as well.
+    /// Returns `None` if the start is out of the range of `OffsetDateTime`.
+    ///
+    /// ```
+    /// use fastdivide::DivRemU64;
+    /// use time::OffsetDateTime;
+    ///
+    /// let seven_minutes = DivRemU64::divide_by(7 * 60);
+    /// let datetime = OffsetDateTime::from_unix_timestamp(-270).unwrap();
+    /// let start = seven_minutes.truncate_offset_datetime(datetime).unwrap();
+    /// assert_eq!(start.unix_timestamp(), -420);
+    /// ```
+    pub fn truncate_offset_datetime(&self, datetime: OffsetDateTime) -> Option<OffsetDateTime> {
+        let start = self.truncate_i64(datetime.unix_timestamp())?;
+        OffsetDateTime::from_unix_timestamp(start)
+            .ok()?
+            .checked_to_offset(datetime.offset())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::DivRemU64;
+    use time::{Duration, OffsetDateTime, UtcOffset};
+
+    #[test]
+    fn test_truncate_offset_datetime() {
+        let divider = DivRemU64::divide_by(90);
+        let start = OffsetDateTime::from_unix_timestamp(1_709_294_400).unwrap();
+        let datetime = start + Duration::seconds(89) + Duration::milliseconds(500);
+        assert_eq!(divider.truncate_offset_datetime(datetime), Some(start));
+        assert_eq!(divider.bucket_offset_datetime(datetime), 1_709_294_400 / 90);
+        let offset = UtcOffset::from_hms(-5, 0, 0).unwrap();
+        let truncated = divider
+            .truncate_offset_datetime(datetime.to_offset(offset))
+            .unwrap();
+        assert_eq!(truncated, start);
+        assert_eq!(truncated.offset(), offset);
+    }
+
+    #[test]
+    fn test_truncate_offset_datetime_before_epoch() {
+        let divider = DivRemU64::divide_by(90);
+        let datetime = OffsetDateTime::UNIX_EPOCH - Duration::nanoseconds(1);
+        assert_eq!(divider.bucket_offset_datetime(datetime), -1);
+        assert_eq!(
+            divider.truncate_offset_datetime(datetime),
+            OffsetDateTime::from_unix_timestamp(-90).ok()
+        );
+        let min = OffsetDateTime::from_unix_timestamp(-377_705_116_800).unwrap();
+        let divider = DivRemU64::divide_by(1 << 40);
+        assert_eq!(divider.truncate_offset_datetime(min), None);
+    }
+}
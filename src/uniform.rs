@@ -0,0 +1,113 @@
+//! Uniform sampling in `0..bound`, with the `rand` feature.
+
+use rand::distr::Distribution;
+use rand::Rng;
+
+/// Uniform distribution over `0..bound`, for a bound fixed once for many samples.
+///
+/// This is Lemire's nearly divisionless method: the random word is mapped onto
+/// `0..bound` with a multiplication, and the rejection threshold that removes
+/// the bias is precomputed, rather than computed with a division when the
+/// first candidate is close to being rejected.
+///
+/// ```
+/// use fastdivide::UniformU64Bounded;
+/// use rand::distr::Distribution;
+///
+/// # fn sample(rng: &mut impl rand::Rng) {
+/// let dice = UniformU64Bounded::new(6);
+/// let roll = dice.sample(rng);
+/// assert!(roll < 6);
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UniformU64Bounded {
+    bound: u64,
+    // `2^64 % bound`
+    threshold: u64,
+}
+
+impl UniformU64Bounded {
+    /// Precomputes the distribution over `0..bound`.
+    ///
+    /// Panics if `bound` is 0.
+    pub fn new(bound: u64) -> UniformU64Bounded {
+        assert!(bound > 0, "the bound must not be 0");
+        UniformU64Bounded {
+            bound,
+            threshold: bound.wrapping_neg() % bound,
+        }
+    }
+
+    /// Returns the exclusive upper bound of the samples.
+    pub fn bound(&self) -> u64 {
+        self.bound
+    }
+}
+
+impl Distribution<u64> for UniformU64Bounded {
+    #[inline]
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> u64 {
+        loop {
+            let product = rng.next_u64() as u128 * self.bound as u128;
+            // The low bits are below the threshold for exactly `2^64 % bound`
+            // of the random words, that would over-represent some of the results.
+            if product as u64 >= self.threshold {
+                return (product >> 64) as u64;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UniformU64Bounded;
+    use rand::distr::Distribution;
+    use rand::RngCore;
+
+    struct XorShift(u64);
+
+    impl RngCore for XorShift {
+        fn next_u32(&mut self) -> u32 {
+            self.next_u64() as u32
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn fill_bytes(&mut self, dst: &mut [u8]) {
+            for byte in dst {
+                *byte = self.next_u64() as u8;
+            }
+        }
+    }
+
+    #[test]
+    fn test_uniform_u64_bounded() {
+        let mut rng = XorShift(0x9E37_79B9_7F4A_7C15);
+        for bound in [1u64, 2, 3, 6, 1000, (1 << 63) + 1, u64::MAX] {
+            let distr = UniformU64Bounded::new(bound);
+            assert_eq!(distr.bound(), bound);
+            for _ in 0..1_000 {
+                assert!(distr.sample(&mut rng) < bound);
+            }
+        }
+        let mut counts = [0u32; 6];
+        let dice = UniformU64Bounded::new(6);
+        for _ in 0..60_000 {
+            counts[dice.sample(&mut rng) as usize] += 1;
+        }
+        assert!(counts.iter().all(|&count| (9_000..11_000).contains(&count)));
+    }
+
+    #[test]
+    fn test_uniform_rejection() {
+        // With a bound of 2^63 + 1, about half of the words are rejected.
+        let distr = UniformU64Bounded::new((1 << 63) + 1);
+        assert_eq!(distr.threshold, (1 << 63) - 1);
+    }
+}
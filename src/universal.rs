@@ -0,0 +1,171 @@
+//! Universal hashing modulo a prime.
+
+use core::hash::{BuildHasher, Hasher};
+
+use crate::Mod64;
+
+/// A member `x -> (a * x + b) % prime` of the Carter-Wegman universal family
+/// of hash functions.
+///
+/// The products are reduced by the precomputed `Mod64` of the prime. This also
+/// implements `BuildHasher`, for use in hash maps, with each word of the keys
+/// folded into the state as a polynomial in `a`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UniversalHasher {
+    a: u64,
+    b: u64,
+    modulus: Mod64,
+}
+
+/// The Mersenne prime `2^61 - 1`, whose remainders require no multiplication.
+pub const MERSENNE_61: u64 = (1 << 61) - 1;
+
+impl UniversalHasher {
+    /// Returns the hash function for the coefficients `a` and `b`,
+    /// which are reduced modulo `prime`.
+    ///
+    /// `prime` is expected to be a prime, such as [`MERSENNE_61`], larger than
+    /// the hashed values. Panics if `a` is a multiple of `prime`.
+    pub fn new(a: u64, b: u64, prime: u64) -> UniversalHasher {
+        let modulus = Mod64::new(prime);
+        let a = modulus.reduce(a);
+        assert!(a != 0, "a must not be a multiple of the prime");
+        UniversalHasher {
+            a,
+            b: modulus.reduce(b),
+            modulus,
+        }
+    }
+
+    /// Returns a hash function of the family, with random coefficients.
+    ///
+    /// Panics if `prime` is lower than 2.
+    #[cfg(feature = "rand")]
+    pub fn random<R: rand::Rng + ?Sized>(rng: &mut R, prime: u64) -> UniversalHasher {
+        use crate::UniformU64Bounded;
+        use rand::distr::Distribution;
+
+        assert!(prime >= 2);
+        let a = 1 + UniformU64Bounded::new(prime - 1).sample(rng);
+        let b = UniformU64Bounded::new(prime).sample(rng);
+        UniversalHasher::new(a, b, prime)
+    }
+
+    /// Returns `(a * x + b) % prime`.
+    #[inline]
+    pub fn hash(&self, x: u64) -> u64 {
+        let ax = self.modulus.mul_mod(self.a, x);
+        self.modulus.add_mod(ax, self.b)
+    }
+}
+
+impl BuildHasher for UniversalHasher {
+    type Hasher = UniversalHasherState;
+
+    fn build_hasher(&self) -> UniversalHasherState {
+        UniversalHasherState {
+            hasher: *self,
+            state: 0,
+        }
+    }
+}
+
+/// The `Hasher` of a [`UniversalHasher`].
+///
+/// Hashing a single `u64` yields `UniversalHasher::hash` of it.
+#[derive(Debug, Clone, Copy)]
+pub struct UniversalHasherState {
+    hasher: UniversalHasher,
+    state: u64,
+}
+
+impl Hasher for UniversalHasherState {
+    fn finish(&self) -> u64 {
+        self.hasher.hash(self.state)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for chunk in bytes.chunks(8) {
+            let mut word = [0u8; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            self.write_u64(u64::from_le_bytes(word));
+        }
+    }
+
+    #[inline]
+    fn write_u64(&mut self, x: u64) {
+        let modulus = &self.hasher.modulus;
+        let state = modulus.mul_mod(self.state, self.hasher.a);
+        self.state = modulus.add_mod(state, modulus.reduce(x));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{UniversalHasher, MERSENNE_61};
+    use core::hash::{BuildHasher, Hasher};
+
+    #[test]
+    fn test_universal_hash() {
+        let hasher = UniversalHasher::new(3, 5, 7);
+        assert_eq!(hasher.hash(0), 5);
+        assert_eq!(hasher.hash(1), 1);
+        assert_eq!(hasher.hash(9), 4);
+        assert_eq!(UniversalHasher::new(10, 12, 7), hasher);
+        let hasher = UniversalHasher::new(u64::MAX, u64::MAX, MERSENNE_61);
+        let (a, b) = (u64::MAX % MERSENNE_61, u64::MAX % MERSENNE_61);
+        for x in [0, 1, 1 << 40, MERSENNE_61 - 1] {
+            let expected = (a as u128 * x as u128 + b as u128) % MERSENNE_61 as u128;
+            assert_eq!(hasher.hash(x) as u128, expected);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_universal_hash_zero_a() {
+        UniversalHasher::new(14, 1, 7);
+    }
+
+    #[test]
+    fn test_build_hasher() {
+        let hasher = UniversalHasher::new(0x1234_5678_9abc, 42, MERSENNE_61);
+        let mut state = hasher.build_hasher();
+        state.write_u64(1 << 40);
+        assert_eq!(state.finish(), hasher.hash(1 << 40));
+        let hash_of = |value: &str| hasher.hash_one(value);
+        assert_eq!(hash_of("fastdivide"), hash_of("fastdivide"));
+        assert_ne!(hash_of("fastdivide"), hash_of("fastdivider"));
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_random() {
+        struct Counter(u64);
+
+        impl rand::RngCore for Counter {
+            fn next_u32(&mut self) -> u32 {
+                self.next_u64() as u32
+            }
+
+            fn next_u64(&mut self) -> u64 {
+                self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+                self.0
+            }
+
+            fn fill_bytes(&mut self, dst: &mut [u8]) {
+                for byte in dst {
+                    *byte = self.next_u64() as u8;
+                }
+            }
+        }
+
+        let mut rng = Counter(0);
+        for _ in 0..100 {
+            let hasher = UniversalHasher::random(&mut rng, 2);
+            assert_eq!(hasher.a, 1);
+            assert!(hasher.b < 2);
+            let hasher = UniversalHasher::random(&mut rng, MERSENNE_61);
+            assert!(hasher.a > 0 && hasher.a < MERSENNE_61);
+        }
+    }
+}
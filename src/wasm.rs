@@ -0,0 +1,89 @@
+//! JavaScript bindings, with the `wasm-bindgen` feature.
+//!
+//! [`Divider`] is exported as a JS class: it is constructed once for a divisor,
+//! and then divides `BigInt`s, or whole `BigUint64Array`s at once.
+//!
+//! ```js
+//! const divider = new Divider(7n);
+//! divider.divide(100n); // 14n
+//! divider.divideMany(new BigUint64Array([7n, 8n, 15n])); // BigUint64Array [1n, 1n, 2n]
+//! ```
+
+use std::vec::Vec;
+
+use wasm_bindgen::prelude::*;
+
+use crate::DivRemU64;
+
+/// A precomputed divider, exported to JavaScript.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy)]
+pub struct Divider {
+    divider: DivRemU64,
+}
+
+#[wasm_bindgen]
+impl Divider {
+    /// Precomputes a divider for `divisor`.
+    ///
+    /// Throws if `divisor` is 0.
+    #[wasm_bindgen(constructor)]
+    pub fn new(divisor: u64) -> Result<Divider, JsError> {
+        if divisor == 0 {
+            return Err(JsError::new("the divisor must not be 0"));
+        }
+        Ok(Divider {
+            divider: DivRemU64::divide_by(divisor),
+        })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn divisor(&self) -> u64 {
+        self.divider.divisor()
+    }
+
+    /// Returns `n / divisor`.
+    pub fn divide(&self, n: u64) -> u64 {
+        self.divider.divide(n)
+    }
+
+    /// Returns `n % divisor`.
+    pub fn remainder(&self, n: u64) -> u64 {
+        self.divider.remainder(n)
+    }
+
+    /// Returns a new array, with each of the values divided by the divisor.
+    #[wasm_bindgen(js_name = divideMany)]
+    pub fn divide_many(&self, values: &[u64]) -> Vec<u64> {
+        let mut quotients = std::vec![0u64; values.len()];
+        self.divider.divider().divide_slice(values, &mut quotients);
+        quotients
+    }
+
+    /// Divides each of the values of the array by the divisor, in place.
+    #[wasm_bindgen(js_name = divideInPlace)]
+    pub fn divide_in_place(&self, values: &mut [u64]) {
+        self.divider.divider().divide_slice_in_place(values);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Divider;
+
+    #[test]
+    fn test_wasm_divider() {
+        let values: std::vec::Vec<u64> = (0..1_000u64).chain([u64::MAX - 1, u64::MAX]).collect();
+        for d in [1u64, 7, 8, 11, (1 << 63) + 1, u64::MAX] {
+            let divider = Divider::new(d).unwrap();
+            assert_eq!(divider.divisor(), d);
+            assert_eq!(divider.divide(234234131223), 234234131223 / d);
+            assert_eq!(divider.remainder(234234131223), 234234131223 % d);
+            let expected: std::vec::Vec<u64> = values.iter().map(|n| n / d).collect();
+            assert_eq!(divider.divide_many(&values), expected);
+            let mut in_place = values.clone();
+            divider.divide_in_place(&mut in_place);
+            assert_eq!(in_place, expected);
+        }
+    }
+}
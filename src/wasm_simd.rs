@@ -0,0 +1,217 @@
+//! WASM SIMD128 implementation of the slice kernels.
+//!
+//! SIMD128 only has a low 64-bit multiplication, so the high multiplication
+//! is emulated with four 32x32 bits multiplications.
+//!
+//! WASM has no runtime feature detection: this backend is used
+//! whenever the crate is compiled with the `simd128` target feature.
+
+use core::arch::wasm32::*;
+
+use crate::DividerU64;
+
+const LANES: usize = 2;
+
+#[inline]
+pub(crate) fn is_available() -> bool {
+    true
+}
+
+/// A 64-bits scalar factor, split into its 32-bits halves
+/// once and for all.
+#[derive(Clone, Copy)]
+struct SplitFactor {
+    lo: v128,
+    hi: v128,
+}
+
+#[inline]
+fn split(factor: u64) -> SplitFactor {
+    SplitFactor {
+        lo: u64x2_splat(factor & u32::MAX as u64),
+        hi: u64x2_splat(factor >> 32),
+    }
+}
+
+/// Returns the high 64 bits of the product of each lane of `n` with `factor`.
+#[inline]
+fn mullhi(factor: SplitFactor, n: v128) -> v128 {
+    let mask = u64x2_splat(u32::MAX as u64);
+    let n_lo = v128_and(n, mask);
+    let n_hi = u64x2_shr(n, 32);
+    let lo_lo = i64x2_mul(factor.lo, n_lo);
+    let hi_lo = i64x2_mul(factor.hi, n_lo);
+    let lo_hi = i64x2_mul(factor.lo, n_hi);
+    let hi_hi = i64x2_mul(factor.hi, n_hi);
+    // None of these additions can overflow.
+    let cross = i64x2_add(
+        i64x2_add(u64x2_shr(lo_lo, 32), v128_and(hi_lo, mask)),
+        lo_hi,
+    );
+    i64x2_add(i64x2_add(hi_hi, u64x2_shr(hi_lo, 32)), u64x2_shr(cross, 32))
+}
+
+/// Runs `$body` with `$divide` bound to a closure dividing `LANES` values
+/// by `$divider`, specialized for the algorithm of the divider.
+macro_rules! with_wasm_divide_fn {
+    ($divider:expr, $divide:ident => $body:expr) => {
+        match $divider {
+            DividerU64::Fast { magic, shift } => {
+                let magic = split(magic);
+                let shift = shift as u32;
+                let $divide = move |n: v128| u64x2_shr(mullhi(magic, n), shift);
+                $body
+            }
+            DividerU64::BitShift(shift) => {
+                let shift = shift as u32;
+                let $divide = move |n: v128| u64x2_shr(n, shift);
+                $body
+            }
+            DividerU64::Identity => {
+                let $divide = |n: v128| n;
+                $body
+            }
+            DividerU64::Compare(divisor) => {
+                // SIMD128 only has a signed comparison. Flipping the sign bits
+                // turns it into an unsigned one.
+                let sign = u64x2_splat(1 << 63);
+                let threshold = u64x2_splat((divisor - 1) ^ (1 << 63));
+                let $divide = move |n: v128| u64x2_shr(i64x2_gt(v128_xor(n, sign), threshold), 63);
+                $body
+            }
+            DividerU64::PreShift {
+                magic,
+                pre_shift,
+                shift,
+            } => {
+                let magic = split(magic);
+                let pre_shift = pre_shift as u32;
+                let shift = shift as u32;
+                let $divide =
+                    move |n: v128| u64x2_shr(mullhi(magic, u64x2_shr(n, pre_shift)), shift);
+                $body
+            }
+            DividerU64::General { magic_low, shift } => {
+                let magic_low = split(magic_low);
+                let shift = shift as u32;
+                let $divide = move |n: v128| {
+                    let q = mullhi(magic_low, n);
+                    let t = i64x2_add(u64x2_shr(i64x2_sub(n, q), 1), q);
+                    u64x2_shr(t, shift)
+                };
+                $body
+            }
+        }
+    };
+}
+
+#[inline]
+fn map_slice(
+    f: impl Fn(v128) -> v128,
+    f_scalar: impl Fn(u64) -> u64,
+    src: &[u64],
+    dst: &mut [u64],
+) {
+    assert_eq!(src.len(), dst.len());
+    let mut src_chunks = src.chunks_exact(LANES);
+    let mut dst_chunks = dst.chunks_exact_mut(LANES);
+    for (src_chunk, dst_chunk) in (&mut src_chunks).zip(&mut dst_chunks) {
+        // The chunks have exactly `LANES` values.
+        unsafe {
+            let n = v128_load(src_chunk.as_ptr() as *const v128);
+            v128_store(dst_chunk.as_mut_ptr() as *mut v128, f(n));
+        }
+    }
+    let src_tail = src_chunks.remainder();
+    for (dst_val, &src_val) in dst_chunks.into_remainder().iter_mut().zip(src_tail) {
+        *dst_val = f_scalar(src_val);
+    }
+}
+
+#[inline]
+fn map_slice_in_place(f: impl Fn(v128) -> v128, f_scalar: impl Fn(u64) -> u64, values: &mut [u64]) {
+    let mut chunks = values.chunks_exact_mut(LANES);
+    for chunk in &mut chunks {
+        let ptr = chunk.as_mut_ptr() as *mut v128;
+        // The chunks have exactly `LANES` values.
+        unsafe { v128_store(ptr, f(v128_load(ptr))) };
+    }
+    for val in chunks.into_remainder() {
+        *val = f_scalar(*val);
+    }
+}
+
+/// # Safety
+///
+/// Always safe, `unsafe` only to share the signature of the other backends.
+pub(crate) unsafe fn divide_slice(divider: DividerU64, src: &[u64], dst: &mut [u64]) {
+    with_wasm_divide_fn!(divider, divide => map_slice(divide, |n| divider.divide(n), src, dst))
+}
+
+/// # Safety
+///
+/// Always safe, `unsafe` only to share the signature of the other backends.
+pub(crate) unsafe fn divide_slice_in_place(divider: DividerU64, values: &mut [u64]) {
+    with_wasm_divide_fn!(divider, divide => map_slice_in_place(divide, |n| divider.divide(n), values))
+}
+
+/// # Safety
+///
+/// Always safe, `unsafe` only to share the signature of the other backends.
+pub(crate) unsafe fn mod_slice(divider: DividerU64, divisor: u64, src: &[u64], dst: &mut [u64]) {
+    let divisors = u64x2_splat(divisor);
+    with_wasm_divide_fn!(divider, divide => map_slice(
+        |n| i64x2_sub(n, i64x2_mul(divide(n), divisors)),
+        |n| n - divider.divide(n) * divisor,
+        src,
+        dst,
+    ))
+}
+
+/// # Safety
+///
+/// Always safe, `unsafe` only to share the signature of the other backends.
+pub(crate) unsafe fn mod_slice_in_place(divider: DividerU64, divisor: u64, values: &mut [u64]) {
+    let divisors = u64x2_splat(divisor);
+    with_wasm_divide_fn!(divider, divide => map_slice_in_place(
+        |n| i64x2_sub(n, i64x2_mul(divide(n), divisors)),
+        |n| n - divider.divide(n) * divisor,
+        values,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_wasm_simd_kernels() {
+        let src: Vec<u64> = (0u64..1_000)
+            .chain([
+                u64::MAX - 1,
+                u64::MAX,
+                1 << 63,
+                (1 << 63) - 1,
+                234234131223u64,
+            ])
+            .collect();
+        let divisors = (1u64..300).chain((0..64).map(|i| 1 << i)).chain([
+            14,
+            234234131223u64,
+            (1 << 63) + 1,
+            u64::MAX,
+        ]);
+        for d in divisors {
+            let divider = DividerU64::divide_by(d);
+            let mut quotients = vec![0u64; src.len()];
+            unsafe { divide_slice(divider, &src, &mut quotients) };
+            let expected: Vec<u64> = src.iter().map(|n| n / d).collect();
+            assert_eq!(quotients, expected);
+            let mut remainders = src.clone();
+            unsafe { mod_slice_in_place(divider, d, &mut remainders) };
+            let expected: Vec<u64> = src.iter().map(|n| n % d).collect();
+            assert_eq!(remainders, expected);
+        }
+    }
+}
@@ -0,0 +1,224 @@
+//! `wide` implementation of the slice kernels, behind the `wide` feature.
+//!
+//! Unlike `core::simd`, `wide` builds on a stable compiler, picking SSE2,
+//! AVX2, NEON or SIMD128 at compile time. As in the `simd` backend, the high
+//! multiplication of 64-bit lanes is emulated with four 32x32 bits multiplications.
+//!
+//! `u64x4` can also be divided directly with `/`:
+//!
+//! ```
+//! use fastdivide::DividerU64;
+//! use wide::u64x4;
+//!
+//! let divider = DividerU64::divide_by(7);
+//! let quotients = u64x4::new([6, 7, 100, u64::MAX]) / divider;
+//! assert_eq!(quotients.to_array(), [0, 1, 14, u64::MAX / 7]);
+//! ```
+
+use core::ops::{Div, DivAssign};
+
+use wide::u64x4;
+
+use crate::DividerU64;
+
+const LANES: usize = 4;
+
+const LOW_MASK: u64 = u32::MAX as u64;
+
+/// A 64-bits scalar factor, split into its 32-bits halves
+/// once and for all.
+#[derive(Clone, Copy)]
+struct SplitFactor {
+    lo: u64x4,
+    hi: u64x4,
+}
+
+impl SplitFactor {
+    #[inline(always)]
+    fn new(factor: u64) -> SplitFactor {
+        SplitFactor {
+            lo: u64x4::splat(factor & LOW_MASK),
+            hi: u64x4::splat(factor >> 32),
+        }
+    }
+
+    /// Returns the high 64 bits of the product of each lane with the factor.
+    #[inline(always)]
+    fn mullhi(self, n: u64x4) -> u64x4 {
+        let mask = u64x4::splat(LOW_MASK);
+        let n_lo = n & mask;
+        let n_hi = n >> 32;
+        let lo_lo = self.lo * n_lo;
+        let hi_lo = self.hi * n_lo;
+        let lo_hi = self.lo * n_hi;
+        let hi_hi = self.hi * n_hi;
+        // None of these additions can overflow.
+        let cross = (lo_lo >> 32) + (hi_lo & mask) + lo_hi;
+        hi_hi + (hi_lo >> 32) + (cross >> 32)
+    }
+}
+
+/// Runs `$body` with `$divide` bound to a closure dividing `LANES` values
+/// by `$divider`, specialized for the algorithm of the divider.
+macro_rules! with_wide_divide_fn {
+    ($divider:expr, $divide:ident => $body:expr) => {
+        match $divider {
+            DividerU64::Fast { magic, shift } => {
+                let magic = SplitFactor::new(magic);
+                let $divide = move |n: u64x4| magic.mullhi(n) >> shift;
+                $body
+            }
+            DividerU64::BitShift(shift) => {
+                let $divide = move |n: u64x4| n >> shift;
+                $body
+            }
+            DividerU64::Identity => {
+                let $divide = |n: u64x4| n;
+                $body
+            }
+            DividerU64::Compare(divisor) => {
+                let divisor = u64x4::splat(divisor);
+                let $divide = move |n: u64x4| divisor.cmp_gt(n).blend(u64x4::ZERO, u64x4::ONE);
+                $body
+            }
+            DividerU64::PreShift {
+                magic,
+                pre_shift,
+                shift,
+            } => {
+                let magic = SplitFactor::new(magic);
+                let $divide = move |n: u64x4| magic.mullhi(n >> pre_shift) >> shift;
+                $body
+            }
+            DividerU64::General { magic_low, shift } => {
+                let magic_low = SplitFactor::new(magic_low);
+                let $divide = move |n: u64x4| {
+                    let q = magic_low.mullhi(n);
+                    (((n - q) >> 1) + q) >> shift
+                };
+                $body
+            }
+        }
+    };
+}
+
+impl Div<DividerU64> for u64x4 {
+    type Output = u64x4;
+
+    #[inline]
+    fn div(self, denom: DividerU64) -> u64x4 {
+        with_wide_divide_fn!(denom, divide => divide(self))
+    }
+}
+
+impl DivAssign<DividerU64> for u64x4 {
+    #[inline]
+    fn div_assign(&mut self, denom: DividerU64) {
+        *self = *self / denom;
+    }
+}
+
+#[inline(always)]
+fn load(values: &[u64]) -> u64x4 {
+    let mut lanes = [0u64; LANES];
+    lanes.copy_from_slice(values);
+    u64x4::new(lanes)
+}
+
+#[inline(always)]
+fn map_slice(
+    f: impl Fn(u64x4) -> u64x4,
+    f_scalar: impl Fn(u64) -> u64,
+    src: &[u64],
+    dst: &mut [u64],
+) {
+    assert_eq!(src.len(), dst.len());
+    let mut src_chunks = src.chunks_exact(LANES);
+    let mut dst_chunks = dst.chunks_exact_mut(LANES);
+    for (src_chunk, dst_chunk) in (&mut src_chunks).zip(&mut dst_chunks) {
+        dst_chunk.copy_from_slice(f(load(src_chunk)).as_array_ref());
+    }
+    let src_tail = src_chunks.remainder();
+    for (dst_val, &src_val) in dst_chunks.into_remainder().iter_mut().zip(src_tail) {
+        *dst_val = f_scalar(src_val);
+    }
+}
+
+#[inline(always)]
+fn map_slice_in_place(
+    f: impl Fn(u64x4) -> u64x4,
+    f_scalar: impl Fn(u64) -> u64,
+    values: &mut [u64],
+) {
+    let mut chunks = values.chunks_exact_mut(LANES);
+    for chunk in &mut chunks {
+        let res = f(load(chunk));
+        chunk.copy_from_slice(res.as_array_ref());
+    }
+    for val in chunks.into_remainder() {
+        *val = f_scalar(*val);
+    }
+}
+
+pub(crate) fn divide_slice(divider: DividerU64, src: &[u64], dst: &mut [u64]) {
+    with_wide_divide_fn!(divider, divide => map_slice(divide, |n| divider.divide(n), src, dst))
+}
+
+pub(crate) fn divide_slice_in_place(divider: DividerU64, values: &mut [u64]) {
+    with_wide_divide_fn!(divider, divide => map_slice_in_place(divide, |n| divider.divide(n), values))
+}
+
+pub(crate) fn mod_slice(divider: DividerU64, divisor: u64, src: &[u64], dst: &mut [u64]) {
+    let divisors = u64x4::splat(divisor);
+    with_wide_divide_fn!(divider, divide => map_slice(
+        |n| n - divide(n) * divisors,
+        |n| n - divider.divide(n) * divisor,
+        src,
+        dst,
+    ))
+}
+
+pub(crate) fn mod_slice_in_place(divider: DividerU64, divisor: u64, values: &mut [u64]) {
+    let divisors = u64x4::splat(divisor);
+    with_wide_divide_fn!(divider, divide => map_slice_in_place(
+        |n| n - divide(n) * divisors,
+        |n| n - divider.divide(n) * divisor,
+        values,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SplitFactor;
+    use crate::DividerU64;
+    use wide::u64x4;
+
+    #[test]
+    fn test_mullhi() {
+        let factors = [0u64, 1, 7, u32::MAX as u64, 1 << 32, u64::MAX - 1, u64::MAX];
+        for &factor in &factors {
+            for n in factors.chunks(4) {
+                let mut lanes = [u64::MAX; 4];
+                lanes[..n.len()].copy_from_slice(n);
+                let res = SplitFactor::new(factor).mullhi(u64x4::new(lanes));
+                for (&n, &res) in lanes.iter().zip(res.as_array_ref()) {
+                    assert_eq!(res, ((factor as u128 * n as u128) >> 64) as u64);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_div_u64x4() {
+        let n = [0u64, 7, 1 << 63, u64::MAX];
+        for d in (1u64..100)
+            .chain((0..64).map(|i| 1 << i))
+            .chain([(1 << 63) + 1, u64::MAX])
+        {
+            let divider = DividerU64::divide_by(d);
+            let mut quotients = u64x4::new(n);
+            quotients /= divider;
+            assert_eq!(quotients.to_array(), n.map(|n| n / d));
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use crate::BranchFreeDividerU64;
+
+/// Divides 4 numerators by 4 different divisors at once.
+///
+/// The divisors are stored as a structure of arrays of branchfree dividers
+/// (see [`BranchFreeDividerU64`]): all of the lanes execute the same
+/// instruction sequence, which maps directly onto SIMD instructions.
+/// When compiled with the `avx2` target feature, `divide` uses AVX2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DividerU64x4 {
+    magic: [u64; 4],
+    half_shift: [u64; 4],
+    shift: [u64; 4],
+}
+
+impl DividerU64x4 {
+    /// Precomputes a divider for each of the `divisors`.
+    ///
+    /// Panics if one of the divisors is 0.
+    pub fn divide_by(divisors: [u64; 4]) -> DividerU64x4 {
+        DividerU64x4::from_dividers(divisors.map(BranchFreeDividerU64::divide_by))
+    }
+
+    pub fn from_dividers(dividers: [BranchFreeDividerU64; 4]) -> DividerU64x4 {
+        DividerU64x4 {
+            magic: dividers.map(|divider| divider.magic),
+            half_shift: dividers.map(|divider| divider.half_shift as u64),
+            shift: dividers.map(|divider| divider.shift as u64),
+        }
+    }
+
+    /// Divides each numerator by the divisor of the same lane.
+    #[inline]
+    pub fn divide(&self, n: [u64; 4]) -> [u64; 4] {
+        #[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+        return unsafe { self.divide_avx2(n) };
+        #[cfg(not(all(target_arch = "x86_64", target_feature = "avx2")))]
+        self.divide_scalar(n)
+    }
+
+    #[cfg(any(not(all(target_arch = "x86_64", target_feature = "avx2")), test))]
+    #[inline(always)]
+    fn divide_scalar(&self, n: [u64; 4]) -> [u64; 4] {
+        let mut quotients = [0u64; 4];
+        for i in 0..4 {
+            let q = crate::libdivide_mullhi_u64(self.magic[i], n[i]);
+            let t = ((n[i] - q) >> self.half_shift[i]).wrapping_add(q);
+            quotients[i] = t >> self.shift[i];
+        }
+        quotients
+    }
+
+    #[cfg(all(target_arch = "x86_64", any(target_feature = "avx2", test)))]
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn divide_avx2(&self, n: [u64; 4]) -> [u64; 4] {
+        use core::arch::x86_64::*;
+        let load = |vals: &[u64; 4]| _mm256_loadu_si256(vals.as_ptr() as *const __m256i);
+        let n = load(&n);
+        let magic = load(&self.magic);
+        let magic_lo = _mm256_and_si256(magic, _mm256_set1_epi64x(u32::MAX as i64));
+        let magic_hi = _mm256_srli_epi64::<32>(magic);
+        let q = crate::avx2::mullhi(magic_lo, magic_hi, n);
+        let t = _mm256_add_epi64(
+            _mm256_srlv_epi64(_mm256_sub_epi64(n, q), load(&self.half_shift)),
+            q,
+        );
+        let mut quotients = [0u64; 4];
+        _mm256_storeu_si256(
+            quotients.as_mut_ptr() as *mut __m256i,
+            _mm256_srlv_epi64(t, load(&self.shift)),
+        );
+        quotients
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DividerU64x4;
+    use proptest::prelude::*;
+
+    fn check(divisors: [u64; 4], n: [u64; 4]) {
+        let divider = DividerU64x4::divide_by(divisors);
+        let expected = [0, 1, 2, 3].map(|i| n[i] / divisors[i]);
+        assert_eq!(divider.divide_scalar(n), expected);
+        assert_eq!(divider.divide(n), expected);
+        #[cfg(target_arch = "x86_64")]
+        if crate::avx2::is_available() {
+            assert_eq!(unsafe { divider.divide_avx2(n) }, expected);
+        }
+    }
+
+    #[test]
+    fn test_divider_x4() {
+        let numerators = [0u64, 1, 7, 1000, 1 << 63, u64::MAX - 1, u64::MAX];
+        for d in (1u64..100).chain((0..64).map(|i| 1 << i)) {
+            for &n in &numerators {
+                check([d, 1, 7, u64::MAX], [n; 4]);
+                check([3, d, (1 << 63) + 1, 14], [n, n / 2, u64::MAX, n]);
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_divider_x4_proptest(divisors in proptest::array::uniform4(1u64..), n: [u64; 4]) {
+            check(divisors, n);
+        }
+    }
+}